@@ -0,0 +1,261 @@
+//! Command-line SQL over CSV files
+//!
+//! ```text
+//! datafusion-csv [OPTIONS] [PATH]... [-e SQL]
+//! ```
+//!
+//! Each PATH is registered as a table named after its file or directory
+//! (`data/users.csv` becomes `users`); use `--table NAME=PATH` to choose the
+//! name. The query comes from `-e` or, if omitted, from standard input.
+
+use std::io::{IsTerminal, Read, Write};
+use std::process::ExitCode;
+
+use arrow::record_batch::RecordBatch;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::prelude::SessionContext;
+use datafusion_csv::{CsvFormatOptions, SessionContextCsvExt};
+
+const USAGE: &str = "\
+Usage: datafusion-csv [OPTIONS] [PATH]... [-e SQL]
+
+Registers each CSV file or directory as a table and runs SQL against them.
+Tables are named after the file or directory (data/users.csv -> users).
+Without -e, the query is read from standard input.
+
+Options:
+  -e, --execute SQL        Query to run
+  -t, --table NAME=PATH    Register PATH as table NAME
+  -f, --format FORMAT      Output format: table (default), csv or json
+  -d, --delimiter CHAR     Field delimiter (default: ,); use \\t for tabs
+      --no-header          Files have no header row
+  -h, --help               Show this help";
+
+/// Output format for query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Parsed command-line arguments
+#[derive(Debug, PartialEq)]
+struct Args {
+    tables: Vec<(String, String)>,
+    sql: Option<String>,
+    format: OutputFormat,
+    delimiter: u8,
+    has_header: bool,
+}
+
+impl Args {
+    /// Parse arguments, excluding the program name; `Ok(None)` means help was requested
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>> {
+        let mut parsed = Args {
+            tables: Vec::new(),
+            sql: None,
+            format: OutputFormat::Table,
+            delimiter: b',',
+            has_header: true,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next().ok_or_else(|| {
+                    DataFusionError::Configuration(format!("{} needs a value", name))
+                })
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-e" | "--execute" => parsed.sql = Some(value(&arg)?),
+                "-t" | "--table" => {
+                    let spec = value(&arg)?;
+                    let (name, path) = spec.split_once('=').ok_or_else(|| {
+                        DataFusionError::Configuration(format!(
+                            "--table expects NAME=PATH, got '{}'",
+                            spec
+                        ))
+                    })?;
+                    parsed.tables.push((name.to_string(), path.to_string()));
+                }
+                "-f" | "--format" => {
+                    parsed.format = match value(&arg)?.as_str() {
+                        "table" => OutputFormat::Table,
+                        "csv" => OutputFormat::Csv,
+                        "json" => OutputFormat::Json,
+                        other => {
+                            return Err(DataFusionError::Configuration(format!(
+                                "Unknown output format '{}'; expected table, csv or json",
+                                other
+                            )));
+                        }
+                    }
+                }
+                "-d" | "--delimiter" => {
+                    parsed.delimiter = match value(&arg)?.as_str() {
+                        "\\t" | "\t" => b'\t',
+                        d if d.len() == 1 => d.as_bytes()[0],
+                        d => {
+                            return Err(DataFusionError::Configuration(format!(
+                                "Delimiter must be a single byte, got '{}'",
+                                d
+                            )));
+                        }
+                    }
+                }
+                "--no-header" => parsed.has_header = false,
+                flag if flag.starts_with('-') && flag.len() > 1 => {
+                    return Err(DataFusionError::Configuration(format!(
+                        "Unknown option '{}'",
+                        flag
+                    )));
+                }
+                path => parsed.tables.push((table_name(path), path.to_string())),
+            }
+        }
+        Ok(Some(parsed))
+    }
+}
+
+/// Derive a SQL-friendly table name from a file or directory path
+fn table_name(path: &str) -> String {
+    let last = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path);
+    let stem = last.split('.').next().unwrap_or(last);
+    let name: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("t_{}", name)
+    } else {
+        name
+    }
+}
+
+/// Write query results to `out` in `format`
+fn write_batches(out: &mut dyn Write, batches: &[RecordBatch], format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Table => {
+            let table = arrow::util::pretty::pretty_format_batches(batches)?;
+            writeln!(out, "{}", table)?;
+        }
+        OutputFormat::Csv => {
+            let mut writer = arrow::csv::Writer::new(out);
+            for batch in batches {
+                writer.write(batch)?;
+            }
+        }
+        OutputFormat::Json => {
+            let mut writer = arrow::json::ArrayWriter::new(out);
+            for batch in batches {
+                writer.write(batch)?;
+            }
+            writer.finish()?;
+            writeln!(writer.into_inner())?;
+        }
+    }
+    Ok(())
+}
+
+async fn run(args: Args) -> Result<()> {
+    let sql = match args.sql {
+        Some(sql) => sql,
+        None if std::io::stdin().is_terminal() => {
+            return Err(DataFusionError::Configuration(
+                "No query given; pass -e SQL or pipe it on standard input".to_string(),
+            ));
+        }
+        None => {
+            let mut sql = String::new();
+            std::io::stdin().read_to_string(&mut sql)?;
+            sql
+        }
+    };
+
+    let ctx = SessionContext::new();
+    let options = CsvFormatOptions::new()
+        .with_delimiter(args.delimiter)
+        .with_has_header(args.has_header);
+    for (name, path) in &args.tables {
+        ctx.register_csv_with_options(name, path, options.clone()).await?;
+    }
+
+    let batches = ctx.sql(&sql).await?.collect().await?;
+    let mut stdout = std::io::stdout().lock();
+    write_batches(&mut stdout, &batches, args.format)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>> {
+        Args::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() -> Result<()> {
+        let args = parse(&[
+            "data/users.csv",
+            "--table",
+            "o=data/2024 orders/",
+            "-f",
+            "json",
+            "-d",
+            "\\t",
+            "--no-header",
+            "-e",
+            "SELECT 1",
+        ])?
+        .unwrap();
+        assert_eq!(
+            args.tables,
+            vec![
+                ("users".to_string(), "data/users.csv".to_string()),
+                ("o".to_string(), "data/2024 orders/".to_string()),
+            ]
+        );
+        assert_eq!(args.format, OutputFormat::Json);
+        assert_eq!(args.delimiter, b'\t');
+        assert!(!args.has_header);
+        assert_eq!(args.sql.as_deref(), Some("SELECT 1"));
+
+        assert!(parse(&["--help"])?.is_none());
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["-e"]).is_err());
+        assert_eq!(table_name("exports/2024-01.csv.gz"), "t_2024_01");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! Metadata caching shared across queries and tables
+//!
+//! File listings and per-file statistics are cached through DataFusion's
+//! runtime [`CacheManager`](datafusion::execution::cache::cache_manager::CacheManager);
+//! [`csv_cache_manager_config`] enables both. DataFusion has no slot for
+//! inferred schemas, so those are cached by a [`CsvSchemaCache`] installed as a
+//! session config extension. All tables in the session share these caches, so
+//! two tables over the same prefix list it and infer its schema only once.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use arrow_schema::SchemaRef;
+use chrono::{DateTime, Utc};
+use datafusion::execution::cache::cache_manager::CacheManagerConfig;
+use datafusion::execution::cache::cache_unit::{DefaultFileStatisticsCache, DefaultListFilesCache};
+use datafusion::execution::object_store::ObjectStoreUrl;
+use object_store::path::Path;
+use object_store::ObjectMeta;
+
+use crate::file_format::CsvFormatOptions;
+
+/// Cache manager configuration with file listing and statistics caches enabled
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use datafusion::execution::runtime_env::RuntimeEnvBuilder;
+/// use datafusion::prelude::{SessionConfig, SessionContext};
+/// use datafusion_csv::{csv_cache_manager_config, CsvSchemaCache};
+///
+/// # fn main() -> datafusion::error::Result<()> {
+/// let runtime = RuntimeEnvBuilder::new()
+///     .with_cache_manager(csv_cache_manager_config())
+///     .build_arc()?;
+/// let config = SessionConfig::new().with_extension(Arc::new(CsvSchemaCache::new()));
+/// let ctx = SessionContext::new_with_config_rt(config, runtime);
+/// # Ok(())
+/// # }
+/// ```
+pub fn csv_cache_manager_config() -> CacheManagerConfig {
+    CacheManagerConfig::default()
+        .with_list_files_cache(Some(Arc::new(DefaultListFilesCache::default())))
+        .with_files_statistics_cache(Some(Arc::new(DefaultFileStatisticsCache::default())))
+}
+
+/// Cache of inferred schemas, keyed by object store URL and file location
+///
+/// An entry is reused only while the file's size, last-modified time and
+/// ETag (when the store reports one) are unchanged and it was inferred with
+/// the same format options. Remote files are then re-registered without
+/// downloading them for inference again.
+#[derive(Debug, Default)]
+pub struct CsvSchemaCache {
+    entries: Mutex<HashMap<(ObjectStoreUrl, Path), SchemaCacheEntry>>,
+}
+
+#[derive(Debug)]
+struct SchemaCacheEntry {
+    size: usize,
+    last_modified: DateTime<Utc>,
+    e_tag: Option<String>,
+    options_key: String,
+    schema: SchemaRef,
+}
+
+impl CsvSchemaCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached schemas
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all cached schemas
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Look up the schema inferred for `meta` in `store_url` with `options`,
+    /// if still valid
+    pub(crate) fn get(
+        &self,
+        store_url: &ObjectStoreUrl,
+        meta: &ObjectMeta,
+        options: &CsvFormatOptions,
+    ) -> Option<SchemaRef> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(&(store_url.clone(), meta.location.clone()))
+            .filter(|entry| {
+                entry.size == meta.size
+                    && entry.last_modified == meta.last_modified
+                    && entry.e_tag == meta.e_tag
+                    && entry.options_key == options_key(options)
+            })
+            .map(|entry| entry.schema.clone())
+    }
+
+    /// Remember the schema inferred for `meta` in `store_url` with `options`
+    pub(crate) fn put(
+        &self,
+        store_url: &ObjectStoreUrl,
+        meta: &ObjectMeta,
+        options: &CsvFormatOptions,
+        schema: SchemaRef,
+    ) {
+        let entry = SchemaCacheEntry {
+            size: meta.size,
+            last_modified: meta.last_modified,
+            e_tag: meta.e_tag.clone(),
+            options_key: options_key(options),
+            schema,
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .insert((store_url.clone(), meta.location.clone()), entry);
+    }
+}
+
+/// Options affecting inference, rendered for comparison
+///
+/// Only fields inference reads are included, so runtime state such as rows
+/// buffered in a dead-letter sink neither invalidates entries nor gets
+/// formatted on every lookup. Maps are sorted first since their debug output
+/// follows hash order. Closures print only their declared types, which is
+/// all inference uses.
+fn options_key(options: &CsvFormatOptions) -> String {
+    let o = options;
+    format!(
+        "{:?}",
+        (
+            (o.has_header, o.delimiter, o.escape, o.terminator, o.newlines_in_values, o.compression),
+            (o.whitespace_delimited, o.collapse_delimiters, o.control_chars, o.max_fields),
+            (o.preamble_rows, o.skip_rows_after_header, o.schema_infer_max_rec, o.two_pass),
+            (o.header_type_hints, o.type_promotion, o.narrow_integers, o.decimal_inference),
+            (o.epoch_timestamps, &o.timestamp_format, sorted(&o.column_timestamp_formats)),
+            (o.integer_overflow_policy, o.decimal_separator, o.thousands_separator, &o.currency_symbols),
+            (&o.null_values, sorted(&o.column_null_values), &o.null_regex),
+            (&o.true_values, &o.false_values),
+            (sorted(&o.value_transforms), sorted(&o.column_parsers)),
+            (sorted(&o.categorical_columns), sorted(&o.binary_columns)),
+        )
+    )
+}
+
+fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> BTreeMap<&K, &V> {
+    map.iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dead_letter::{DeadLetterSink, RejectedRow};
+    use arrow_schema::{DataType, Field, Schema};
+
+    #[test]
+    fn test_schema_cache_etag() {
+        let cache = CsvSchemaCache::new();
+        let store_url = ObjectStoreUrl::parse("s3://exports").unwrap();
+        let options = CsvFormatOptions::default();
+        let meta = ObjectMeta {
+            location: Path::from("exports/daily.csv"),
+            last_modified: DateTime::default(),
+            size: 1024,
+            e_tag: Some("\"v1\"".to_string()),
+            version: None,
+        };
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, true)]));
+        cache.put(&store_url, &meta, &options, schema.clone());
+        assert_eq!(cache.get(&store_url, &meta, &options), Some(schema));
+
+        // Rewritten in place with the same size and timestamp, e.g. by a server
+        // that sends no Last-Modified header
+        let rewritten = ObjectMeta {
+            e_tag: Some("\"v2\"".to_string()),
+            ..meta
+        };
+        assert_eq!(cache.get(&store_url, &rewritten, &options), None);
+    }
+
+    #[test]
+    fn test_schema_cache_key() {
+        let cache = CsvSchemaCache::new();
+        let options = CsvFormatOptions::default();
+        let meta = ObjectMeta {
+            location: Path::from("2024/orders.csv"),
+            last_modified: DateTime::default(),
+            size: 1024,
+            e_tag: None,
+            version: None,
+        };
+        let prod = ObjectStoreUrl::parse("s3://prod").unwrap();
+        let staging = ObjectStoreUrl::parse("s3://staging").unwrap();
+        let prod_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, true)]));
+        let staging_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, true)]));
+        cache.put(&prod, &meta, &options, prod_schema.clone());
+        assert_eq!(cache.get(&staging, &meta, &options), None);
+        cache.put(&staging, &meta, &options, staging_schema.clone());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&prod, &meta, &options), Some(prod_schema.clone()));
+        assert_eq!(cache.get(&staging, &meta, &options), Some(staging_schema));
+
+        // Rows buffered in a dead-letter sink don't affect inference
+        let sink = DeadLetterSink::in_memory();
+        let with_sink = options.clone().with_dead_letter(sink.clone());
+        assert_eq!(cache.get(&prod, &meta, &with_sink), Some(prod_schema.clone()));
+        sink.push(RejectedRow {
+            file: "2024/orders.csv".to_string(),
+            line: Some(2),
+            error: "bad row".to_string(),
+            raw: "x,y".to_string(),
+        })
+        .unwrap();
+        assert_eq!(cache.get(&prod, &meta, &with_sink), Some(prod_schema));
+
+        // Options inference reads still do
+        let semicolons = options.with_delimiter(b';');
+        assert_eq!(cache.get(&prod, &meta, &semicolons), None);
+    }
+}
@@ -0,0 +1,718 @@
+//! Object stores for remote URLs
+//!
+//! Stores for `http://` and `https://` URLs are built from an [`HttpConfig`],
+//! set per table with [`CsvFormatOptions::with_http_config`].
+//!
+//! Paths on `s3://`, `gs://` and `az://`/`abfss://` are read through an
+//! object store built from a config type such as [`S3Config`]. Tables
+//! registered with an explicit config use it; otherwise a store configured
+//! from the environment (e.g. `AWS_REGION`, `GOOGLE_APPLICATION_CREDENTIALS`,
+//! `AZURE_STORAGE_CONNECTION_STRING`) is registered on first use, like the
+//! automatic HTTP store. Each backend needs its cargo feature (`s3`, `gcs`,
+//! `azure`).
+//!
+//! [`CsvFormatOptions::with_http_config`]: crate::CsvFormatOptions::with_http_config
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use datafusion::execution::object_store::ObjectStoreUrl;
+use object_store::ObjectStore;
+use url::Url;
+
+/// Client settings for the store registered for `http://` and `https://` URLs
+///
+/// Failed requests, including 5xx responses and dropped connections, are
+/// retried with exponential backoff until `max_retries` or `retry_timeout`
+/// runs out.
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    /// Headers sent with every request, e.g. `Authorization` or an API key (default: none)
+    pub headers: Vec<(String, String)>,
+    /// Time allowed for each request, including reading the response (default: 30 seconds)
+    pub timeout: Option<Duration>,
+    /// Time allowed to establish a connection (default: 5 seconds)
+    pub connect_timeout: Option<Duration>,
+    /// Retries of a failed request before the query fails (default: 10)
+    pub max_retries: Option<usize>,
+    /// Total time spent retrying one request (default: 3 minutes)
+    pub retry_timeout: Option<Duration>,
+    /// Wait before the first retry, doubled on each retry (default: 100 milliseconds)
+    pub initial_backoff: Option<Duration>,
+    /// Longest wait between retries (default: 15 seconds)
+    pub max_backoff: Option<Duration>,
+}
+
+impl HttpConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `name: value` with every request
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Authenticate with `Authorization: Bearer <token>`
+    pub fn with_bearer_token(self, token: impl AsRef<str>) -> Self {
+        let value = format!("Bearer {}", token.as_ref());
+        self.with_header("Authorization", value)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Retry a failed request up to `max_retries` times; 0 fails on the first error
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn with_retry_timeout(mut self, timeout: Duration) -> Self {
+        self.retry_timeout = Some(timeout);
+        self
+    }
+
+    /// Wait `initial` before the first retry, doubling up to `max`
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = Some(initial);
+        self.max_backoff = Some(max);
+        self
+    }
+
+    /// Build a store for `base_url` (scheme + host + port)
+    #[cfg(feature = "http")]
+    pub fn build(&self, base_url: &str) -> Result<Arc<dyn ObjectStore>> {
+        let mut client_options = object_store::ClientOptions::new().with_allow_http(base_url.starts_with("http://"));
+        if let Some(timeout) = self.timeout {
+            client_options = client_options.with_timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            client_options = client_options.with_connect_timeout(timeout);
+        }
+        if !self.headers.is_empty() {
+            client_options = client_options.with_default_headers(self.header_map()?);
+        }
+
+        let mut retry = object_store::RetryConfig::default();
+        if let Some(max_retries) = self.max_retries {
+            retry.max_retries = max_retries;
+        }
+        if let Some(timeout) = self.retry_timeout {
+            retry.retry_timeout = timeout;
+        }
+        if let Some(backoff) = self.initial_backoff {
+            retry.backoff.init_backoff = backoff;
+        }
+        if let Some(backoff) = self.max_backoff {
+            retry.backoff.max_backoff = backoff;
+        }
+
+        let store = object_store::http::HttpBuilder::new()
+            .with_url(base_url)
+            .with_client_options(client_options)
+            .with_retry(retry)
+            .build()
+            .map_err(|e| DataFusionError::Execution(format!("Failed to create HTTP object store: {}", e)))?;
+        Ok(Arc::new(store))
+    }
+
+    #[cfg(feature = "http")]
+    fn header_map(&self) -> Result<http::HeaderMap> {
+        let mut headers = http::HeaderMap::new();
+        for (name, value) in &self.headers {
+            let invalid = |e: &dyn std::fmt::Display| {
+                DataFusionError::Configuration(format!("Invalid HTTP header '{}': {}", name, e))
+            };
+            let name = http::HeaderName::try_from(name.as_str()).map_err(|e| invalid(&e))?;
+            let mut value = http::HeaderValue::try_from(value.as_str()).map_err(|e| invalid(&e))?;
+            // Keeps credentials out of debug output and HTTP/2 header compression
+            value.set_sensitive(name == http::header::AUTHORIZATION);
+            headers.append(name, value);
+        }
+        Ok(headers)
+    }
+
+    /// Without the `http` feature, URLs are readable only through a store the caller registered
+    #[cfg(not(feature = "http"))]
+    pub fn build(&self, base_url: &str) -> Result<Arc<dyn ObjectStore>> {
+        Err(missing_feature("http", base_url))
+    }
+}
+
+/// Connection settings for Amazon S3 and S3-compatible stores
+///
+/// Unset fields fall back to the standard `AWS_*` environment variables, so
+/// `S3Config::new()` behaves like the AWS CLI.
+#[derive(Debug, Clone, Default)]
+pub struct S3Config {
+    /// Region of the bucket, e.g. `us-east-1` (default: `AWS_REGION`)
+    pub region: Option<String>,
+    /// Access key ID (default: `AWS_ACCESS_KEY_ID`)
+    pub access_key_id: Option<String>,
+    /// Secret access key (default: `AWS_SECRET_ACCESS_KEY`)
+    pub secret_access_key: Option<String>,
+    /// Session token of temporary credentials (default: `AWS_SESSION_TOKEN`)
+    pub session_token: Option<String>,
+    /// Endpoint of an S3-compatible store such as MinIO (default: AWS)
+    pub endpoint: Option<String>,
+    /// Allow a plain `http://` endpoint (default: false)
+    pub allow_http: bool,
+}
+
+impl S3Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// Set static credentials
+    pub fn with_credentials(mut self, access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        self.access_key_id = Some(access_key_id.into());
+        self.secret_access_key = Some(secret_access_key.into());
+        self
+    }
+
+    pub fn with_session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+
+    /// Read from an S3-compatible store at `endpoint` instead of AWS
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn with_allow_http(mut self, allow_http: bool) -> Self {
+        self.allow_http = allow_http;
+        self
+    }
+
+    /// Build a store for `bucket`, e.g. to pass to `CsvSourceBuilder::with_object_store`
+    #[cfg(feature = "s3")]
+    pub fn build(&self, bucket: &str) -> Result<Arc<dyn ObjectStore>> {
+        let mut builder = object_store::aws::AmazonS3Builder::from_env().with_bucket_name(bucket);
+        if let Some(region) = &self.region {
+            builder = builder.with_region(region);
+        }
+        if let Some(access_key_id) = &self.access_key_id {
+            builder = builder.with_access_key_id(access_key_id);
+        }
+        if let Some(secret_access_key) = &self.secret_access_key {
+            builder = builder.with_secret_access_key(secret_access_key);
+        }
+        if let Some(token) = &self.session_token {
+            builder = builder.with_token(token);
+        }
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        let store = builder.with_allow_http(self.allow_http).build().map_err(|e| {
+            DataFusionError::Configuration(format!("Failed to create S3 object store for '{}': {}", bucket, e))
+        })?;
+        Ok(Arc::new(store))
+    }
+
+    /// Without the `s3` feature, S3 is readable only through a store the caller registered
+    #[cfg(not(feature = "s3"))]
+    pub fn build(&self, bucket: &str) -> Result<Arc<dyn ObjectStore>> {
+        Err(missing_feature("s3", &format!("s3://{}", bucket)))
+    }
+}
+
+/// Connection settings for Google Cloud Storage
+///
+/// Unset fields fall back to the `GOOGLE_*` environment variables; with no
+/// credentials at all, Application Default Credentials are used (the
+/// `gcloud auth application-default login` file, or the metadata server on GCP).
+#[derive(Debug, Clone, Default)]
+pub struct GcsConfig {
+    /// Path of a service account JSON key file (default: `GOOGLE_SERVICE_ACCOUNT`)
+    pub service_account_path: Option<String>,
+    /// Contents of a service account JSON key (default: `GOOGLE_SERVICE_ACCOUNT_KEY`)
+    pub service_account_key: Option<String>,
+    /// Path of an Application Default Credentials file (default: `GOOGLE_APPLICATION_CREDENTIALS`)
+    pub application_credentials: Option<String>,
+}
+
+impl GcsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_service_account_path(mut self, path: impl Into<String>) -> Self {
+        self.service_account_path = Some(path.into());
+        self
+    }
+
+    pub fn with_service_account_key(mut self, key: impl Into<String>) -> Self {
+        self.service_account_key = Some(key.into());
+        self
+    }
+
+    pub fn with_application_credentials(mut self, path: impl Into<String>) -> Self {
+        self.application_credentials = Some(path.into());
+        self
+    }
+
+    /// Build a store for `bucket`, e.g. to pass to `CsvSourceBuilder::with_object_store`
+    #[cfg(feature = "gcs")]
+    pub fn build(&self, bucket: &str) -> Result<Arc<dyn ObjectStore>> {
+        let mut builder = object_store::gcp::GoogleCloudStorageBuilder::from_env().with_bucket_name(bucket);
+        if let Some(path) = &self.service_account_path {
+            builder = builder.with_service_account_path(path);
+        }
+        if let Some(key) = &self.service_account_key {
+            builder = builder.with_service_account_key(key);
+        }
+        if let Some(path) = &self.application_credentials {
+            builder = builder.with_application_credentials(path);
+        }
+        let store = builder.build().map_err(|e| {
+            DataFusionError::Configuration(format!("Failed to create GCS object store for '{}': {}", bucket, e))
+        })?;
+        Ok(Arc::new(store))
+    }
+
+    /// Without the `gcs` feature, GCS is readable only through a store the caller registered
+    #[cfg(not(feature = "gcs"))]
+    pub fn build(&self, bucket: &str) -> Result<Arc<dyn ObjectStore>> {
+        Err(missing_feature("gcs", &format!("gs://{}", bucket)))
+    }
+}
+
+/// Connection settings for Azure Blob Storage and Data Lake Storage Gen2
+///
+/// Unset fields fall back to the `AZURE_*` environment variables, including
+/// `AZURE_STORAGE_CONNECTION_STRING`; with no key, connection string or SAS,
+/// managed identity is used.
+#[derive(Debug, Clone, Default)]
+pub struct AzureConfig {
+    /// Storage account name (default: `AZURE_STORAGE_ACCOUNT_NAME`, or the host of an `abfss://` URL)
+    pub account: Option<String>,
+    /// Storage account key (default: `AZURE_STORAGE_ACCOUNT_KEY`)
+    pub access_key: Option<String>,
+    /// Connection string as shown in the Azure portal (default: `AZURE_STORAGE_CONNECTION_STRING`)
+    pub connection_string: Option<String>,
+    /// Shared access signature query string, with or without the leading `?` (default: `AZURE_STORAGE_SAS_KEY`)
+    pub sas_token: Option<String>,
+}
+
+impl AzureConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_account(mut self, account: impl Into<String>) -> Self {
+        self.account = Some(account.into());
+        self
+    }
+
+    /// Authenticate with the storage account key
+    pub fn with_access_key(mut self, access_key: impl Into<String>) -> Self {
+        self.access_key = Some(access_key.into());
+        self
+    }
+
+    /// Authenticate with a shared access signature such as `sv=...&sig=...`
+    pub fn with_sas_token(mut self, sas_token: impl Into<String>) -> Self {
+        self.sas_token = Some(sas_token.into());
+        self
+    }
+
+    /// Take the account, key, SAS token and endpoint from a connection string
+    pub fn with_connection_string(mut self, connection_string: impl Into<String>) -> Self {
+        self.connection_string = Some(connection_string.into());
+        self
+    }
+
+    /// Build a store for `container`, e.g. to pass to `CsvSourceBuilder::with_object_store`
+    #[cfg(feature = "azure")]
+    pub fn build(&self, container: &str) -> Result<Arc<dyn ObjectStore>> {
+        let error = |e: &dyn std::fmt::Display| {
+            DataFusionError::Configuration(format!("Failed to create Azure object store for '{}': {}", container, e))
+        };
+        let mut builder = object_store::azure::MicrosoftAzureBuilder::from_env().with_container_name(container);
+        let connection_string = self
+            .connection_string
+            .clone()
+            .or_else(|| std::env::var("AZURE_STORAGE_CONNECTION_STRING").ok());
+        if let Some(connection_string) = connection_string {
+            for (key, value) in parse_connection_string(&connection_string).map_err(|e| error(&e))? {
+                builder = match key {
+                    "AccountName" => builder.with_account(value),
+                    "AccountKey" => builder.with_access_key(value),
+                    "BlobEndpoint" => builder.with_endpoint(value.to_string()),
+                    "SharedAccessSignature" => builder.with_sas_authorization(sas_pairs(value)),
+                    "UseDevelopmentStorage" => builder.with_use_emulator(value.eq_ignore_ascii_case("true")),
+                    _ => builder,
+                };
+            }
+        }
+        if let Some(account) = &self.account {
+            builder = builder.with_account(account);
+        }
+        if let Some(access_key) = &self.access_key {
+            builder = builder.with_access_key(access_key);
+        }
+        if let Some(sas_token) = &self.sas_token {
+            builder = builder.with_sas_authorization(sas_pairs(sas_token));
+        }
+        let store = builder.build().map_err(|e| error(&e))?;
+        Ok(Arc::new(store))
+    }
+
+    /// Without the `azure` feature, Azure is readable only through a store the caller registered
+    #[cfg(not(feature = "azure"))]
+    pub fn build(&self, container: &str) -> Result<Arc<dyn ObjectStore>> {
+        Err(missing_feature("azure", &format!("az://{}", container)))
+    }
+}
+
+/// Split a `Key=Value;...` connection string into its pairs
+#[cfg(feature = "azure")]
+fn parse_connection_string(connection_string: &str) -> std::result::Result<Vec<(&str, &str)>, String> {
+    connection_string
+        .split(';')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.split_once('=')
+                .ok_or_else(|| format!("connection string entry '{}' is not Key=Value", part))
+        })
+        .collect()
+}
+
+/// Decode a SAS token's query string into the pairs appended to each request
+#[cfg(feature = "azure")]
+fn sas_pairs(sas_token: &str) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(sas_token.trim_start_matches('?').as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Register a store for the bucket of `path`, a cloud URL such as `s3://bucket/key.csv`
+///
+/// A store configured from the environment is registered only if none is
+/// registered for the bucket yet. Paths with other schemes are left alone.
+pub(crate) fn register_cloud_object_store(state: &SessionState, path: &str) -> Result<()> {
+    let Some((store_url, bucket)) = bucket_url(path)? else {
+        return Ok(());
+    };
+    if let Ok(registered) = state.runtime_env().object_store(ObjectStoreUrl::parse(store_url.as_str())?) {
+        return check_container(path, &store_url, &bucket, registered.as_ref());
+    }
+    let store = match store_url.scheme() {
+        "s3" => S3Config::new().build(&bucket)?,
+        "gs" => GcsConfig::new().build(&bucket)?,
+        _ => {
+            let mut config = AzureConfig::new();
+            // `abfss://container@account.dfs.core.windows.net` names the account in its host
+            if let Some(host) = store_url.host_str().filter(|_| store_url.scheme() != "az") {
+                config = config.with_account(host.split('.').next().unwrap_or(host));
+            }
+            config.build(&bucket)?
+        }
+    };
+    state.runtime_env().register_object_store(&store_url, store);
+    Ok(())
+}
+
+/// Fail if `path` is in a different container than the Azure store registered for its account
+///
+/// DataFusion keys stores by scheme and host, which for `abfss://` URLs is the
+/// account, so a second container of the account would otherwise be read
+/// from the first one's store.
+fn check_container(path: &str, store_url: &Url, container: &str, registered: &dyn ObjectStore) -> Result<()> {
+    if !matches!(store_url.scheme(), "abfs" | "abfss") {
+        return Ok(());
+    }
+    // Azure stores, also when wrapped in a limit or listing cache, print as
+    // `MicrosoftAzure { account: .., container: .. }`; other stores are the caller's
+    let display = registered.to_string();
+    let Some(registered_container) = display
+        .split("container: ")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-')).next())
+    else {
+        return Ok(());
+    };
+    if registered_container == container {
+        return Ok(());
+    }
+    Err(DataFusionError::Configuration(format!(
+        "Cannot read '{}': the session already reads {} from container '{}', and stores are \
+         registered per account. Use an az://{}/... URL or a separate session",
+        path, store_url, registered_container, container
+    )))
+}
+
+/// Register the store built from `config` for the bucket of `path`, replacing any registered
+pub(crate) fn register_s3_object_store(state: &SessionState, path: &str, config: &S3Config) -> Result<()> {
+    match bucket_url(path)? {
+        Some((store_url, bucket)) if store_url.scheme() == "s3" => {
+            state.runtime_env().register_object_store(&store_url, config.build(&bucket)?);
+            Ok(())
+        }
+        _ => Err(DataFusionError::Configuration(format!(
+            "S3 settings need an s3:// path, not '{}'",
+            path
+        ))),
+    }
+}
+
+/// Split a cloud URL into the URL of its bucket and the bucket (or container) name
+///
+/// Stores are registered per scheme and host, so only one container of an
+/// account can be read through `abfss://` URLs in a session.
+fn bucket_url(path: &str) -> Result<Option<(Url, String)>> {
+    const SCHEMES: [&str; 5] = ["s3://", "gs://", "az://", "abfs://", "abfss://"];
+    if !SCHEMES.iter().any(|scheme| path.starts_with(scheme)) {
+        return Ok(None);
+    }
+    let url = Url::parse(path)
+        .map_err(|e| DataFusionError::Configuration(format!("Invalid URL '{}': {}", path, e)))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| DataFusionError::Configuration(format!("URL '{}' has no bucket", path)))?;
+    let bucket = match url.scheme() {
+        "abfs" | "abfss" if url.username().is_empty() => {
+            return Err(DataFusionError::Configuration(format!(
+                "URL '{}' has no container; expected {}://container@account.dfs.core.windows.net/path",
+                path,
+                url.scheme()
+            )));
+        }
+        "abfs" | "abfss" => url.username(),
+        _ => host,
+    };
+    let store_url = Url::parse(&format!("{}://{}", url.scheme(), host))
+        .map_err(|e| DataFusionError::Configuration(format!("Invalid URL '{}': {}", path, e)))?;
+    Ok(Some((store_url, bucket.to_string())))
+}
+
+#[cfg(not(all(feature = "http", feature = "s3", feature = "gcs", feature = "azure")))]
+fn missing_feature(feature: &str, url: &str) -> DataFusionError {
+    DataFusionError::Configuration(format!(
+        "Reading '{}' needs the `{}` feature of datafusion-csv, or an object store \
+         registered for it (e.g. with `CsvSourceBuilder::with_object_store`)",
+        url, feature
+    ))
+}
+
+#[cfg(all(test, any(feature = "http", feature = "s3", feature = "gcs", feature = "azure")))]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+
+    /// Serve `body` at a local `http://` URL, answering the first `failures` requests with a 503
+    ///
+    /// Also returns the header lines of the requests received, lowercased.
+    #[cfg(feature = "http")]
+    fn serve_flaky(body: &'static str, failures: usize) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/data.csv", listener.local_addr().unwrap());
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let headers = received.clone();
+        std::thread::spawn(move || {
+            let mut failures = failures;
+            for mut stream in listener.incoming().map_while(|stream| stream.ok()) {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut range = (0, body.len());
+                let mut line = String::new();
+                reader.read_line(&mut request).unwrap();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    headers.lock().unwrap().push(line.trim().to_ascii_lowercase());
+                    if let Some((start, end)) = line
+                        .to_ascii_lowercase()
+                        .strip_prefix("range: bytes=")
+                        .and_then(|r| r.trim().split_once('-'))
+                    {
+                        range = (start.parse().unwrap(), end.parse::<usize>().unwrap().min(body.len() - 1) + 1);
+                    }
+                    line.clear();
+                }
+
+                let response = if failures > 0 {
+                    failures -= 1;
+                    "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    let (status, content_range) = match range {
+                        (0, end) if end == body.len() => ("200 OK", String::new()),
+                        (start, end) => (
+                            "206 Partial Content",
+                            format!("Content-Range: bytes {}-{}/{}\r\n", start, end - 1, body.len()),
+                        ),
+                    };
+                    let content = if request.starts_with("HEAD") { "" } else { &body[range.0..range.1] };
+                    format!(
+                        "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+                        status,
+                        range.1 - range.0,
+                        content_range,
+                        content
+                    )
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, received)
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_http_retries() -> Result<()> {
+        use crate::{CsvFormatOptions, SessionContextCsvExt};
+
+        let body = "id,name\n1,a\n2,b\n";
+        let retrying = HttpConfig::new()
+            .with_max_retries(3)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(10));
+
+        // A transient 503 is retried
+        let (url, _) = serve_flaky(body, 2);
+        let options = CsvFormatOptions::new().with_http_config(retrying);
+        let df = SessionContext::new().read_csv_with_options(&url, options).await?;
+        assert_eq!(df.count().await?, 2);
+
+        // Without retries, the first error fails the query
+        let (url, _) = serve_flaky(body, 1);
+        let options = CsvFormatOptions::new().with_http_config(HttpConfig::new().with_max_retries(0));
+        let err = SessionContext::new().read_csv_with_options(&url, options).await.unwrap_err();
+        assert!(err.to_string().contains("503"), "{}", err);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_http_headers() -> Result<()> {
+        use crate::{CsvFormatOptions, SessionContextCsvExt};
+
+        let (url, received) = serve_flaky("id\n1\n", 0);
+        let http = HttpConfig::new().with_bearer_token("s3cret").with_header("X-Api-Key", "k1");
+        let options = CsvFormatOptions::new().with_http_config(http);
+        let df = SessionContext::new().read_csv_with_options(&url, options).await?;
+        assert_eq!(df.count().await?, 1);
+        let received = received.lock().unwrap();
+        assert!(received.contains(&"authorization: bearer s3cret".to_string()), "{:?}", received);
+        assert!(received.contains(&"x-api-key: k1".to_string()), "{:?}", received);
+
+        let err = HttpConfig::new().with_header("Bad Name", "x").build("https://example.com").unwrap_err();
+        assert!(err.to_string().contains("Invalid HTTP header 'Bad Name'"), "{}", err);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "s3")]
+    #[tokio::test]
+    async fn test_register_s3_store() -> Result<()> {
+        use crate::SessionContextCsvExt;
+
+        let ctx = SessionContext::new();
+        let config = S3Config::new()
+            .with_region("eu-west-1")
+            .with_credentials("key", "secret")
+            .with_endpoint("http://localhost:9000")
+            .with_allow_http(true);
+        register_s3_object_store(&ctx.state(), "s3://landing/orders/2024.csv", &config)?;
+        let store = ctx.runtime_env().object_store(ObjectStoreUrl::parse("s3://landing")?)?;
+        assert!(store.to_string().contains("landing"), "{}", store);
+
+        // The settings only make sense for S3 paths
+        let err = ctx
+            .register_csv_s3("orders", "data/orders.csv", config)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("need an s3:// path"), "{}", err);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "gcs")]
+    #[tokio::test]
+    async fn test_register_gcs_store() -> Result<()> {
+        // Building the store reads credentials lazily, so no network is needed
+        let ctx = SessionContext::new();
+        register_cloud_object_store(&ctx.state(), "gs://exports/daily/")?;
+        let store = ctx.runtime_env().object_store(ObjectStoreUrl::parse("gs://exports")?)?;
+        assert!(store.to_string().contains("exports"), "{}", store);
+
+        let err = GcsConfig::new()
+            .with_service_account_key("not json")
+            .build("exports")
+            .unwrap_err();
+        assert!(err.to_string().contains("Failed to create GCS object store"), "{}", err);
+
+        // The key file is read when the store is built
+        let err = GcsConfig::new()
+            .with_service_account_path("/nonexistent/reader.json")
+            .build("exports")
+            .unwrap_err();
+        assert!(err.to_string().contains("Failed to create GCS object store"), "{}", err);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "azure")]
+    #[tokio::test]
+    async fn test_register_azure_store() -> Result<()> {
+        let ctx = SessionContext::new();
+        let store = AzureConfig::new()
+            .with_connection_string("UseDevelopmentStorage=true;")
+            .build("raw")?;
+        assert!(store.to_string().contains("raw"), "{}", store);
+
+        let err = AzureConfig::new()
+            .with_connection_string("AccountName=lake;AccountKey")
+            .build("raw")
+            .unwrap_err();
+        assert!(err.to_string().contains("is not Key=Value"), "{}", err);
+
+        let store = AzureConfig::new()
+            .with_account("lake")
+            .with_sas_token("?sv=2022-11-02&sp=rl&sig=a%2Bb%3D")
+            .build("raw")?;
+        assert!(store.to_string().contains("lake"), "{}", store);
+        assert_eq!(
+            sas_pairs("?sv=2022-11-02&sp=rl&sig=a%2Bb%3D"),
+            [("sv", "2022-11-02"), ("sp", "rl"), ("sig", "a+b=")].map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+
+        let err = register_cloud_object_store(&ctx.state(), "abfss://lake.dfs.core.windows.net/raw/").unwrap_err();
+        assert!(err.to_string().contains("has no container"), "{}", err);
+
+        // The account of an abfss:// URL comes from its host
+        register_cloud_object_store(&ctx.state(), "abfss://raw@lake.dfs.core.windows.net/events/")?;
+        let store = ctx
+            .runtime_env()
+            .object_store(ObjectStoreUrl::parse("abfss://lake.dfs.core.windows.net")?)?;
+        assert!(store.to_string().contains("lake"), "{}", store);
+
+        // A second container of the account would be read from the first one's store
+        register_cloud_object_store(&ctx.state(), "abfss://raw@lake.dfs.core.windows.net/orders/")?;
+        let err =
+            register_cloud_object_store(&ctx.state(), "abfss://curated@lake.dfs.core.windows.net/events/").unwrap_err();
+        assert!(err.to_string().contains("from container 'raw'"), "{}", err);
+
+        Ok(())
+    }
+}
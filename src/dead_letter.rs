@@ -0,0 +1,242 @@
+//! Capture of rows dropped by the `SkipRow` error policy
+//!
+//! A [`DeadLetterSink`] set with [`CsvFormatOptions::with_dead_letter`]
+//! receives every row that [`ParseErrorPolicy::SkipRow`] drops, with its file,
+//! line, error and raw text, so ETL jobs can inspect or re-process them later.
+//! Rows go either to an in-memory buffer read back with
+//! [`DeadLetterSink::rows`] or appended to a CSV file.
+//!
+//! Tables registered with an in-memory sink also get a companion
+//! `<table>__rejects` table for querying the captured rows with SQL.
+//!
+//! [`CsvFormatOptions::with_dead_letter`]: crate::CsvFormatOptions::with_dead_letter
+//! [`ParseErrorPolicy::SkipRow`]: crate::ParseErrorPolicy::SkipRow
+
+use std::any::Any;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use arrow::record_batch::RecordBatch;
+use arrow_array::{StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::catalog::Session;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+
+use crate::error::CsvError;
+
+/// A row dropped by the `SkipRow` error policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedRow {
+    /// Location of the file the row was read from
+    pub file: String,
+    /// 1-based line the row starts on
+    pub line: Option<u64>,
+    /// Why the row was rejected
+    pub error: String,
+    /// The row's fields, re-encoded as one CSV line
+    pub raw: String,
+}
+
+/// Destination for rows dropped by the `SkipRow` error policy
+///
+/// Clones share the same destination, so a sink kept by the caller sees the
+/// rows of every scan using options it was set on.
+#[derive(Debug, Clone)]
+pub struct DeadLetterSink {
+    destination: Arc<Mutex<Destination>>,
+}
+
+#[derive(Debug)]
+enum Destination {
+    Memory(Vec<RejectedRow>),
+    File(Box<csv::Writer<File>>),
+}
+
+impl DeadLetterSink {
+    /// Keep rejected rows in memory, to read back with [`rows`](Self::rows)
+    pub fn in_memory() -> Self {
+        Self::new(Destination::Memory(Vec::new()))
+    }
+
+    /// Append rejected rows to the CSV file at `path`
+    ///
+    /// The file gets `file,line,error,raw` columns, with a header row when it
+    /// is created. Fails if the file cannot be opened.
+    pub fn to_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let io_error = |source| CsvError::Io {
+            context: format!("Failed to open dead-letter file '{}'", path.display()),
+            source,
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(io_error)?;
+        let is_new = file.metadata().map_err(io_error)?.len() == 0;
+
+        let mut writer = csv::Writer::from_writer(file);
+        if is_new {
+            writer
+                .write_record(["file", "line", "error", "raw"])
+                .and_then(|_| Ok(writer.flush()?))
+                .map_err(CsvError::from)?;
+        }
+        Ok(Self::new(Destination::File(Box::new(writer))))
+    }
+
+    fn new(destination: Destination) -> Self {
+        Self {
+            destination: Arc::new(Mutex::new(destination)),
+        }
+    }
+
+    /// Rows captured so far; always empty for a file sink
+    pub fn rows(&self) -> Vec<RejectedRow> {
+        match &*self.destination.lock().unwrap() {
+            Destination::Memory(rows) => rows.clone(),
+            Destination::File(_) => Vec::new(),
+        }
+    }
+
+    /// Forget the rows captured so far, e.g. before reloading
+    pub fn clear(&self) {
+        if let Destination::Memory(rows) = &mut *self.destination.lock().unwrap() {
+            rows.clear();
+        }
+    }
+
+    pub(crate) fn is_in_memory(&self) -> bool {
+        matches!(&*self.destination.lock().unwrap(), Destination::Memory(_))
+    }
+
+    pub(crate) fn push(&self, row: RejectedRow) -> Result<()> {
+        match &mut *self.destination.lock().unwrap() {
+            Destination::Memory(rows) => rows.push(row),
+            Destination::File(writer) => {
+                // Flushed per row, so the file is complete even if the scan fails later
+                let line = row.line.map(|l| l.to_string()).unwrap_or_default();
+                writer
+                    .write_record([row.file.as_str(), &line, &row.error, &row.raw])
+                    .and_then(|_| Ok(writer.flush()?))
+                    .map_err(CsvError::from)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Re-encode a record's fields as one CSV line
+pub(crate) fn raw_text(record: &csv::ByteRecord, delimiter: u8) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(Vec::new());
+    // Writing to memory cannot fail
+    let _ = writer.write_byte_record(record);
+    let mut line = writer.into_inner().unwrap_or_default();
+    line.pop();
+    String::from_utf8_lossy(&line).into_owned()
+}
+
+/// The `<table>__rejects` table: rows captured by an in-memory sink, read at scan time
+#[derive(Debug)]
+pub(crate) struct RejectsTable {
+    sink: DeadLetterSink,
+    schema: SchemaRef,
+}
+
+impl RejectsTable {
+    pub(crate) fn new(sink: DeadLetterSink) -> Self {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("file", DataType::Utf8, false),
+            Field::new("line", DataType::UInt64, true),
+            Field::new("error", DataType::Utf8, false),
+            Field::new("raw", DataType::Utf8, false),
+        ]));
+        Self { sink, schema }
+    }
+
+    fn to_batch(&self) -> Result<RecordBatch> {
+        let rows = self.sink.rows();
+        let file: StringArray = rows.iter().map(|r| Some(r.file.as_str())).collect();
+        let line: UInt64Array = rows.iter().map(|r| r.line).collect();
+        let error: StringArray = rows.iter().map(|r| Some(r.error.as_str())).collect();
+        let raw: StringArray = rows.iter().map(|r| Some(r.raw.as_str())).collect();
+        RecordBatch::try_new(
+            self.schema.clone(),
+            vec![Arc::new(file), Arc::new(line), Arc::new(error), Arc::new(raw)],
+        )
+        .map_err(|e| DataFusionError::Execution(format!("Failed to build rejects batch: {}", e)))
+    }
+}
+
+#[async_trait]
+impl TableProvider for RejectsTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let exec = MemoryExec::try_new(&[vec![self.to_batch()?]], self.schema.clone(), projection.cloned())?;
+        Ok(Arc::new(exec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CsvFormatOptions, ParseErrorPolicy, SessionContextCsvExt};
+    use datafusion::prelude::SessionContext;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_dead_letter_file() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("orders.csv");
+        std::fs::write(&csv_path, "id,note\n1,ok\n2,\"quoted, note\",x\n").unwrap();
+        let rejects_path = temp_dir.path().join("rejects.csv");
+
+        // Scans append to the same file; the header is written once
+        for _ in 0..2 {
+            let options = CsvFormatOptions::new()
+                .with_on_error(ParseErrorPolicy::SkipRow)
+                .with_dead_letter(DeadLetterSink::to_file(&rejects_path)?);
+            let ctx = SessionContext::new();
+            let df = ctx.read_csv_with_options(csv_path.to_str().unwrap(), options).await?;
+            assert_eq!(df.count().await?, 1);
+        }
+
+        let mut reader = csv::Reader::from_path(&rejects_path).unwrap();
+        assert_eq!(reader.headers().unwrap(), vec!["file", "line", "error", "raw"]);
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0][0].ends_with("orders.csv"), "{:?}", rows[0]);
+        assert_eq!(&rows[0][1], "3");
+        assert!(rows[0][2].contains("found record with 3 fields"), "{:?}", rows[0]);
+        assert_eq!(&rows[0][3], "2,\"quoted, note\",x");
+        assert_eq!(rows[0], rows[1]);
+
+        Ok(())
+    }
+}
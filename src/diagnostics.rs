@@ -0,0 +1,476 @@
+//! Data-quality diagnostics for CSV files
+//!
+//! [`profile_csv`] summarises every column of the files at a path (null
+//! counts, values that do not parse as the inferred type, min/max, distinct
+//! estimates and sample bad values) as a single `RecordBatch`, without
+//! registering a table. [`validate_csv`] is a dry run that lists every
+//! problem a scan would hit, with the line it occurs on.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{ArrayRef, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use bytes::Bytes;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use object_store::{ObjectMeta, ObjectStore};
+
+use crate::cache::CsvSchemaCache;
+use crate::error::CsvError;
+use crate::file_format::{infer_schema_from_store, CsvFormatOptions};
+use crate::file_source::list_matching_files;
+use crate::physical_exec::{self, normalize_number, SchemaAdapter};
+
+/// Issues reported by one validation before it stops
+const MAX_ISSUES: usize = 1000;
+
+/// Sample bad values kept per column
+const MAX_BAD_VALUES: usize = 5;
+
+/// Hashes kept by the distinct-count sketch; counts below this are exact
+const DISTINCT_SKETCH_SIZE: usize = 1024;
+
+/// Schema of the batch returned by [`profile_csv`]
+fn profile_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("column", DataType::Utf8, false),
+        Field::new("data_type", DataType::Utf8, false),
+        Field::new("rows", DataType::UInt64, false),
+        Field::new("null_count", DataType::UInt64, false),
+        Field::new("type_violations", DataType::UInt64, false),
+        Field::new("min", DataType::Utf8, true),
+        Field::new("max", DataType::Utf8, true),
+        Field::new("distinct_estimate", DataType::UInt64, false),
+        Field::new(
+            "bad_values",
+            DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+            false,
+        ),
+    ]))
+}
+
+/// Profile every column of the CSV files at `path`, one row per column
+pub(crate) async fn profile_csv(
+    state: &SessionState,
+    path: &str,
+    options: CsvFormatOptions,
+) -> Result<RecordBatch> {
+    let (store_url, store, files, options) = list_matching_files(state, path, &options).await?;
+    if files.is_empty() {
+        return Err(DataFusionError::Plan(format!("No CSV files match '{}'", path)));
+    }
+
+    let cache = state.config().get_extension::<CsvSchemaCache>();
+    let cache = cache.as_deref().map(|cache| (cache, &store_url));
+    let schema = infer_schema_from_store(&store, &files, &options, cache).await?;
+    let mut profiles: Vec<ColumnProfile> =
+        schema.fields().iter().map(|_| ColumnProfile::default()).collect();
+    let mut rows = 0u64;
+
+    for file in &files {
+        let bytes = fetch_file(&store, file, &options).await?;
+        let input = physical_exec::prepare_input(&bytes, &options);
+        let mut reader = physical_exec::reader_builder(&options)
+            .flexible(true)
+            .from_reader(input.as_ref());
+        let column_map = column_map(&mut reader, &schema, &options)?;
+        physical_exec::skip_rows_after_header(&mut reader, &options)?;
+
+        for record in reader.byte_records() {
+            let record = record.map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "CSV parse error in '{}': {}",
+                    file.location, e
+                ))
+            })?;
+            rows += 1;
+
+            for ((field, profile), index) in schema.fields().iter().zip(&mut profiles).zip(&column_map) {
+                let raw = index.and_then(|i| record.get(i)).map(String::from_utf8_lossy);
+                let value = raw.as_deref().map(|v| options.prepare_value(field.name(), v));
+                match value.as_deref() {
+                    Some(v) if !v.is_empty() && !options.is_null_value(field.name(), v) => {
+                        profile.observe(v, field, &options)
+                    }
+                    _ => profile.nulls += 1,
+                }
+            }
+        }
+    }
+
+    let mut bad_values = ListBuilder::new(StringBuilder::new());
+    for profile in &profiles {
+        for value in &profile.bad_values {
+            bad_values.values().append_value(value);
+        }
+        bad_values.append(true);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            schema.fields().iter().map(|f| f.name().as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            schema.fields().iter().map(|f| f.data_type().to_string()),
+        )),
+        Arc::new(UInt64Array::from(vec![rows; profiles.len()])),
+        Arc::new(UInt64Array::from_iter_values(profiles.iter().map(|p| p.nulls))),
+        Arc::new(UInt64Array::from_iter_values(profiles.iter().map(|p| p.violations))),
+        Arc::new(StringArray::from_iter(profiles.iter().map(|p| p.min.as_ref().map(|v| v.to_string())))),
+        Arc::new(StringArray::from_iter(profiles.iter().map(|p| p.max.as_ref().map(|v| v.to_string())))),
+        Arc::new(UInt64Array::from_iter_values(profiles.iter().map(|p| p.distinct.estimate()))),
+        Arc::new(bad_values.finish()),
+    ];
+    RecordBatch::try_new(profile_schema(), columns)
+        .map_err(|e| DataFusionError::Execution(format!("Failed to create profile batch: {}", e)))
+}
+
+/// Kind of problem found by [`validate_csv`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvIssueKind {
+    /// The record has a different number of fields than the table has columns
+    RaggedRow,
+    /// A value is not valid UTF-8
+    InvalidUtf8,
+    /// A non-null value does not parse as its column's type and would read as null
+    TypeMismatch,
+    /// The record could not be split into fields
+    ParseError,
+}
+
+/// A problem found in a CSV file by a validation run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvIssue {
+    /// Location of the file in its object store
+    pub file: String,
+    /// 1-based line number the record starts on
+    pub line: u64,
+    /// Column the issue applies to, if any
+    pub column: Option<String>,
+    /// Kind of problem
+    pub kind: CsvIssueKind,
+    /// Human-readable description
+    pub message: String,
+}
+
+/// Scan the CSV files at `path` and list their problems without producing rows
+///
+/// Stops after the first 1000 issues.
+pub(crate) async fn validate_csv(
+    state: &SessionState,
+    path: &str,
+    options: CsvFormatOptions,
+) -> Result<Vec<CsvIssue>> {
+    let (store_url, store, files, options) = list_matching_files(state, path, &options).await?;
+    if files.is_empty() {
+        return Err(DataFusionError::Plan(format!("No CSV files match '{}'", path)));
+    }
+
+    let cache = state.config().get_extension::<CsvSchemaCache>();
+    let cache = cache.as_deref().map(|cache| (cache, &store_url));
+    let schema = infer_schema_from_store(&store, &files, &options, cache).await?;
+    let mut issues = Vec::new();
+
+    'files: for file in &files {
+        let bytes = fetch_file(&store, file, &options).await?;
+        let input = physical_exec::prepare_input(&bytes, &options);
+        let mut reader = physical_exec::reader_builder(&options)
+            .flexible(true)
+            .from_reader(input.as_ref());
+        let column_map = column_map(&mut reader, &schema, &options)?;
+        physical_exec::skip_rows_after_header(&mut reader, &options)?;
+        let expected_fields = match reader.byte_headers() {
+            Ok(headers) if options.has_header => headers.len(),
+            _ => schema.fields().len(),
+        };
+        let issue = |line: u64, column: Option<&str>, kind: CsvIssueKind, message: String| CsvIssue {
+            file: file.location.to_string(),
+            line,
+            column: column.map(str::to_string),
+            kind,
+            message,
+        };
+
+        for record in reader.byte_records() {
+            if issues.len() >= MAX_ISSUES {
+                break 'files;
+            }
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    let line = e.position().map_or(0, |p| p.line());
+                    issues.push(issue(line, None, CsvIssueKind::ParseError, e.to_string()));
+                    break;
+                }
+            };
+            let line = record.position().map_or(0, |p| p.line());
+
+            if record.len() != expected_fields {
+                issues.push(issue(
+                    line,
+                    None,
+                    CsvIssueKind::RaggedRow,
+                    format!("expected {} fields, found {}", expected_fields, record.len()),
+                ));
+            }
+
+            for (field, index) in schema.fields().iter().zip(&column_map) {
+                let Some(raw) = index.and_then(|i| record.get(i)) else {
+                    continue;
+                };
+                let Ok(value) = std::str::from_utf8(raw) else {
+                    issues.push(issue(
+                        line,
+                        Some(field.name()),
+                        CsvIssueKind::InvalidUtf8,
+                        format!("value is not valid UTF-8: {}", String::from_utf8_lossy(raw)),
+                    ));
+                    continue;
+                };
+                let value = options.prepare_value(field.name(), value);
+                if !value.is_empty()
+                    && !options.is_null_value(field.name(), &value)
+                    && !physical_exec::value_parses_as(&value, field, &options)
+                {
+                    issues.push(issue(
+                        line,
+                        Some(field.name()),
+                        CsvIssueKind::TypeMismatch,
+                        format!("'{}' is not a valid {}", value, field.data_type()),
+                    ));
+                }
+            }
+        }
+    }
+
+    issues.truncate(MAX_ISSUES);
+    Ok(issues)
+}
+
+/// Read and decompress a file
+async fn fetch_file(
+    store: &Arc<dyn ObjectStore>,
+    file: &ObjectMeta,
+    options: &CsvFormatOptions,
+) -> Result<Bytes> {
+    physical_exec::fetch_decompressed(store, &file.location, options).await
+}
+
+/// Position of each schema column in the file's records
+///
+/// Files may order or omit columns differently, so columns are matched by
+/// header name as in a scan; otherwise they are read by position.
+fn column_map<R: std::io::Read>(
+    reader: &mut csv::Reader<R>,
+    schema: &Schema,
+    options: &CsvFormatOptions,
+) -> Result<Vec<Option<usize>>> {
+    let positional = || (0..schema.fields().len()).map(Some).collect::<Vec<_>>();
+    if !options.has_header {
+        return Ok(positional());
+    }
+
+    let headers = reader.byte_headers().map_err(CsvError::from)?;
+    let headers = csv::StringRecord::from_byte_record_lossy(headers.clone());
+    Ok(SchemaAdapter::new(schema, options)
+        .map_columns(&headers)
+        .unwrap_or_else(positional))
+}
+
+/// Running statistics for one column
+#[derive(Default)]
+struct ColumnProfile {
+    nulls: u64,
+    violations: u64,
+    min: Option<ProfileValue>,
+    max: Option<ProfileValue>,
+    distinct: DistinctSketch,
+    bad_values: Vec<String>,
+}
+
+impl ColumnProfile {
+    /// Record a non-null value
+    fn observe(&mut self, value: &str, field: &Field, options: &CsvFormatOptions) {
+        if !physical_exec::value_parses_as(value, field, options) {
+            self.violations += 1;
+            if self.bad_values.len() < MAX_BAD_VALUES && !self.bad_values.iter().any(|v| v == value) {
+                self.bad_values.push(value.to_string());
+            }
+            return;
+        }
+
+        self.distinct.insert(value);
+        let value = ProfileValue::parse(value, field.data_type(), options);
+        if self.min.as_ref().is_none_or(|min| value.compare(min) == Ordering::Less) {
+            self.min = Some(value.clone());
+        }
+        if self.max.as_ref().is_none_or(|max| value.compare(max) == Ordering::Greater) {
+            self.max = Some(value);
+        }
+    }
+}
+
+/// A valid value, compared according to its column type
+#[derive(Debug, Clone)]
+enum ProfileValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl ProfileValue {
+    fn parse(value: &str, data_type: &DataType, options: &CsvFormatOptions) -> Self {
+        let parsed = match data_type {
+            DataType::Int64 => normalize_number(value, options).parse().ok().map(Self::Int),
+            DataType::Float64 => normalize_number(value, options).parse().ok().map(Self::Float),
+            DataType::Boolean => options.parse_bool(value).map(Self::Bool),
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| Self::Text(value.to_string()))
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => a.cmp(b),
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            _ => self.to_string().cmp(&other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for ProfileValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Float(v) => write!(f, "{}", v),
+            Self::Bool(v) => write!(f, "{}", v),
+            Self::Text(v) => f.write_str(v),
+        }
+    }
+}
+
+/// K-minimum-values sketch estimating the number of distinct values
+///
+/// Keeps the smallest [`DISTINCT_SKETCH_SIZE`] hashes; the count is exact
+/// until the sketch fills, then extrapolated from the largest kept hash.
+#[derive(Default)]
+struct DistinctSketch {
+    hashes: BTreeSet<u64>,
+}
+
+impl DistinctSketch {
+    fn insert(&mut self, value: &str) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if self.hashes.len() < DISTINCT_SKETCH_SIZE {
+            self.hashes.insert(hash);
+        } else if self.hashes.last().is_some_and(|&largest| hash < largest) && self.hashes.insert(hash) {
+            self.hashes.pop_last();
+        }
+    }
+
+    fn estimate(&self) -> u64 {
+        match self.hashes.last() {
+            Some(&largest) if self.hashes.len() >= DISTINCT_SKETCH_SIZE => {
+                let fraction = largest as f64 / u64::MAX as f64;
+                ((DISTINCT_SKETCH_SIZE - 1) as f64 / fraction).round() as u64
+            }
+            _ => self.hashes.len() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array, ListArray};
+    use datafusion::prelude::SessionContext;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_profile_csv() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("orders.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,amount,status").unwrap();
+        for i in 0..2_000 {
+            writeln!(file, "{},{}.5,{}", i, i % 10, if i % 2 == 0 { "open" } else { "" }).unwrap();
+        }
+        // A bad amount beyond the inference sample
+        writeln!(file, "2000,N/A,open").unwrap();
+
+        let ctx = SessionContext::new();
+        let profile = profile_csv(&ctx.state(), csv_path.to_str().unwrap(), CsvFormatOptions::default())
+            .await?;
+        assert_eq!(profile.num_rows(), 3);
+
+        let strings = |i: usize| profile.column(i).as_any().downcast_ref::<StringArray>().unwrap().clone();
+        let counts = |i: usize| profile.column(i).as_any().downcast_ref::<UInt64Array>().unwrap().clone();
+
+        assert_eq!(strings(0).value(1), "amount");
+        assert_eq!(strings(1).value(1), "Float64");
+        assert_eq!(counts(2).value(0), 2_001);
+        assert_eq!(counts(3).value(2), 1_000);
+        assert_eq!(counts(4).value(1), 1);
+        assert_eq!(strings(5).value(1), "0.5");
+        assert_eq!(strings(6).value(1), "9.5");
+        assert_eq!(counts(7).value(1), 10);
+        // Beyond the sketch size the distinct count is an estimate
+        let ids = counts(7).value(0);
+        assert!((1_800..2_200).contains(&ids), "estimated {} ids", ids);
+
+        let bad = profile.column(8).as_any().downcast_ref::<ListArray>().unwrap();
+        let amount_bad = bad.value(1);
+        let amount_bad = amount_bad.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(amount_bad.len(), 1);
+        assert_eq!(amount_bad.value(0), "N/A");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_csv() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("orders.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        file.write_all(b"id,amount,note\n").unwrap();
+        file.write_all(b"1,2.5,ok\n").unwrap();
+        file.write_all(b"2,abc,ok\n").unwrap();
+        file.write_all(b"3,4.0\n").unwrap();
+        file.write_all(b"4,1.0,caf\xe9\n").unwrap();
+        file.write_all(b"5,,\n").unwrap();
+
+        let ctx = SessionContext::new();
+        let issues = validate_csv(&ctx.state(), csv_path.to_str().unwrap(), CsvFormatOptions::default())
+            .await?;
+
+        let summary: Vec<(u64, Option<&str>, CsvIssueKind)> = issues
+            .iter()
+            .map(|i| (i.line, i.column.as_deref(), i.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (3, Some("amount"), CsvIssueKind::TypeMismatch),
+                (4, None, CsvIssueKind::RaggedRow),
+                (5, Some("note"), CsvIssueKind::InvalidUtf8),
+            ]
+        );
+        assert_eq!(issues[0].message, "'abc' is not a valid Float64");
+        assert!(issues[0].file.ends_with("orders.csv"));
+
+        Ok(())
+    }
+}
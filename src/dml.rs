@@ -0,0 +1,248 @@
+//! UPDATE and DELETE support for CSV tables
+//!
+//! CSV files cannot be modified in place, so both operations are implemented
+//! as a rewrite: the table is read, the predicate/assignments are applied, and
+//! the result replaces the table's files through the `INSERT OVERWRITE` path
+//! of [`CsvSink`](crate::file_sink::CsvSink).
+
+use datafusion::dataframe::DataFrameWriteOptions;
+use datafusion::datasource::listing::ListingTable;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::dml::InsertOp;
+use datafusion::logical_expr::{cast, lit, when, Expr};
+use datafusion::prelude::{DataFrame, SessionContext};
+use datafusion_common::Column;
+
+use crate::file_format::{CsvFormat, CsvFormatOptions, ParseErrorPolicy};
+
+/// Delete all rows matching `predicate`, returning the number of deleted rows
+pub(crate) async fn delete_rows(ctx: &SessionContext, table: &str, predicate: &str) -> Result<u64> {
+    let df = csv_table(ctx, table).await?;
+    let predicate = df.parse_sql_expr(predicate)?;
+
+    let deleted = df.clone().filter(predicate.clone())?.count().await?;
+
+    // Rows where the predicate evaluates to NULL are kept, matching SQL DELETE
+    let remaining = df.filter(predicate.is_not_true())?;
+    overwrite(remaining, table).await?;
+
+    Ok(deleted as u64)
+}
+
+/// Apply `assignments` (column name, SQL expression) to all rows matching
+/// `predicate`, returning the number of updated rows
+pub(crate) async fn update_rows(
+    ctx: &SessionContext,
+    table: &str,
+    assignments: &[(&str, &str)],
+    predicate: Option<&str>,
+) -> Result<u64> {
+    let df = csv_table(ctx, table).await?;
+    let predicate = match predicate {
+        Some(sql) => df.parse_sql_expr(sql)?,
+        None => lit(true),
+    };
+
+    for (name, _) in assignments {
+        if df.schema().index_of_column_by_name(None, name).is_none() {
+            return Err(DataFusionError::Plan(format!(
+                "Column '{}' not found in table '{}'",
+                name, table
+            )));
+        }
+    }
+
+    let mut exprs: Vec<Expr> = Vec::with_capacity(df.schema().fields().len());
+    for field in df.schema().fields() {
+        let current = Expr::Column(Column::new_unqualified(field.name()));
+        let expr = match assignments.iter().find(|(name, _)| name == field.name()) {
+            Some((_, sql)) => {
+                let value = cast(df.parse_sql_expr(sql)?, field.data_type().clone());
+                when(predicate.clone(), value)
+                    .otherwise(current)?
+                    .alias(field.name())
+            }
+            None => current,
+        };
+        exprs.push(expr);
+    }
+
+    let updated = df.clone().filter(predicate.clone())?.count().await?;
+    overwrite(df.select(exprs)?, table).await?;
+
+    Ok(updated as u64)
+}
+
+/// Resolve `table` as a DataFrame, ensuring it is backed by this crate's CSV format
+async fn csv_table(ctx: &SessionContext, table: &str) -> Result<DataFrame> {
+    let provider = ctx.table_provider(table).await?;
+    let options = provider
+        .as_any()
+        .downcast_ref::<ListingTable>()
+        .and_then(|t| t.options().format.as_any().downcast_ref::<CsvFormat>())
+        .map(CsvFormat::options);
+
+    let Some(options) = options else {
+        return Err(DataFusionError::Plan(format!(
+            "Table '{}' is not a CSV table registered by datafusion-csv",
+            table
+        )));
+    };
+    check_rewritable(options, table)?;
+
+    ctx.table(table).await
+}
+
+/// Refuse tables whose scans leave rows of their files out
+///
+/// The rewrite replaces every file with what the scan returned, so any row
+/// the scan does not return would be deleted along with the matching ones.
+fn check_rewritable(options: &CsvFormatOptions, table: &str) -> Result<()> {
+    let reason = if options.max_rows.is_some() {
+        "its scans are capped by `with_max_rows`"
+    } else if options.row_filter.is_some() {
+        "its scans drop the rows rejected by `with_row_filter`"
+    } else if options.on_error == ParseErrorPolicy::SkipRow {
+        "its scans drop malformed rows under `ParseErrorPolicy::SkipRow`"
+    } else if options.modified_since.is_some() {
+        "its scans skip files older than the `with_modified_since` watermark"
+    } else {
+        return Ok(());
+    };
+
+    Err(DataFusionError::Plan(format!(
+        "Cannot rewrite table '{}': {}, so rows the scan leaves out would be lost",
+        table, reason
+    )))
+}
+
+/// Replace the table's files with the contents of `df`
+async fn overwrite(df: DataFrame, table: &str) -> Result<()> {
+    let options = DataFrameWriteOptions::new().with_insert_operation(InsertOp::Overwrite);
+    df.write_table(table, options).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CsvFormatOptions, ParseErrorPolicy, SessionContextCsvExt};
+    use arrow_array::{Int64Array, StringArray};
+    use datafusion::prelude::SessionContext;
+    use datafusion_common::Result;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_update_and_delete() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let mut file = File::create(temp_dir.path().join("people.csv")).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,Alice").unwrap();
+        writeln!(file, "2,Bob").unwrap();
+        writeln!(file, "3,Charlie").unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let ctx = SessionContext::new();
+        ctx.register_csv_file("people", &table_path).await?;
+
+        let deleted = ctx.delete_csv_rows("people", "id = 2").await?;
+        assert_eq!(deleted, 1);
+
+        let updated = ctx
+            .update_csv_rows("people", &[("name", "upper(name)")], Some("id = 3"))
+            .await?;
+        assert_eq!(updated, 1);
+
+        let batches = ctx
+            .sql("SELECT id, name FROM people ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let ids = batches[0].column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        let names = batches[0].column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(ids.values(), &[1, 3]);
+        assert_eq!(names.value(0), "Alice");
+        assert_eq!(names.value(1), "CHARLIE");
+
+        Ok(())
+    }
+
+    /// Write `id` 1 to 5 into a new table directory, returning its path
+    fn five_row_table(temp_dir: &TempDir) -> String {
+        let rows: String = (1..=5).map(|id| format!("{},row{}\n", id, id)).collect();
+        std::fs::write(temp_dir.path().join("rows.csv"), format!("id,name\n{}", rows)).unwrap();
+        format!("{}/", temp_dir.path().to_str().unwrap())
+    }
+
+    /// Assert that DML on `table` is refused and the file on disk is unchanged
+    async fn assert_not_rewritten(ctx: &SessionContext, temp_dir: &TempDir, table: &str) {
+        let before = std::fs::read_to_string(temp_dir.path().join("rows.csv")).unwrap();
+
+        let err = ctx.delete_csv_rows(table, "id = 1").await.unwrap_err();
+        assert!(err.to_string().contains("Cannot rewrite table"), "{}", err);
+        let err = ctx
+            .update_csv_rows(table, &[("name", "'x'")], None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Cannot rewrite table"), "{}", err);
+
+        let after = std::fs::read_to_string(temp_dir.path().join("rows.csv")).unwrap();
+        assert_eq!(before, after);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_capped_table() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let table_path = five_row_table(&temp_dir);
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_max_rows(2);
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+        assert_not_rewritten(&ctx, &temp_dir, "t").await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refuses_row_filtered_table() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let table_path = five_row_table(&temp_dir);
+
+        let ctx = SessionContext::new();
+        let options =
+            CsvFormatOptions::new().with_row_filter(|record| record.get_by_name("id") != Some("5"));
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+        assert_not_rewritten(&ctx, &temp_dir, "t").await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refuses_skip_row_table() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let table_path = five_row_table(&temp_dir);
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_on_error(ParseErrorPolicy::SkipRow);
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+        assert_not_rewritten(&ctx, &temp_dir, "t").await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refuses_watermarked_table() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let table_path = five_row_table(&temp_dir);
+
+        // Even a watermark every file is newer than would let the rewrite
+        // delete files that fall behind it later
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_modified_since(chrono::DateTime::UNIX_EPOCH);
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+        assert_not_rewritten(&ctx, &temp_dir, "t").await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+//! Structured errors for reading CSV files
+//!
+//! Failures while fetching, decoding and parsing files are reported as a
+//! [`CsvError`] wrapped in [`DataFusionError::External`]. Use
+//! [`CsvError::find`] to recover it from an error returned by a query:
+//!
+//! ```no_run
+//! use datafusion::prelude::*;
+//! use datafusion_csv::{CsvError, SessionContextCsvExt};
+//!
+//! # async fn example() -> datafusion::error::Result<()> {
+//! let ctx = SessionContext::new();
+//! ctx.register_csv_file("events", "data/events.csv").await?;
+//! if let Err(e) = ctx.sql("SELECT * FROM events").await?.collect().await {
+//!     match CsvError::find(&e) {
+//!         Some(CsvError::Store(_)) => println!("retry later: {}", e),
+//!         Some(CsvError::Parse { line, .. }) => println!("bad record at line {:?}", line),
+//!         _ => return Err(e),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use arrow_schema::Schema;
+use datafusion::error::DataFusionError;
+
+/// Category of a failure while reading CSV data
+///
+/// Line numbers are 1-based; column indices are 0-based field positions.
+/// `file` and `column_name` are filled in once the failing file and its
+/// columns are known.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CsvError {
+    /// Reading or decompressing file contents failed
+    Io {
+        /// What was being done, e.g. `Failed to decompress Gzip data`
+        context: String,
+        source: std::io::Error,
+    },
+    /// A field is not valid UTF-8
+    Encoding {
+        file: Option<String>,
+        line: Option<u64>,
+        column: Option<usize>,
+        column_name: Option<String>,
+    },
+    /// A record could not be split into fields, breaks a parsing limit or holds a bad value
+    Parse {
+        file: Option<String>,
+        line: Option<u64>,
+        column: Option<usize>,
+        column_name: Option<String>,
+        /// The raw value at fault, when a single value is
+        value: Option<String>,
+        message: String,
+    },
+    /// A file's columns do not match what the table expects
+    Schema(String),
+    /// The object store request for a file failed
+    Store(object_store::Error),
+}
+
+impl CsvError {
+    /// Find the `CsvError` behind `error`, looking through wrapping errors
+    pub fn find(error: &DataFusionError) -> Option<&CsvError> {
+        let mut current: Option<&(dyn Error + 'static)> = Some(error);
+        while let Some(e) = current {
+            if let Some(csv_error) = e.downcast_ref::<CsvError>() {
+                return Some(csv_error);
+            }
+            current = e.source();
+        }
+        None
+    }
+
+    /// Record the file being read and the names of its `columns`, where not yet known
+    pub(crate) fn locate(mut self, location: &str, columns: &Schema) -> Self {
+        if let CsvError::Encoding { file, column, column_name, .. }
+        | CsvError::Parse { file, column, column_name, .. } = &mut self
+        {
+            file.get_or_insert_with(|| location.to_string());
+            if column_name.is_none() {
+                *column_name = column.and_then(|c| columns.fields().get(c)).map(|f| f.name().clone());
+            }
+        }
+        self
+    }
+
+    /// A `Parse` error at the same position, with `context` before its message
+    pub(crate) fn into_parse(self, context: &str) -> Self {
+        match self {
+            CsvError::Parse { file, line, column, column_name, value, message } => CsvError::Parse {
+                file,
+                line,
+                column,
+                column_name,
+                value,
+                message: format!("{}: {}", context, message),
+            },
+            CsvError::Encoding { file, line, column, column_name } => CsvError::Parse {
+                file,
+                line,
+                column,
+                column_name,
+                value: None,
+                message: format!("{}: invalid UTF-8", context),
+            },
+            other => CsvError::Parse {
+                file: None,
+                line: None,
+                column: None,
+                column_name: None,
+                value: None,
+                message: format!("{}: {}", context, other),
+            },
+        }
+    }
+}
+
+/// Apply [`CsvError::locate`] to the `CsvError` carried by `error`, if any
+pub(crate) fn locate_error(error: DataFusionError, location: &str, columns: &Schema) -> DataFusionError {
+    match error {
+        DataFusionError::External(source) => match source.downcast::<CsvError>() {
+            Ok(csv_error) => csv_error.locate(location, columns).into(),
+            Err(source) => DataFusionError::External(source),
+        },
+        other => other,
+    }
+}
+
+/// ` at line L, field F ('name') of 'file'` for whichever parts are known
+fn position(
+    file: &Option<String>,
+    line: Option<u64>,
+    column: Option<usize>,
+    column_name: &Option<String>,
+) -> String {
+    let mut position = String::new();
+    if let Some(line) = line {
+        position.push_str(&format!(" at line {}", line));
+    }
+    if let Some(column) = column {
+        let separator = if line.is_some() { "," } else { " at" };
+        position.push_str(&format!("{} field {}", separator, column + 1));
+        if let Some(name) = column_name {
+            position.push_str(&format!(" ('{}')", name));
+        }
+    }
+    if let Some(file) = file {
+        let preposition = if position.is_empty() { "in" } else { "of" };
+        position.push_str(&format!(" {} '{}'", preposition, file));
+    }
+    position
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Io { context, source } => write!(f, "{}: {}", context, source),
+            CsvError::Encoding { file, line, column, column_name } => {
+                write!(f, "Invalid UTF-8{}", position(file, *line, *column, column_name))
+            }
+            CsvError::Parse { file, line, column, column_name, message, .. } => {
+                let position = position(file, *line, *column, column_name);
+                write!(f, "CSV parse error{}: {}", position, message)
+            }
+            CsvError::Schema(message) => write!(f, "{}", message),
+            CsvError::Store(e) => write!(f, "Failed to read file: {}", e),
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Io { source, .. } => Some(source),
+            CsvError::Store(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(error: csv::Error) -> Self {
+        let line = error.position().map(|p| p.line());
+        if error.is_io_error() {
+            let csv::ErrorKind::Io(source) = error.into_kind() else {
+                unreachable!("checked by is_io_error")
+            };
+            return CsvError::Io {
+                context: "Failed to read CSV data".to_string(),
+                source,
+            };
+        }
+        match error.kind() {
+            csv::ErrorKind::Utf8 { err, .. } => CsvError::Encoding {
+                file: None,
+                line,
+                column: Some(err.field()),
+                column_name: None,
+            },
+            csv::ErrorKind::UnequalLengths { expected_len, len, .. } => CsvError::Parse {
+                file: None,
+                line,
+                column: None,
+                column_name: None,
+                value: None,
+                message: format!(
+                    "found record with {} fields, but the previous record has {} fields",
+                    len, expected_len
+                ),
+            },
+            _ => CsvError::Parse {
+                file: None,
+                line,
+                column: None,
+                column_name: None,
+                value: None,
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+impl From<object_store::Error> for CsvError {
+    fn from(error: object_store::Error) -> Self {
+        CsvError::Store(error)
+    }
+}
+
+impl From<CsvError> for DataFusionError {
+    fn from(error: CsvError) -> Self {
+        DataFusionError::External(Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_format::{
+        CsvFormatOptions, IntegerOverflowPolicy, ParseErrorPolicy, StringOverflowPolicy, UnseenCategoryPolicy,
+    };
+    use crate::SessionContextCsvExt;
+    use datafusion::prelude::SessionContext;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_find_csv_error() -> datafusion::error::Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("bad.csv");
+        std::fs::write(&csv_path, b"id,name\n1,Alice\n2,\xff\n").unwrap();
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_schema_infer_max_rec(Some(1));
+        ctx.register_csv_with_options("t", csv_path.to_str().unwrap(), options).await?;
+        let err = ctx.sql("SELECT * FROM t").await?.collect().await.unwrap_err();
+        match CsvError::find(&err) {
+            Some(CsvError::Encoding { file, line, column, column_name }) => {
+                assert!(file.as_deref().is_some_and(|f| f.ends_with("bad.csv")), "{:?}", file);
+                assert_eq!(*line, Some(3));
+                assert_eq!(*column, Some(1));
+                assert_eq!(column_name.as_deref(), Some("name"));
+            }
+            other => panic!("expected an encoding error, got {:?}", other),
+        }
+        assert!(err.to_string().contains("Invalid UTF-8 at line 3, field 2 ('name') of '"), "{}", err);
+
+        assert!(CsvError::find(&DataFusionError::Plan("no".to_string())).is_none());
+
+        Ok(())
+    }
+
+    /// Scan `contents` as `bad.csv` and return the error the query fails with
+    async fn scan_error(contents: &str, options: CsvFormatOptions) -> datafusion::error::Result<DataFusionError> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("bad.csv");
+        std::fs::write(&csv_path, contents).unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_options("t", csv_path.to_str().unwrap(), options).await?;
+        Ok(ctx.sql("SELECT * FROM t").await?.collect().await.unwrap_err())
+    }
+
+    /// Check `error` carries a `Parse` error at `line` of `column` in `bad.csv`
+    fn assert_parse_error(error: &DataFusionError, line: u64, column: &str) {
+        match CsvError::find(error) {
+            Some(CsvError::Parse { file, line: l, column_name, .. }) => {
+                assert!(file.as_deref().is_some_and(|f| f.ends_with("bad.csv")), "{:?}", file);
+                assert_eq!(*l, Some(line), "{}", error);
+                assert_eq!(column_name.as_deref(), Some(column), "{}", error);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_integer_overflow_error() -> datafusion::error::Result<()> {
+        let options = CsvFormatOptions::new()
+            .with_schema_infer_max_rec(Some(1))
+            .with_integer_overflow_policy(IntegerOverflowPolicy::Error);
+        let error = scan_error("id,account\n1,42\n2,12345678901234567890\n", options).await?;
+        assert_parse_error(&error, 3, "account");
+        assert!(error.to_string().contains("outside the Int64 range"), "{}", error);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_string_length_error() -> datafusion::error::Result<()> {
+        let options = CsvFormatOptions::new()
+            .with_max_string_length("name", 5)
+            .with_string_overflow_policy(StringOverflowPolicy::Error);
+        let error = scan_error("id,name\n1,Alice\n2,Bartholomew\n", options).await?;
+        assert_parse_error(&error, 3, "name");
+        assert!(error.to_string().contains("maximum string length of 5"), "{}", error);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unseen_category_error() -> datafusion::error::Result<()> {
+        let options = CsvFormatOptions::new().with_categorical("size", ["S", "M"], UnseenCategoryPolicy::Error);
+        let error = scan_error("id,size\n1,S\n2,M\n3,XL\n", options).await?;
+        assert_parse_error(&error, 4, "size");
+        assert!(error.to_string().contains("'XL' is not one of the column's categories"), "{}", error);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_errors_error() -> datafusion::error::Result<()> {
+        let options = CsvFormatOptions::new()
+            .with_schema_infer_max_rec(Some(1))
+            .with_on_error(ParseErrorPolicy::SkipRow)
+            .with_max_errors(1);
+        let error = scan_error("id,score\n1,10\n2,abc\n3,xyz\n", options).await?;
+        assert_parse_error(&error, 4, "score");
+        assert!(error.to_string().contains("Scan aborted after 2 bad rows"), "{}", error);
+        Ok(())
+    }
+}
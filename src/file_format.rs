@@ -4,20 +4,446 @@
 //! the DataFusion FileFormat trait for independent CSV reading.
 
 use std::any::Any;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 
-use arrow_schema::{Schema, SchemaRef};
+use arrow_array::{ArrayRef, BinaryArray, DictionaryArray, FixedSizeBinaryArray, Int32Array, StringArray};
+use arrow_schema::{DataType, Field, Fields, IntervalUnit, Schema, SchemaRef, TimeUnit, UnionMode};
 use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Utc};
 use datafusion::datasource::file_format::FileFormat;
-use datafusion::datasource::physical_plan::FileScanConfig;
-use datafusion::error::Result;
+use datafusion::datasource::physical_plan::{FileScanConfig, FileSinkConfig};
+use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::SessionState;
+use datafusion::execution::object_store::ObjectStoreUrl;
+use datafusion::logical_expr::dml::InsertOp;
+use datafusion::physical_plan::insert::DataSinkExec;
 use datafusion::physical_plan::{ExecutionPlan, PhysicalExpr, Statistics};
+use datafusion_common::stats::Precision;
+use datafusion_common::ScalarValue;
+use datafusion_physical_expr::LexRequirement;
 use object_store::{ObjectMeta, ObjectStore};
+use regex::Regex;
 
-use crate::file_source::CsvExec;
+use crate::cache::CsvSchemaCache;
+use crate::cloud::HttpConfig;
+use crate::dead_letter::DeadLetterSink;
+use crate::error::{locate_error, CsvError};
+use crate::file_sink::CsvSink;
+use crate::file_source::{cap_rows, CsvExec};
+use crate::object_store_reader::estimate_object_row_count;
 use crate::physical_exec;
+use crate::sidecar;
+
+/// Action taken when a string value exceeds its column's maximum length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StringOverflowPolicy {
+    /// Truncate the value to the maximum length (default)
+    #[default]
+    Truncate,
+    /// Fail the scan with an error naming the column
+    Error,
+}
+
+/// Handling of integers outside the `Int64` range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntegerOverflowPolicy {
+    /// Infer such columns as `Int64` and read oversized values as null (default)
+    #[default]
+    Null,
+    /// Infer columns with oversized values as `Float64`, losing precision
+    Float64,
+    /// Infer columns with oversized values as `Decimal128(38, 0)`, keeping every digit
+    Decimal128,
+    /// Fail the scan with an error naming the value and column
+    Error,
+}
+
+/// Handling of rows that cannot be read
+///
+/// Covers records the CSV parser rejects, such as rows with the wrong number
+/// of fields or invalid UTF-8, and values that do not parse as their column's
+/// type. Empty values are missing rather than bad, and integers beyond the
+/// `Int64` range follow [`IntegerOverflowPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseErrorPolicy {
+    /// Strict mode: fail the scan at the first bad row, naming its file, line and column
+    Fail,
+    /// Drop bad rows and keep reading
+    SkipRow,
+    /// Read unparsable values as null; malformed records still fail (default)
+    #[default]
+    NullValue,
+}
+
+/// Order in which a multi-file table's files are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOrder {
+    /// Paths in ascending lexicographic order
+    NameAscending,
+    /// Paths in descending lexicographic order
+    NameDescending,
+    /// Oldest last-modified time first, ties broken by name
+    ModifiedAscending,
+    /// Newest last-modified time first, ties broken by name
+    ModifiedDescending,
+}
+
+impl FileOrder {
+    /// Compare two files by this order
+    pub(crate) fn compare(&self, a: &ObjectMeta, b: &ObjectMeta) -> Ordering {
+        match self {
+            FileOrder::NameAscending => a.location.cmp(&b.location),
+            FileOrder::NameDescending => b.location.cmp(&a.location),
+            FileOrder::ModifiedAscending => {
+                (a.last_modified, &a.location).cmp(&(b.last_modified, &b.location))
+            }
+            FileOrder::ModifiedDescending => b
+                .last_modified
+                .cmp(&a.last_modified)
+                .then_with(|| a.location.cmp(&b.location)),
+        }
+    }
+}
+
+/// Compression codec applied to CSV files on read and write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvCompression {
+    /// Plain, uncompressed CSV (default)
+    #[default]
+    Uncompressed,
+    /// Gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+    /// Bzip2 (`.bz2`)
+    Bzip2,
+}
+
+impl CsvCompression {
+    /// File name suffix appended to the CSV extension (e.g. `".gz"`)
+    pub fn file_suffix(&self) -> &'static str {
+        match self {
+            CsvCompression::Uncompressed => "",
+            CsvCompression::Gzip => ".gz",
+            CsvCompression::Zstd => ".zst",
+            CsvCompression::Bzip2 => ".bz2",
+        }
+    }
+
+    /// Detect the codec from a file extension such as `"gz"` or `".zst"`
+    pub(crate) fn from_extension(ext: &str) -> Option<Self> {
+        match ext.trim_start_matches('.').to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(CsvCompression::Gzip),
+            "zst" | "zstd" => Some(CsvCompression::Zstd),
+            "bz2" | "bzip2" => Some(CsvCompression::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// Fail unless support for this codec was compiled in
+    pub(crate) fn check_enabled(&self) -> Result<()> {
+        let (feature, enabled) = match self {
+            CsvCompression::Uncompressed => return Ok(()),
+            CsvCompression::Gzip => ("gzip", cfg!(feature = "gzip")),
+            CsvCompression::Zstd => ("zstd", cfg!(feature = "zstd")),
+            CsvCompression::Bzip2 => ("bzip2", cfg!(feature = "bzip2")),
+        };
+        if enabled {
+            return Ok(());
+        }
+        Err(DataFusionError::Configuration(format!(
+            "{:?} compression needs the `{}` feature of datafusion-csv",
+            self, feature
+        )))
+    }
+}
+
+/// A regex find/replace rule applied to raw values of a column before parsing
+#[derive(Debug, Clone)]
+pub struct ValueTransform {
+    /// Pattern to search for
+    pub pattern: Regex,
+    /// Replacement text; may reference capture groups as `$1` or `${name}`
+    pub replacement: String,
+}
+
+/// Signature of a user-defined parser converting a raw value to a scalar
+pub type ColumnParserFn = dyn Fn(&str) -> Result<ScalarValue> + Send + Sync;
+
+/// A user-defined parser for a column, producing values of a fixed type
+#[derive(Clone)]
+pub struct ColumnParser {
+    data_type: DataType,
+    parser: Arc<ColumnParserFn>,
+}
+
+impl ColumnParser {
+    /// Create a parser whose values all have type `data_type`
+    pub fn new(
+        data_type: DataType,
+        parser: impl Fn(&str) -> Result<ScalarValue> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            data_type,
+            parser: Arc::new(parser),
+        }
+    }
+
+    /// The type of the values produced by this parser
+    pub fn data_type(&self) -> &DataType {
+        &self.data_type
+    }
+
+    /// Parse raw column values into an array; nulls are passed through
+    pub(crate) fn build_array(&self, data: &[Option<&str>]) -> Result<ArrayRef> {
+        let null = ScalarValue::try_from(&self.data_type)?;
+        let scalars = data
+            .iter()
+            .map(|v| match v {
+                Some(s) => (self.parser)(s),
+                None => Ok(null.clone()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if scalars.is_empty() {
+            return Ok(arrow_array::new_empty_array(&self.data_type));
+        }
+        ScalarValue::iter_to_array(scalars)
+    }
+}
+
+impl fmt::Debug for ColumnParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnParser")
+            .field("data_type", &self.data_type)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What happens to a value that is not one of a categorical column's categories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnseenCategoryPolicy {
+    /// Read the value as null (default)
+    #[default]
+    Null,
+    /// Fail the scan with an error naming the column and value
+    Error,
+}
+
+/// The fixed categories of a categorical column
+///
+/// Values are read as `Dictionary(Int32, Utf8)` arrays that all share the
+/// declared categories, in declaration order, as their dictionary, so a key
+/// means the same category in every batch and file.
+#[derive(Debug, Clone)]
+pub struct Categories {
+    values: ArrayRef,
+    keys: HashMap<String, i32>,
+    policy: UnseenCategoryPolicy,
+}
+
+impl Categories {
+    /// Create categories from `values`; duplicates are ignored
+    pub fn new(values: impl IntoIterator<Item = impl Into<String>>, policy: UnseenCategoryPolicy) -> Self {
+        let mut keys = HashMap::new();
+        let mut unique = Vec::new();
+        for value in values {
+            let value = value.into();
+            if !keys.contains_key(&value) {
+                keys.insert(value.clone(), unique.len() as i32);
+                unique.push(value);
+            }
+        }
+        Self {
+            values: Arc::new(StringArray::from(unique)),
+            keys,
+            policy,
+        }
+    }
+
+    /// The categories, in declaration order
+    #[cfg(any(feature = "proto", feature = "substrait"))]
+    pub(crate) fn values(&self) -> impl Iterator<Item = &str> {
+        let values = self.values.as_any().downcast_ref::<StringArray>();
+        values.into_iter().flat_map(|values| values.iter().flatten())
+    }
+
+    #[cfg(any(feature = "proto", feature = "substrait"))]
+    pub(crate) fn policy(&self) -> UnseenCategoryPolicy {
+        self.policy
+    }
+
+    /// The type of categorical columns
+    pub fn data_type() -> DataType {
+        DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+    }
+
+    /// Map raw column values to keys into the shared dictionary
+    pub(crate) fn build_array(&self, column: &str, data: &[Option<&str>]) -> Result<ArrayRef> {
+        let keys = data
+            .iter()
+            .map(|value| match value {
+                None => Ok(None),
+                Some(value) => match (self.keys.get(*value), self.policy) {
+                    (Some(key), _) => Ok(Some(*key)),
+                    (None, UnseenCategoryPolicy::Null) => Ok(None),
+                    (None, UnseenCategoryPolicy::Error) => Err(CsvError::Parse {
+                        file: None,
+                        line: None,
+                        column: None,
+                        column_name: Some(column.to_string()),
+                        value: Some(value.to_string()),
+                        message: format!("'{}' is not one of the column's categories", value),
+                    }
+                    .into()),
+                },
+            })
+            .collect::<Result<Int32Array>>()?;
+        Ok(Arc::new(DictionaryArray::try_new(keys, self.values.clone())?))
+    }
+}
+
+/// Text encoding of the values of a binary column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryEncoding {
+    /// Hexadecimal digits in either case, optionally prefixed with `0x`
+    Hex,
+    /// Standard base64 with padding
+    Base64,
+}
+
+impl BinaryEncoding {
+    /// Decode a value, or `None` if it is not validly encoded
+    fn decode(self, value: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Hex => hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok(),
+            Self::Base64 => base64::engine::general_purpose::STANDARD.decode(value).ok(),
+        }
+    }
+}
+
+/// A column of hex or base64 text read as `Binary` or `FixedSizeBinary`
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryColumn {
+    encoding: BinaryEncoding,
+    byte_width: Option<i32>,
+}
+
+impl BinaryColumn {
+    /// The type of the column: `FixedSizeBinary` with a byte width, else `Binary`
+    pub fn data_type(&self) -> DataType {
+        match self.byte_width {
+            Some(width) => DataType::FixedSizeBinary(width),
+            None => DataType::Binary,
+        }
+    }
+
+    #[cfg(any(feature = "proto", feature = "substrait"))]
+    pub(crate) fn encoding(&self) -> BinaryEncoding {
+        self.encoding
+    }
+
+    #[cfg(any(feature = "proto", feature = "substrait"))]
+    pub(crate) fn byte_width(&self) -> Option<i32> {
+        self.byte_width
+    }
+
+    /// Decode a value, or `None` if it is invalid or has the wrong width
+    pub(crate) fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        let bytes = self.encoding.decode(value)?;
+        match self.byte_width {
+            Some(width) if bytes.len() != width as usize => None,
+            _ => Some(bytes),
+        }
+    }
+
+    /// Decode raw column values; invalid values are read as null
+    pub(crate) fn build_array(&self, data: &[Option<&str>]) -> Result<ArrayRef> {
+        let values = data.iter().map(|value| value.and_then(|v| self.decode(v)));
+        Ok(match self.byte_width {
+            Some(width) => Arc::new(FixedSizeBinaryArray::try_from_sparse_iter_with_size(values, width)?),
+            None => Arc::new(values.collect::<BinaryArray>()),
+        })
+    }
+}
+
+/// A raw CSV record as seen by a row filter, before any type conversion
+pub struct RawRecord<'a> {
+    record: &'a csv::StringRecord,
+    schema: &'a Schema,
+}
+
+impl<'a> RawRecord<'a> {
+    pub(crate) fn new(record: &'a csv::StringRecord, schema: &'a Schema) -> Self {
+        Self { record, schema }
+    }
+
+    /// Get the raw value at a field position
+    pub fn get(&self, index: usize) -> Option<&'a str> {
+        self.record.get(index)
+    }
+
+    /// Get the raw value of a column by name
+    pub fn get_by_name(&self, name: &str) -> Option<&'a str> {
+        let index = self.schema.index_of(name).ok()?;
+        self.record.get(index)
+    }
+
+    /// Number of fields in the record
+    pub fn len(&self) -> usize {
+        self.record.len()
+    }
+
+    /// Whether the record has no fields
+    pub fn is_empty(&self) -> bool {
+        self.record.is_empty()
+    }
+
+    /// Iterate over the raw field values
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + 'a {
+        self.record.iter()
+    }
+}
+
+/// Signature of a row-level filter; rows for which it returns `false` are dropped
+pub type RowFilterFn = dyn Fn(&RawRecord) -> bool + Send + Sync;
+
+/// How ASCII control characters embedded in field values are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlCharPolicy {
+    /// Leave values untouched (default)
+    #[default]
+    Keep,
+    /// Remove control characters
+    Strip,
+    /// Replace each control character with the given character
+    Replace(char),
+}
+
+impl ControlCharPolicy {
+    /// Apply the policy to a value
+    pub(crate) fn apply<'a>(&self, value: Cow<'a, str>) -> Cow<'a, str> {
+        if *self == ControlCharPolicy::Keep || !value.chars().any(|c| c.is_ascii_control()) {
+            return value;
+        }
+
+        let sanitized = match self {
+            ControlCharPolicy::Keep => unreachable!(),
+            ControlCharPolicy::Strip => value.chars().filter(|c| !c.is_ascii_control()).collect(),
+            ControlCharPolicy::Replace(replacement) => value
+                .chars()
+                .map(|c| if c.is_ascii_control() { *replacement } else { c })
+                .collect(),
+        };
+        Cow::Owned(sanitized)
+    }
+}
 
 /// CSV format configuration options
 #[derive(Debug, Clone)]
@@ -32,6 +458,124 @@ pub struct CsvFormatOptions {
     pub batch_size: usize,
     /// File extension to look for (default: ".csv")
     pub file_extension: String,
+    /// Maximum string length in bytes, keyed by column name
+    pub max_string_lengths: HashMap<String, usize>,
+    /// What to do when a value exceeds its maximum string length
+    pub string_overflow_policy: StringOverflowPolicy,
+    /// Handling of integers outside the `Int64` range (default: null)
+    pub integer_overflow_policy: IntegerOverflowPolicy,
+    /// Handling of malformed rows and unparsable values (default: null values)
+    pub on_error: ParseErrorPolicy,
+    /// Rows `SkipRow` may drop before the scan fails (default: unlimited)
+    pub max_errors: Option<usize>,
+    /// Where rows dropped by `SkipRow` are captured (default: nowhere)
+    pub dead_letter: Option<DeadLetterSink>,
+    /// Read `name:type` headers as the column name and its type (default: false)
+    pub header_type_hints: bool,
+    /// Rows to discard between the header and the data (default: 0)
+    pub skip_rows_after_header: usize,
+    /// Compression codec of the files (default: uncompressed)
+    pub compression: CsvCompression,
+    /// Decimal separator used in numeric values (default: b'.')
+    pub decimal_separator: u8,
+    /// Separator grouping thousands in numeric values, e.g. b',' (default: none)
+    pub thousands_separator: Option<u8>,
+    /// Symbols stripped from the start of numeric values, e.g. `$` (default: none)
+    pub currency_symbols: Vec<String>,
+    /// Infer 10- and 13-digit integers as epoch seconds and milliseconds (default: false)
+    pub epoch_timestamps: bool,
+    /// Infer fixed-point numbers as `Decimal128` instead of `Float64` (default: false)
+    pub decimal_inference: bool,
+    /// Infer `Int16`, `Int32` or `UInt64` when values fit (default: false, always `Int64`)
+    pub narrow_integers: bool,
+    /// strftime format of dates and timestamps in every column (default: ISO 8601)
+    pub timestamp_format: Option<String>,
+    /// strftime format of dates and timestamps, keyed by column name
+    pub column_timestamp_formats: HashMap<String, String>,
+    /// Split fields on runs of spaces/tabs instead of `delimiter` (default: false)
+    pub whitespace_delimited: bool,
+    /// Treat a run of consecutive delimiters as one separator (default: false)
+    pub collapse_delimiters: bool,
+    /// Character escaping quotes inside quoted fields, e.g. `\` (default: none)
+    pub escape: Option<u8>,
+    /// Byte ending each record, instead of `\n`, `\r\n` or `\r` (default: none)
+    pub terminator: Option<u8>,
+    /// Quoted values may contain line breaks, so files are never split (default: true)
+    pub newlines_in_values: bool,
+    /// Values treated as null in every column (default: none)
+    pub null_values: Vec<String>,
+    /// Values treated as null, keyed by column name
+    pub column_null_values: HashMap<String, Vec<String>>,
+    /// Pattern of values treated as null in every column (default: none)
+    pub null_regex: Option<Regex>,
+    /// Values read as `true` in boolean columns, ignoring case (default: `true`)
+    pub true_values: Vec<String>,
+    /// Values read as `false` in boolean columns, ignoring case (default: `false`)
+    pub false_values: Vec<String>,
+    /// Regex find/replace rules applied in order, keyed by column name
+    pub value_transforms: HashMap<String, Vec<ValueTransform>>,
+    /// User-defined parsers, keyed by column name
+    pub column_parsers: HashMap<String, ColumnParser>,
+    /// Categorical columns read as dictionaries of fixed values, keyed by column name
+    pub categorical_columns: HashMap<String, Categories>,
+    /// Columns of hex or base64 text read as binary, keyed by column name
+    pub binary_columns: HashMap<String, BinaryColumn>,
+    /// Filter applied to raw records before Arrow conversion
+    pub row_filter: Option<RowFilter>,
+    /// Handling of ASCII control characters inside values (default: keep)
+    pub control_chars: ControlCharPolicy,
+    /// Maximum number of fields allowed in a record (default: unlimited)
+    pub max_fields: Option<usize>,
+    /// Maximum bytes a single file scan may hold in memory (default: unlimited)
+    pub max_scan_memory: Option<usize>,
+    /// How long directory listings are cached for (default: no caching)
+    pub list_cache_ttl: Option<Duration>,
+    /// Hive-style partition columns (`key=value` directories) and their types
+    pub partition_columns: Vec<(String, DataType)>,
+    /// Infer from every file and merge columns by header name (default: false)
+    pub union_schema: bool,
+    /// Fail when a file's header differs from the table schema (default: false)
+    pub schema_drift_check: bool,
+    /// Widen inferred column types on values beyond the inference sample (default: false)
+    pub type_promotion: bool,
+    /// Infer types from every row of every file before reading (default: false)
+    pub two_pass: bool,
+    /// Read the schema from a `.schema.json` sidecar when one exists (default: false)
+    pub schema_sidecar: bool,
+    /// Merge small batches up to `batch_size` rows before returning them (default: false)
+    pub coalesce_batches: bool,
+    /// Maximum concurrent requests to the table's object store (default: unlimited)
+    pub max_concurrent_requests: Option<usize>,
+    /// Headers, timeouts and retries of the store built for `http(s)://` paths (default: none)
+    pub http_config: Option<HttpConfig>,
+    /// Files each partition downloads ahead of the one being parsed (default: 0)
+    pub prefetch_files: usize,
+    /// Parse files while they download instead of after (default: false)
+    pub streaming_reads: bool,
+    /// Column holding each row's source file modification time (default: none)
+    pub file_modified_column: Option<String>,
+    /// Column holding each record's starting byte offset in its file (default: none)
+    pub byte_offset_column: Option<String>,
+    /// Column holding each record's 1-based line number in its file (default: none)
+    pub line_number_column: Option<String>,
+    /// Only scan files modified after this time (default: none)
+    pub modified_since: Option<DateTime<Utc>>,
+    /// Order in which files are assigned to partitions and read (default: listing order)
+    pub file_order: Option<FileOrder>,
+    /// Stop scans after this many rows across all files (default: none)
+    pub max_rows: Option<usize>,
+    /// `key,value` lines before the header, read as table metadata (default: 0)
+    pub preamble_rows: usize,
+}
+
+/// Wrapper giving a row filter closure `Debug` so it can live in the options
+#[derive(Clone)]
+pub struct RowFilter(pub Arc<RowFilterFn>);
+
+impl fmt::Debug for RowFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RowFilter")
+    }
 }
 
 impl Default for CsvFormatOptions {
@@ -42,6 +586,60 @@ impl Default for CsvFormatOptions {
             schema_infer_max_rec: Some(1000),
             batch_size: 8192,
             file_extension: ".csv".to_string(),
+            max_string_lengths: HashMap::new(),
+            string_overflow_policy: StringOverflowPolicy::default(),
+            integer_overflow_policy: IntegerOverflowPolicy::default(),
+            on_error: ParseErrorPolicy::default(),
+            max_errors: None,
+            dead_letter: None,
+            header_type_hints: false,
+            skip_rows_after_header: 0,
+            compression: CsvCompression::default(),
+            decimal_separator: b'.',
+            thousands_separator: None,
+            currency_symbols: Vec::new(),
+            epoch_timestamps: false,
+            decimal_inference: false,
+            narrow_integers: false,
+            timestamp_format: None,
+            column_timestamp_formats: HashMap::new(),
+            whitespace_delimited: false,
+            collapse_delimiters: false,
+            escape: None,
+            terminator: None,
+            newlines_in_values: true,
+            null_values: Vec::new(),
+            column_null_values: HashMap::new(),
+            null_regex: None,
+            true_values: vec!["true".to_string()],
+            false_values: vec!["false".to_string()],
+            value_transforms: HashMap::new(),
+            column_parsers: HashMap::new(),
+            categorical_columns: HashMap::new(),
+            binary_columns: HashMap::new(),
+            row_filter: None,
+            control_chars: ControlCharPolicy::default(),
+            max_fields: None,
+            max_scan_memory: None,
+            list_cache_ttl: None,
+            partition_columns: Vec::new(),
+            union_schema: false,
+            schema_drift_check: false,
+            type_promotion: false,
+            two_pass: false,
+            schema_sidecar: false,
+            coalesce_batches: false,
+            max_concurrent_requests: None,
+            http_config: None,
+            prefetch_files: 0,
+            streaming_reads: false,
+            file_modified_column: None,
+            byte_offset_column: None,
+            line_number_column: None,
+            modified_since: None,
+            file_order: None,
+            max_rows: None,
+            preamble_rows: 0,
         }
     }
 }
@@ -76,12 +674,709 @@ impl CsvFormatOptions {
         self
     }
 
+    /// Merge small batches from a partition into batches of about `batch_size` rows
+    ///
+    /// Each file ends with a partial batch, so a partition of many small files
+    /// otherwise yields many tiny batches. Coalescing concatenates them at the
+    /// cost of a copy.
+    pub fn with_coalesce_batches(mut self, coalesce: bool) -> Self {
+        self.coalesce_batches = coalesce;
+        self
+    }
+
     /// Set file extension
     pub fn with_file_extension(mut self, ext: impl Into<String>) -> Self {
         self.file_extension = ext.into();
         self
     }
 
+    /// Cap the length (in bytes) of values in a string column
+    ///
+    /// Truncation always happens on a UTF-8 character boundary, so a
+    /// truncated value may be slightly shorter than `max_length`.
+    pub fn with_max_string_length(mut self, column: impl Into<String>, max_length: usize) -> Self {
+        self.max_string_lengths.insert(column.into(), max_length);
+        self
+    }
+
+    /// Set the policy applied when a value exceeds its maximum string length
+    pub fn with_string_overflow_policy(mut self, policy: StringOverflowPolicy) -> Self {
+        self.string_overflow_policy = policy;
+        self
+    }
+
+    /// Set the policy for integers too large for `Int64`, such as 20-digit IDs
+    ///
+    /// Promotion to `Float64` or `Decimal128` applies to values seen during
+    /// inference; combine with `with_type_promotion` to catch values past
+    /// the sample.
+    pub fn with_integer_overflow_policy(mut self, policy: IntegerOverflowPolicy) -> Self {
+        self.integer_overflow_policy = policy;
+        self
+    }
+
+    /// Set what happens to rows that cannot be read
+    ///
+    /// `SkipRow` keeps one malformed row from aborting a long scan; dropped
+    /// rows are counted in the scan's `skipped_rows` metric. `Fail` also
+    /// rejects values that would otherwise be read as null.
+    pub fn with_on_error(mut self, policy: ParseErrorPolicy) -> Self {
+        self.on_error = policy;
+        self
+    }
+
+    /// Fail the scan once [`ParseErrorPolicy::SkipRow`] has dropped more than `max` rows
+    ///
+    /// The count covers every file and partition of one scan, so a handful of
+    /// bad rows is tolerated while a wrong delimiter or schema still fails fast.
+    pub fn with_max_errors(mut self, max: usize) -> Self {
+        self.max_errors = Some(max);
+        self
+    }
+
+    /// Capture rows dropped by [`ParseErrorPolicy::SkipRow`] in `sink`
+    ///
+    /// Keep a clone of the sink to read the rows back after the scan. Tables
+    /// registered with an in-memory sink also get a `<table>__rejects` table.
+    pub fn with_dead_letter(mut self, sink: DeadLetterSink) -> Self {
+        self.dead_letter = Some(sink);
+        self
+    }
+
+    /// Take column types from `name:type` suffixes in the header row
+    ///
+    /// A header such as `age:int,name:string,joined:date` gives columns `age`
+    /// (`Int64`), `name` (`Utf8`) and `joined` (`Date32`, `YYYY-MM-DD`) without
+    /// inference. Also accepted: `float`, `double`, `bool`. Columns without a
+    /// suffix are inferred as usual; an unknown type fails the read.
+    pub fn with_header_type_hints(mut self, hints: bool) -> Self {
+        self.header_type_hints = hints;
+        self
+    }
+
+    /// Skip `rows` rows after the header, such as a row of units or descriptions
+    ///
+    /// Skipped rows are excluded from inference and results. Without a header,
+    /// the first `rows` rows are skipped.
+    pub fn with_skip_rows_after_header(mut self, rows: usize) -> Self {
+        self.skip_rows_after_header = rows;
+        self
+    }
+
+    /// Set the compression codec used when reading and writing files
+    pub fn with_compression(mut self, compression: CsvCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set the decimal separator for numeric values
+    ///
+    /// Use `b','` for European locale exports where `3,14` means 3.14; such
+    /// files normally use another delimiter, e.g. `b';'`. With a decimal
+    /// comma, `.` is read as a thousands separator, so `1.234,56` is 1234.56.
+    /// Applies to inference and reads, and to floats and decimals written
+    /// to the table.
+    pub fn with_decimal_separator(mut self, separator: u8) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Set the separator grouping thousands in numeric values, e.g. `b','` for `1,234.00`
+    ///
+    /// Separators are removed before parsing, during inference and reads,
+    /// when they split the integer part into groups of three digits.
+    pub fn with_thousands_separator(mut self, separator: u8) -> Self {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// Strip currency symbols such as `"$"` or `"EUR "` from the start of numeric values
+    ///
+    /// Financial exports rarely emit bare numbers; with
+    /// `with_thousands_separator(b',')`, `$1,234.00` and `-$5` read as numbers.
+    pub fn with_currency_symbols(mut self, symbols: Vec<String>) -> Self {
+        self.currency_symbols = symbols;
+        self
+    }
+
+    /// Infer columns of fixed-point numbers, such as prices, as `Decimal128`
+    ///
+    /// Sums of `Float64` money columns pick up rounding error; decimals add
+    /// exactly. A column of values like `19.99` and `5` is read as
+    /// `Decimal128(p, s)`, with `s` the most fractional digits seen and `p`
+    /// wide enough for every `Int64`. Values in exponent notation and columns
+    /// needing more than 38 digits stay `Float64`.
+    pub fn with_decimal_inference(mut self, decimal_inference: bool) -> Self {
+        self.decimal_inference = decimal_inference;
+        self
+    }
+
+    /// Infer integer columns as the narrowest type holding the values seen
+    ///
+    /// Cuts memory on wide numeric files: a column of values between -32768
+    /// and 32767 is read as `Int16`, within the `i32` range as `Int32`, and
+    /// non-negative values beyond `Int64` as `UInt64`. Only inferred values
+    /// are checked; combine with `with_type_promotion` to check every row.
+    /// Later values that don't fit are read as null.
+    pub fn with_narrow_integers(mut self, narrow_integers: bool) -> Self {
+        self.narrow_integers = narrow_integers;
+        self
+    }
+
+    /// Read integer columns that look like Unix epoch times as timestamps
+    ///
+    /// Inference already reads values such as `2024-01-31 10:00:00` and
+    /// RFC 3339 times as `Timestamp(Microsecond, None)`. Epoch times are
+    /// indistinguishable from other integers, so this is opt-in: columns of
+    /// 10-digit values are read as seconds and of 13-digit values as
+    /// milliseconds.
+    pub fn with_epoch_timestamps(mut self, epoch_timestamps: bool) -> Self {
+        self.epoch_timestamps = epoch_timestamps;
+        self
+    }
+
+    /// Parse dates and timestamps in every column with a strftime `format`
+    ///
+    /// For non-ISO exports such as `31/01/2024 10:00` (`"%d/%m/%Y %H:%M"`).
+    /// Inference reads columns matching a format with a time as
+    /// `Timestamp(Microsecond, None)` and a date-only format as `Date32`.
+    /// Values in other layouts are not recognised as temporal.
+    pub fn with_timestamp_format(mut self, format: impl Into<String>) -> Self {
+        self.timestamp_format = Some(format.into());
+        self
+    }
+
+    /// Parse dates and timestamps in `column` with a strftime `format`
+    ///
+    /// Takes precedence over `with_timestamp_format` for that column.
+    pub fn with_column_timestamp_format(
+        mut self,
+        column: impl Into<String>,
+        format: impl Into<String>,
+    ) -> Self {
+        self.column_timestamp_formats.insert(column.into(), format.into());
+        self
+    }
+
+    /// The strftime format configured for `column`'s dates and timestamps
+    pub(crate) fn timestamp_format(&self, column: &str) -> Option<&str> {
+        self.column_timestamp_formats
+            .get(column)
+            .or(self.timestamp_format.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Treat any run of spaces and tabs as a single field separator
+    ///
+    /// Intended for column-aligned text such as `ps` or log output. Leading and
+    /// trailing whitespace on each line is ignored and `delimiter` is unused.
+    pub fn with_whitespace_delimited(mut self, whitespace_delimited: bool) -> Self {
+        self.whitespace_delimited = whitespace_delimited;
+        self
+    }
+
+    /// Treat runs of consecutive delimiters as a single separator
+    ///
+    /// For legacy feeds where `a,,b` means the two fields `a` and `b` rather
+    /// than three fields with an empty one in the middle.
+    pub fn with_collapse_delimiters(mut self, collapse: bool) -> Self {
+        self.collapse_delimiters = collapse;
+        self
+    }
+
+    /// Set the character that escapes a quote inside a quoted field
+    ///
+    /// MySQL's `SELECT ... INTO OUTFILE` and many PHP exports write `\"` instead
+    /// of doubling quotes; read them with `Some(b'\\')`. Doubled quotes are
+    /// still accepted.
+    pub fn with_escape(mut self, escape: Option<u8>) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Set a single byte that ends each record
+    ///
+    /// By default records end at `\n`, `\r\n` or a bare `\r`, which covers Unix,
+    /// Windows and classic Mac files. Set a byte such as `b'~'` or `0x1E` for
+    /// mainframe exports that end records with another character; `\n` and
+    /// `\r` are then ordinary characters.
+    pub fn with_terminator(mut self, terminator: Option<u8>) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Declare whether quoted values may span lines
+    ///
+    /// Set this to `false` for files known to have no line breaks inside
+    /// quoted values, so large uncompressed files are split into byte ranges
+    /// scanned in parallel, each starting after the first line break in its
+    /// range. A line break inside a quoted value would be taken for the end
+    /// of a record, so by default files are read whole.
+    pub fn with_newlines_in_values(mut self, newlines_in_values: bool) -> Self {
+        self.newlines_in_values = newlines_in_values;
+        self
+    }
+
+    /// Byte ending each line, for code splitting input into lines itself
+    pub(crate) fn line_terminator(&self) -> u8 {
+        self.terminator.unwrap_or(b'\n')
+    }
+
+    /// Set values treated as null in every column (e.g. `"NA"`, `"NULL"`)
+    pub fn with_null_values(mut self, values: Vec<String>) -> Self {
+        self.null_values = values;
+        self
+    }
+
+    /// Set values treated as null only in the given column
+    ///
+    /// These apply in addition to the global `null_values`, e.g. `-999` as a
+    /// missing-reading marker in a `temperature` column.
+    pub fn with_column_null_values(mut self, column: impl Into<String>, values: Vec<String>) -> Self {
+        self.column_null_values.insert(column.into(), values);
+        self
+    }
+
+    /// Treat values matching `pattern` as null in every column
+    ///
+    /// Saves enumerating every spelling of a marker, e.g. `^(NA|n/a|-)$`.
+    /// Anchor the pattern to match whole values; it applies in addition to
+    /// `null_values`. Fails if `pattern` is not a valid regex.
+    pub fn with_null_regex(mut self, pattern: &str) -> Result<Self> {
+        let pattern = Regex::new(pattern).map_err(|e| {
+            DataFusionError::Configuration(format!("Invalid null regex '{}': {}", pattern, e))
+        })?;
+        self.null_regex = Some(pattern);
+        Ok(self)
+    }
+
+    /// Check whether a raw value in a column is one of the configured null tokens
+    pub(crate) fn is_null_value(&self, column: &str, value: &str) -> bool {
+        self.null_values.iter().any(|v| v == value)
+            || self
+                .column_null_values
+                .get(column)
+                .is_some_and(|values| values.iter().any(|v| v == value))
+            || self.null_regex.as_ref().is_some_and(|regex| regex.is_match(value))
+    }
+
+    /// Set the values read as `true`, e.g. `yes`, `Y` or `1`
+    ///
+    /// Replaces the default `true`. Matching ignores case and applies to
+    /// inference too: a column whose values are all true or false values is
+    /// read as `Boolean`, so listing `1` and `0` turns 0/1 columns into booleans.
+    pub fn with_true_values(mut self, values: Vec<String>) -> Self {
+        self.true_values = values;
+        self
+    }
+
+    /// Set the values read as `false`, e.g. `no`, `N` or `0`; replaces the default `false`
+    pub fn with_false_values(mut self, values: Vec<String>) -> Self {
+        self.false_values = values;
+        self
+    }
+
+    /// The boolean a value stands for, if it is one of the true or false values
+    pub(crate) fn parse_bool(&self, value: &str) -> Option<bool> {
+        let matches = |values: &[String]| values.iter().any(|v| v.eq_ignore_ascii_case(value));
+        if matches(&self.true_values) {
+            Some(true)
+        } else if matches(&self.false_values) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Add a regex find/replace rule for a column
+    ///
+    /// Rules run in the order they are added, before null detection and type
+    /// parsing (including schema inference), e.g. to strip units such as `" kg"`
+    /// or footnote markers such as `"[1]"`. Fails if `pattern` is not a valid regex.
+    pub fn with_value_transform(
+        mut self,
+        column: impl Into<String>,
+        pattern: &str,
+        replacement: impl Into<String>,
+    ) -> Result<Self> {
+        let column = column.into();
+        let pattern = Regex::new(pattern).map_err(|e| {
+            DataFusionError::Configuration(format!(
+                "Invalid value transform pattern for column '{}': {}",
+                column, e
+            ))
+        })?;
+        self.value_transforms
+            .entry(column)
+            .or_default()
+            .push(ValueTransform {
+                pattern,
+                replacement: replacement.into(),
+            });
+        Ok(self)
+    }
+
+    /// Parse a column with a user-defined callback instead of the built-in parsers
+    ///
+    /// The column gets type `data_type` without inference. The callback is
+    /// called for every non-null value and must return a scalar of that type;
+    /// an error from the callback fails the scan.
+    pub fn with_column_parser(
+        mut self,
+        column: impl Into<String>,
+        data_type: DataType,
+        parser: impl Fn(&str) -> Result<ScalarValue> + Send + Sync + 'static,
+    ) -> Self {
+        self.column_parsers
+            .insert(column.into(), ColumnParser::new(data_type, parser));
+        self
+    }
+
+    /// Read a column as a dictionary with a fixed set of categories
+    ///
+    /// Every batch and file shares the same dictionary, which keeps group-bys
+    /// and joins on the column fast and consistent. Values outside
+    /// `categories` are handled by `policy`.
+    pub fn with_categorical(
+        mut self,
+        column: impl Into<String>,
+        categories: impl IntoIterator<Item = impl Into<String>>,
+        policy: UnseenCategoryPolicy,
+    ) -> Self {
+        self.categorical_columns
+            .insert(column.into(), Categories::new(categories, policy));
+        self
+    }
+
+    /// Decode a column of hex or base64 text, such as hashes or IDs, as `Binary`
+    ///
+    /// The column is not inferred. Values that don't decode are read as null.
+    pub fn with_binary_column(mut self, column: impl Into<String>, encoding: BinaryEncoding) -> Self {
+        let binary = BinaryColumn { encoding, byte_width: None };
+        self.binary_columns.insert(column.into(), binary);
+        self
+    }
+
+    /// Decode a column of hex or base64 text as `FixedSizeBinary(byte_width)`
+    ///
+    /// Suits fixed-length values such as SHA-256 hashes (32 bytes); values
+    /// that don't decode to exactly `byte_width` bytes are read as null.
+    pub fn with_fixed_size_binary_column(
+        mut self,
+        column: impl Into<String>,
+        encoding: BinaryEncoding,
+        byte_width: i32,
+    ) -> Self {
+        let binary = BinaryColumn { encoding, byte_width: Some(byte_width) };
+        self.binary_columns.insert(column.into(), binary);
+        self
+    }
+
+    /// Add a `name` column holding the last-modified time of each row's file
+    ///
+    /// The column is a UTC `Timestamp(Microsecond)` taken from the object
+    /// store listing, so incremental readers can filter on arrival time, e.g.
+    /// `WHERE _modified > '2024-06-01T00:00:00Z'`. It is appended after the
+    /// file's columns and ignored by inference and drift checks.
+    pub fn with_file_modified_column(mut self, name: impl Into<String>) -> Self {
+        self.file_modified_column = Some(name.into());
+        self
+    }
+
+    /// Add a `name` column holding the byte offset at which each record starts
+    ///
+    /// The `UInt64` offset lets downstream pipelines re-read a record exactly
+    /// or resume after it. Offsets count from the start of the decompressed
+    /// contents; with `whitespace_delimited` or `collapse_delimiters` they
+    /// refer to the input after separator runs are collapsed.
+    pub fn with_byte_offset_column(mut self, name: impl Into<String>) -> Self {
+        self.byte_offset_column = Some(name.into());
+        self
+    }
+
+    /// Add a `name` column, e.g. `_line_number`, holding the line each record starts on
+    ///
+    /// The `UInt64` line is 1-based and counts preamble and header lines, so
+    /// it matches what an editor shows for the file, e.g. to report or
+    /// re-extract problem rows.
+    pub fn with_line_number_column(mut self, name: impl Into<String>) -> Self {
+        self.line_number_column = Some(name.into());
+        self
+    }
+
+    /// Whether `column` is filled by the scan rather than read from the file
+    pub(crate) fn is_virtual_column(&self, column: &str) -> bool {
+        self.file_modified_column.as_deref() == Some(column)
+            || self.byte_offset_column.as_deref() == Some(column)
+            || self.line_number_column.as_deref() == Some(column)
+    }
+
+    /// Append the columns filled by the scan to an inferred file schema
+    pub(crate) fn with_virtual_columns(&self, schema: SchemaRef) -> SchemaRef {
+        if self.file_modified_column.is_none()
+            && self.byte_offset_column.is_none()
+            && self.line_number_column.is_none()
+        {
+            return schema;
+        }
+        let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+        if let Some(name) = &self.file_modified_column {
+            fields.push(Field::new(
+                name,
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                true,
+            ));
+        }
+        if let Some(name) = &self.byte_offset_column {
+            fields.push(Field::new(name, DataType::UInt64, true));
+        }
+        if let Some(name) = &self.line_number_column {
+            fields.push(Field::new(name, DataType::UInt64, true));
+        }
+        Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+    }
+
+    /// The type declared for a column by a parser, categories or binary encoding, bypassing inference
+    pub(crate) fn declared_type(&self, column: &str) -> Option<DataType> {
+        if let Some(parser) = self.column_parsers.get(column) {
+            return Some(parser.data_type().clone());
+        }
+        if let Some(binary) = self.binary_columns.get(column) {
+            return Some(binary.data_type());
+        }
+        self.categorical_columns
+            .get(column)
+            .map(|_| Categories::data_type())
+    }
+
+    /// Drop rows before Arrow conversion unless `filter` returns `true`
+    ///
+    /// Useful for cheap filtering that SQL cannot express, applied to every
+    /// record of the scan. Schema inference still sees all rows.
+    pub fn with_row_filter(
+        mut self,
+        filter: impl Fn(&RawRecord) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.row_filter = Some(RowFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Strip or replace ASCII control characters (including stray `\r`) in values
+    pub fn with_control_chars(mut self, policy: ControlCharPolicy) -> Self {
+        self.control_chars = policy;
+        self
+    }
+
+    /// Fail with a clear error when a record (or the header) has more fields than `max_fields`
+    ///
+    /// Guards against files where an unquoted delimiter inside binary or free
+    /// text splits a line into thousands of fields.
+    pub fn with_max_fields(mut self, max_fields: Option<usize>) -> Self {
+        self.max_fields = max_fields;
+        self
+    }
+
+    /// Cap the memory a single file scan may use (buffered input plus the current batch)
+    ///
+    /// Scans exceeding the limit fail with a `ResourcesExhausted` error instead
+    /// of growing without bound.
+    pub fn with_max_scan_memory(mut self, max_bytes: Option<usize>) -> Self {
+        self.max_scan_memory = max_bytes;
+        self
+    }
+
+    /// Only scan files whose last-modified time is after `watermark`
+    ///
+    /// Incremental jobs can store the time of their last run and pass it here
+    /// to read only new arrivals in a landing directory. The schema is still
+    /// inferred from the first listed file, whatever its age.
+    pub fn with_modified_since(mut self, watermark: DateTime<Utc>) -> Self {
+        self.modified_since = Some(watermark);
+        self
+    }
+
+    /// Read a multi-file table's files in `order`
+    ///
+    /// Object stores list files in no guaranteed order. With an order, files
+    /// are split into partitions as contiguous runs of the sorted list, so
+    /// each partition reads the same files in the same order on every run.
+    /// With one partition the table's rows come out in file order.
+    pub fn with_file_order(mut self, order: FileOrder) -> Self {
+        self.file_order = Some(order);
+        self
+    }
+
+    /// Stop every scan of the table after `rows` rows in total
+    ///
+    /// A safety valve for exploring unexpectedly large datasets: queries see
+    /// at most `rows` rows, as if the table were that long. Scans with a cap
+    /// produce a single output partition.
+    pub fn with_max_rows(mut self, rows: usize) -> Self {
+        self.max_rows = Some(rows);
+        self
+    }
+
+    /// Treat the first `rows` lines as a `key,value` preamble before the header
+    ///
+    /// Instrument and lab exports often start with lines such as
+    /// `Instrument,XRF-200` or `Operator,J. Doe`. They are skipped when
+    /// reading and their pairs become the inferred schema's metadata; see
+    /// [`SessionContextCsvExt::csv_preamble`](crate::SessionContextCsvExt::csv_preamble).
+    pub fn with_preamble_rows(mut self, rows: usize) -> Self {
+        self.preamble_rows = rows;
+        self
+    }
+
+    /// Cache the listing of a directory table for `ttl`
+    ///
+    /// Repeated queries reuse the cached file list instead of re-listing the
+    /// object store. Files added by other writers appear once the TTL expires;
+    /// `INSERT`s through the table are visible immediately.
+    pub fn with_list_cache_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.list_cache_ttl = ttl;
+        self
+    }
+
+    /// Cap the number of concurrent requests to the table's object store
+    ///
+    /// Scans over thousands of small files otherwise issue a GET per file
+    /// from every partition at once, which can trip rate limits or exhaust
+    /// sockets. Requests beyond the cap wait for a slot, so partitions still
+    /// overlap I/O up to the limit. The cap applies to the store as
+    /// registered in the session's runtime, so other tables on it share it;
+    /// registering a table with a different cap on the same store fails.
+    pub fn with_max_concurrent_requests(mut self, max_requests: Option<usize>) -> Self {
+        self.max_concurrent_requests = max_requests;
+        self
+    }
+
+    /// Build the store for the table's `http(s)://` paths with `config`
+    ///
+    /// The store replaces any registered for the same host, so later tables
+    /// on the host use it too. Without a config, a store with default
+    /// timeouts and retries is registered unless one exists.
+    pub fn with_http_config(mut self, config: HttpConfig) -> Self {
+        self.http_config = Some(config);
+        self
+    }
+
+    /// Download up to `files` files ahead of the one being parsed in each partition
+    ///
+    /// Hides object store latency when a partition reads many files in
+    /// sequence, at the cost of holding up to `files` extra files in memory
+    /// per partition.
+    pub fn with_prefetch_files(mut self, files: usize) -> Self {
+        self.prefetch_files = files;
+        self
+    }
+
+    /// Parse each file as its chunks arrive instead of downloading it first
+    ///
+    /// Memory per file stays bounded regardless of its size, so multi-GB
+    /// remote files can be scanned. Files are then never prefetched, and
+    /// `max_scan_memory` only bounds the batches built. Whitespace-delimited
+    /// and collapsed-delimiter files are still downloaded whole.
+    pub fn with_streaming_reads(mut self, streaming: bool) -> Self {
+        self.streaming_reads = streaming;
+        self
+    }
+
+    /// Declare a `key=value` path segment as a partition column of type `data_type`
+    ///
+    /// Columns are matched in directory order, so declare the outermost first.
+    /// Values are parsed into the declared type (e.g. `Int32`, `Date32`) so
+    /// range predicates compare numerically and prune partitions correctly.
+    pub fn with_partition_column(mut self, name: impl Into<String>, data_type: DataType) -> Self {
+        self.partition_columns.push((name.into(), data_type));
+        self
+    }
+
+    /// Build the table schema from every file instead of only the first
+    ///
+    /// Columns are merged by header name, so files written before a column
+    /// was added still load, with nulls in that column. Conflicting types are
+    /// widened (`Int64` and `Float64` to `Float64`, anything else to `Utf8`).
+    /// Inference reads every file, so the first query on a large prefix is slower.
+    pub fn with_union_schema(mut self, union_schema: bool) -> Self {
+        self.union_schema = union_schema;
+        self
+    }
+
+    /// Compare each file's header with the table schema when it is scanned
+    ///
+    /// A file whose columns are missing, unexpected or reordered fails the
+    /// query with a diff naming the file, instead of silently reading nulls
+    /// for renamed columns. With `union_schema`, missing columns are allowed
+    /// and order is not checked. Has no effect on files without a header.
+    pub fn with_schema_drift_check(mut self, check: bool) -> Self {
+        self.schema_drift_check = check;
+        self
+    }
+
+    /// Keep checking inferred types past the inference sample
+    ///
+    /// Types are inferred from the first rows, and values that don't fit,
+    /// such as a later `3.5` in an `Int64` column or `abc` in a `Float64`
+    /// one, read as null. With promotion, inference checks the rest of each
+    /// inferred file and widens such columns (`Int64` to `Float64` to `Utf8`)
+    /// before the table's schema is fixed.
+    ///
+    /// Columns are not promoted while a query runs, as the schema of a scan
+    /// is fixed when it is planned. Values that still don't fit, e.g. in
+    /// files that were not inferred, are counted by the scan's
+    /// `type_mismatches` metric.
+    pub fn with_type_promotion(mut self, type_promotion: bool) -> Self {
+        self.type_promotion = type_promotion;
+        self
+    }
+
+    /// Infer types from every row of every file, then read with that schema
+    ///
+    /// Sampled inference can guess a type that a later row or file breaks.
+    /// A two-pass read makes a full inference pass over all files first, so
+    /// the types always fit, at the cost of reading everything twice. Ignores
+    /// `schema_infer_max_rec`.
+    pub fn with_two_pass(mut self, two_pass: bool) -> Self {
+        self.two_pass = two_pass;
+        self
+    }
+
+    /// Use a `.schema.json` sidecar as the schema when one exists
+    ///
+    /// The sidecar next to the first file (`orders.csv.schema.json` for
+    /// `orders.csv`) takes precedence over inference; without one, the schema
+    /// is inferred as usual. See
+    /// [`SessionContextCsvExt::write_csv_schema_sidecar`](crate::SessionContextCsvExt::write_csv_schema_sidecar)
+    /// for the format.
+    pub fn with_schema_sidecar(mut self, schema_sidecar: bool) -> Self {
+        self.schema_sidecar = schema_sidecar;
+        self
+    }
+
+    /// Prepare a raw value for null detection and parsing
+    ///
+    /// Applies the column's value transforms, then control-character handling.
+    pub(crate) fn prepare_value<'a>(&self, column: &str, value: &'a str) -> Cow<'a, str> {
+        self.control_chars.apply(self.transform_value(column, value))
+    }
+
+    /// Apply the column's value transforms to a raw value
+    pub(crate) fn transform_value<'a>(&self, column: &str, value: &'a str) -> Cow<'a, str> {
+        let Some(transforms) = self.value_transforms.get(column) else {
+            return Cow::Borrowed(value);
+        };
+
+        let mut value = Cow::Borrowed(value);
+        for transform in transforms {
+            if let Cow::Owned(replaced) =
+                transform.pattern.replace_all(&value, transform.replacement.as_str())
+            {
+                value = Cow::Owned(replaced);
+            }
+        }
+        value
+    }
+
     /// Get file extension with leading dot
     pub(crate) fn file_extension_with_dot(&self) -> String {
         if self.file_extension.starts_with('.') {
@@ -90,17 +1385,47 @@ impl CsvFormatOptions {
             format!(".{}", self.file_extension)
         }
     }
+
+    /// Get file extension with leading dot and the compression suffix, if any
+    pub(crate) fn file_extension_with_compression(&self) -> String {
+        let ext = self.file_extension_with_dot();
+        let suffix = self.compression.file_suffix();
+        if ext.ends_with(suffix) {
+            ext
+        } else {
+            format!("{}{}", ext, suffix)
+        }
+    }
 }
 
 /// Independent CSV file format implementation
 #[derive(Debug, Clone)]
 pub struct CsvFormat {
     options: CsvFormatOptions,
+    store_url: Option<ObjectStoreUrl>,
 }
 
 impl CsvFormat {
     pub fn new(options: CsvFormatOptions) -> Self {
-        Self { options }
+        Self {
+            options,
+            store_url: None,
+        }
+    }
+
+    /// Set the object store the format's files are read from
+    ///
+    /// Inferred schemas are cached in a session's [`CsvSchemaCache`] only when
+    /// the store is known, since the same path can name different files in
+    /// different stores.
+    pub fn with_store_url(mut self, store_url: ObjectStoreUrl) -> Self {
+        self.store_url = Some(store_url);
+        self
+    }
+
+    /// Options the format reads files with
+    pub fn options(&self) -> &CsvFormatOptions {
+        &self.options
     }
 }
 
@@ -123,7 +1448,7 @@ impl FileFormat for CsvFormat {
     }
 
     fn get_ext(&self) -> String {
-        self.options.file_extension_with_dot()
+        self.options.file_extension_with_compression()
     }
 
     fn get_ext_with_compression(
@@ -135,55 +1460,253 @@ impl FileFormat for CsvFormat {
 
     async fn infer_schema(
         &self,
-        _state: &SessionState,
+        state: &SessionState,
         store: &Arc<dyn ObjectStore>,
         objects: &[ObjectMeta],
     ) -> Result<SchemaRef> {
-        if objects.is_empty() {
-            return Ok(Arc::new(Schema::empty()));
-        }
-
-        // Read the first file to infer schema
-        let obj = &objects[0];
-        let bytes = store
-            .get(&obj.location)
-            .await
-            .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?
-            .bytes()
-            .await
-            .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?;
-
-        // Use our independent schema inference
-        let schema = physical_exec::infer_schema(&bytes, &self.options).await?;
-
-        Ok(Arc::new(schema))
+        let cache = state.config().get_extension::<CsvSchemaCache>();
+        let cache = cache.as_deref().zip(self.store_url.as_ref());
+        let schema = infer_schema_from_store(store, objects, &self.options, cache).await?;
+        Ok(self.options.with_virtual_columns(schema))
     }
 
     async fn infer_stats(
         &self,
         _state: &SessionState,
-        _store: &Arc<dyn ObjectStore>,
+        store: &Arc<dyn ObjectStore>,
         table_schema: SchemaRef,
-        _object: &ObjectMeta,
+        object: &ObjectMeta,
     ) -> Result<Statistics> {
-        // Return unknown statistics for now
-        Ok(Statistics::new_unknown(&table_schema))
+        // Compressed files can't be sampled at an offset
+        let mut statistics = Statistics::new_unknown(&table_schema);
+        if self.options.compression != CsvCompression::Uncompressed {
+            return Ok(statistics);
+        }
+        let rows = estimate_object_row_count(store, object, &self.options).await?;
+        statistics.num_rows = Precision::Inexact(rows as usize);
+        statistics.total_byte_size = Precision::Inexact(object.size);
+        Ok(statistics)
     }
 
     async fn create_physical_plan(
         &self,
         _state: &SessionState,
         conf: FileScanConfig,
-        _filters: Option<&Arc<dyn PhysicalExpr>>,
+        filters: Option<&Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        // Create our custom CSV execution plan; no partition needs more than the row cap
+        let mut conf = conf;
+        if let Some(max_rows) = self.options.max_rows {
+            conf.limit = Some(conf.limit.map_or(max_rows, |limit| limit.min(max_rows)));
+        }
+        // A row cap applies to the files' first rows, not the first matching ones
+        let filters = filters.filter(|_| self.options.max_rows.is_none());
+        let exec = CsvExec::new(conf, self.options.clone()).with_predicate(filters)?;
+        Ok(cap_rows(Arc::new(exec), &self.options))
+    }
+
+    async fn create_writer_physical_plan(
+        &self,
+        input: Arc<dyn ExecutionPlan>,
+        _state: &SessionState,
+        conf: FileSinkConfig,
+        order_requirements: Option<LexRequirement>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        // Create our custom CSV execution plan
-        let exec = CsvExec::new(conf, self.options.clone());
-        Ok(Arc::new(exec))
+        if conf.insert_op == InsertOp::Replace {
+            return Err(DataFusionError::NotImplemented(
+                "REPLACE INTO is not supported for CSV tables".to_string(),
+            ));
+        }
+
+        let sink_schema = conf.output_schema().clone();
+        let sink = Arc::new(CsvSink::new(conf, self.options.clone()));
+
+        Ok(Arc::new(DataSinkExec::new(
+            input,
+            sink,
+            sink_schema,
+            order_requirements,
+        )))
+    }
+}
+
+/// Infer a schema from the first of `objects`, or all of them with
+/// `union_schema` or `two_pass`
+///
+/// Per-file schemas are looked up in and added to `cache` when one is given,
+/// keyed by the URL of the store the objects were listed from.
+/// With `schema_sidecar`, the first file's sidecar replaces inference.
+pub(crate) async fn infer_schema_from_store(
+    store: &Arc<dyn ObjectStore>,
+    objects: &[ObjectMeta],
+    options: &CsvFormatOptions,
+    cache: Option<(&CsvSchemaCache, &ObjectStoreUrl)>,
+) -> Result<SchemaRef> {
+    if options.schema_sidecar
+        && let Some(first) = objects.first()
+        && let Some(schema) = sidecar::read_sidecar(store, &first.location, options).await?
+    {
+        return Ok(Arc::new(schema));
+    }
+
+    let objects = if options.union_schema || options.two_pass {
+        objects
+    } else {
+        &objects[..objects.len().min(1)]
+    };
+
+    let mut schemas = Vec::with_capacity(objects.len());
+    for obj in objects {
+        if let Some(schema) = cache.and_then(|(cache, store_url)| cache.get(store_url, obj, options)) {
+            schemas.push(schema);
+            continue;
+        }
+
+        let schema = Arc::new(infer_object_schema(store, obj, options).await?);
+        if let Some((cache, store_url)) = cache {
+            cache.put(store_url, obj, options, schema.clone());
+        }
+        schemas.push(schema);
+    }
+
+    match schemas.len() {
+        0 => Ok(Arc::new(Schema::empty())),
+        1 => Ok(schemas.remove(0)),
+        _ if options.union_schema => Ok(Arc::new(physical_exec::union_schemas(
+            schemas.iter().map(AsRef::as_ref),
+        ))),
+        _ => {
+            // Files are read by position, so widen types column by column
+            let mut fields: Vec<Field> =
+                schemas[0].fields().iter().map(|f| f.as_ref().clone()).collect();
+            for schema in &schemas[1..] {
+                for (field, other) in fields.iter_mut().zip(schema.fields()) {
+                    let data_type = physical_exec::widen_type(field.data_type(), other.data_type());
+                    *field = Field::new(field.name(), data_type, true);
+                }
+            }
+            // Table metadata, such as a preamble, comes from the first file
+            Ok(Arc::new(Schema::new_with_metadata(fields, schemas[0].metadata().clone())))
+        }
+    }
+}
+
+/// Infer the schema of a single file
+async fn infer_object_schema(
+    store: &Arc<dyn ObjectStore>,
+    obj: &ObjectMeta,
+    options: &CsvFormatOptions,
+) -> Result<Schema> {
+    let bytes = physical_exec::fetch_decompressed(store, &obj.location, options).await?;
+
+    // Use our independent schema inference
+    physical_exec::infer_schema(&bytes, options)
+        .await
+        .map_err(|e| locate_error(e, obj.location.as_ref(), &Schema::empty()))
+}
+
+/// Stable fingerprint of a schema's column names, types and order
+///
+/// The fingerprint is a 16-digit hex FNV-1a hash of each column's name and
+/// type; nullability and metadata are ignored. Types are hashed in a
+/// spelling of this crate's own, so the fingerprint depends on neither the
+/// Rust or arrow version nor the platform, and can be stored and compared
+/// across runs to detect upstream schema changes.
+pub fn schema_fingerprint(schema: &Schema) -> String {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+    for field in schema.fields() {
+        let column = format!("{}:{}\n", field.name(), fingerprint_type(field.data_type()));
+        for byte in column.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Spelling of `data_type` hashed by [`schema_fingerprint`]
+///
+/// Written out rather than taken from arrow's `Display`, which may change
+/// between arrow versions. Types without children are spelled as arrow 53
+/// displays them.
+fn fingerprint_type(data_type: &DataType) -> String {
+    let unit = |unit: &TimeUnit| match unit {
+        TimeUnit::Second => "Second",
+        TimeUnit::Millisecond => "Millisecond",
+        TimeUnit::Microsecond => "Microsecond",
+        TimeUnit::Nanosecond => "Nanosecond",
+    };
+    let field = |field: &Field| format!("{}: {}", field.name(), fingerprint_type(field.data_type()));
+    let fields = |fields: &Fields| fields.iter().map(|f| field(f)).collect::<Vec<_>>().join(", ");
+    match data_type {
+        DataType::Null => "Null".into(),
+        DataType::Boolean => "Boolean".into(),
+        DataType::Int8 => "Int8".into(),
+        DataType::Int16 => "Int16".into(),
+        DataType::Int32 => "Int32".into(),
+        DataType::Int64 => "Int64".into(),
+        DataType::UInt8 => "UInt8".into(),
+        DataType::UInt16 => "UInt16".into(),
+        DataType::UInt32 => "UInt32".into(),
+        DataType::UInt64 => "UInt64".into(),
+        DataType::Float16 => "Float16".into(),
+        DataType::Float32 => "Float32".into(),
+        DataType::Float64 => "Float64".into(),
+        DataType::Date32 => "Date32".into(),
+        DataType::Date64 => "Date64".into(),
+        DataType::Binary => "Binary".into(),
+        DataType::LargeBinary => "LargeBinary".into(),
+        DataType::BinaryView => "BinaryView".into(),
+        DataType::Utf8 => "Utf8".into(),
+        DataType::LargeUtf8 => "LargeUtf8".into(),
+        DataType::Utf8View => "Utf8View".into(),
+        DataType::Timestamp(time_unit, None) => format!("Timestamp({}, None)", unit(time_unit)),
+        DataType::Timestamp(time_unit, Some(tz)) => {
+            format!("Timestamp({}, Some(\"{}\"))", unit(time_unit), tz)
+        }
+        DataType::Time32(time_unit) => format!("Time32({})", unit(time_unit)),
+        DataType::Time64(time_unit) => format!("Time64({})", unit(time_unit)),
+        DataType::Duration(time_unit) => format!("Duration({})", unit(time_unit)),
+        DataType::Interval(IntervalUnit::YearMonth) => "Interval(YearMonth)".into(),
+        DataType::Interval(IntervalUnit::DayTime) => "Interval(DayTime)".into(),
+        DataType::Interval(IntervalUnit::MonthDayNano) => "Interval(MonthDayNano)".into(),
+        DataType::FixedSizeBinary(size) => format!("FixedSizeBinary({})", size),
+        DataType::Decimal128(precision, scale) => format!("Decimal128({}, {})", precision, scale),
+        DataType::Decimal256(precision, scale) => format!("Decimal256({}, {})", precision, scale),
+        DataType::Dictionary(key, value) => {
+            format!("Dictionary({}, {})", fingerprint_type(key), fingerprint_type(value))
+        }
+        DataType::List(item) => format!("List({})", field(item)),
+        DataType::ListView(item) => format!("ListView({})", field(item)),
+        DataType::LargeList(item) => format!("LargeList({})", field(item)),
+        DataType::LargeListView(item) => format!("LargeListView({})", field(item)),
+        DataType::FixedSizeList(item, size) => format!("FixedSizeList({}, {})", field(item), size),
+        DataType::Struct(children) => format!("Struct({})", fields(children)),
+        DataType::Union(children, mode) => {
+            let mode = match mode {
+                UnionMode::Sparse => "Sparse",
+                UnionMode::Dense => "Dense",
+            };
+            let children: Vec<String> = children.iter().map(|(id, f)| format!("{} {}", id, field(f))).collect();
+            format!("Union({}, {})", mode, children.join(", "))
+        }
+        DataType::Map(entries, sorted) => format!("Map({}, {})", field(entries), sorted),
+        DataType::RunEndEncoded(run_ends, values) => {
+            format!("RunEndEncoded({}, {})", field(run_ends), field(values))
+        }
     }
 }
 
 /// Helper to detect file extension from path
+///
+/// Directory paths (ending in `/`) have no extension, even if a directory name contains a dot.
 pub(crate) fn detect_file_extension(path: &str) -> Option<String> {
+    if path.ends_with('/') {
+        return None;
+    }
     std::path::Path::new(path)
         .extension()
         .and_then(|e| e.to_str())
@@ -0,0 +1,795 @@
+//! CSV data sink for INSERT statements
+//!
+//! This module implements DataFusion's DataSink trait so that listing tables
+//! backed by this crate's CSV format can be written to with `INSERT INTO`
+//! (append) and `INSERT OVERWRITE` (replace all existing files).
+//!
+//! Output can be gzip, zstd or bzip2 compressed via [`CsvCompression`].
+//! Files are first written under a temporary name and renamed into place once
+//! complete, so concurrent readers never observe a partially written CSV.
+//! An overwrite is not atomic, though: the previous files are deleted one by
+//! one after the new file is in place, and a failed delete leaves their rows
+//! in the table, with the error naming the files left behind.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use arrow::record_batch::RecordBatch;
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, BooleanArray};
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_schema::DataType;
+use async_trait::async_trait;
+use datafusion::datasource::physical_plan::FileSinkConfig;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::dml::InsertOp;
+use datafusion::physical_plan::insert::DataSink;
+use datafusion::physical_plan::metrics::MetricsSet;
+use datafusion::physical_plan::{DisplayAs, DisplayFormatType, SendableRecordBatchStream};
+use futures::{StreamExt, TryStreamExt};
+use object_store::buffered::BufWriter;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::file_format::{CsvCompression, CsvFormatOptions};
+use crate::physical_exec;
+
+/// Counter used to keep output file names unique within a process
+static FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Data sink that writes record batches as a new CSV file in the table directory
+pub struct CsvSink {
+    /// File sink configuration (target table path, existing files, insert mode)
+    config: FileSinkConfig,
+    /// CSV format options used for the header and delimiter
+    options: CsvFormatOptions,
+}
+
+impl CsvSink {
+    pub fn new(config: FileSinkConfig, options: CsvFormatOptions) -> Self {
+        Self { config, options }
+    }
+
+    /// Write all batches to `path` after `preamble`, returning the number of rows written
+    ///
+    /// The object is only created once the first non-empty batch arrives.
+    async fn write_batches(
+        &self,
+        data: &mut SendableRecordBatchStream,
+        store: &Arc<dyn ObjectStore>,
+        path: &Path,
+        preamble: &[u8],
+    ) -> Result<u64> {
+        self.options.compression.check_enabled()?;
+        let mut writer: Option<Box<dyn AsyncWrite + Send + Unpin>> = None;
+        let mut row_count: u64 = 0;
+
+        while let Some(batch) = data.next().await {
+            let batch = batch?;
+            if batch.num_rows() == 0 {
+                continue;
+            }
+
+            let start_of_file = writer.is_none();
+            let mut bytes = encode_batch(&batch, &self.options, start_of_file)?;
+            if start_of_file {
+                bytes.splice(0..0, preamble.iter().copied());
+            }
+
+            let writer = writer.get_or_insert_with(|| {
+                let buf_writer = BufWriter::new(store.clone(), path.clone());
+                compressed_writer(buf_writer, self.options.compression)
+            });
+            writer
+                .write_all(&bytes)
+                .await
+                .map_err(|e| DataFusionError::Execution(format!("Failed to write CSV: {}", e)))?;
+
+            row_count += batch.num_rows() as u64;
+        }
+
+        if let Some(mut writer) = writer {
+            writer
+                .shutdown()
+                .await
+                .map_err(|e| DataFusionError::Execution(format!("Failed to write CSV: {}", e)))?;
+        }
+
+        Ok(row_count)
+    }
+
+    /// The `preamble_rows` lines to start a new file with
+    ///
+    /// Copied from the table's first existing file, so the preamble metadata
+    /// survives an overwrite; blank lines stand in when the table has no files.
+    async fn preamble(&self, store: &Arc<dyn ObjectStore>) -> Result<Vec<u8>> {
+        if self.options.preamble_rows == 0 {
+            return Ok(Vec::new());
+        }
+        let terminator = self.options.line_terminator();
+
+        let mut preamble = match self.config.file_groups.first() {
+            Some(file) => {
+                let bytes =
+                    physical_exec::fetch_decompressed(store, &file.object_meta.location, &self.options)
+                        .await?;
+                physical_exec::split_preamble(&bytes, &self.options).0.to_vec()
+            }
+            None => Vec::new(),
+        };
+        if preamble.last().is_some_and(|&b| b != terminator) {
+            preamble.push(terminator);
+        }
+        let lines = preamble.iter().filter(|&&b| b == terminator).count();
+        preamble.resize(preamble.len() + self.options.preamble_rows.saturating_sub(lines), terminator);
+        Ok(preamble)
+    }
+
+    /// Build a unique path for the next output file in the table directory
+    fn output_path(&self) -> Path {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let counter = FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let file_name = format!(
+            "part-{}-{}{}",
+            nanos,
+            counter,
+            self.options.file_extension_with_compression()
+        );
+        self.config.table_paths[0].prefix().child(file_name)
+    }
+}
+
+impl fmt::Debug for CsvSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CsvSink")
+            .field("insert_op", &self.config.insert_op)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl DisplayAs for CsvSink {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "CsvSink: mode={}", self.config.insert_op.name())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl DataSink for CsvSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64> {
+        if !self.config.table_partition_cols.is_empty() {
+            return Err(DataFusionError::NotImplemented(
+                "Writing to partitioned CSV tables is not supported".to_string(),
+            ));
+        }
+
+        let store = context
+            .runtime_env()
+            .object_store(&self.config.object_store_url)?;
+
+        // Data is written to a temporary object that does not match the table's
+        // file extension, then promoted with a rename once fully written
+        let final_path = self.output_path();
+        let temp_path = Path::from(format!("{}.tmp", final_path));
+
+        let preamble = self.preamble(&store).await?;
+        let row_count = match self.write_batches(&mut data, &store, &temp_path, &preamble).await {
+            Ok(row_count) => row_count,
+            Err(e) => {
+                // Best-effort cleanup; the write error is what matters to the caller
+                let _ = store.delete(&temp_path).await;
+                return Err(e);
+            }
+        };
+
+        if row_count > 0 {
+            store
+                .rename(&temp_path, &final_path)
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        }
+
+        // Only remove the previous files once the new data is safely written.
+        // The deletes are not atomic: until they finish, readers see both the
+        // old and the new rows
+        if self.config.insert_op == InsertOp::Overwrite {
+            let mut left = Vec::new();
+            let mut first_error = None;
+            for file in &self.config.file_groups {
+                if let Err(e) = store.delete(&file.object_meta.location).await {
+                    left.push(file.object_meta.location.to_string());
+                    first_error.get_or_insert(e);
+                }
+            }
+            if let Some(e) = first_error {
+                let written = match row_count {
+                    0 => "no new file".to_string(),
+                    _ => final_path.to_string(),
+                };
+                return Err(DataFusionError::Execution(format!(
+                    "Overwrite wrote {} but could not delete {} of the previous files, \
+                     whose rows are still part of the table: {} ({})",
+                    written,
+                    left.len(),
+                    left.join(", "),
+                    e
+                )));
+            }
+        }
+
+        // Refresh a cached listing of the table so the next scan sees the change
+        if let Some(cache) = context.runtime_env().cache_manager.get_list_files_cache() {
+            let prefix = self.config.table_paths[0].prefix();
+            if cache.contains_key(prefix) {
+                let files = store
+                    .list(Some(prefix))
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .map_err(|e| DataFusionError::External(Box::new(e)))?;
+                cache.put(prefix, Arc::new(files));
+            }
+        }
+
+        Ok(row_count)
+    }
+}
+
+/// Wrap an object store writer with the encoder for the configured codec
+///
+/// Shutting down the returned writer finishes the compressed stream and then
+/// completes the upload.
+fn compressed_writer(
+    writer: BufWriter,
+    compression: CsvCompression,
+) -> Box<dyn AsyncWrite + Send + Unpin> {
+    match compression {
+        CsvCompression::Uncompressed => Box::new(writer),
+        #[cfg(feature = "gzip")]
+        CsvCompression::Gzip => Box::new(async_compression::tokio::write::GzipEncoder::new(writer)),
+        #[cfg(feature = "zstd")]
+        CsvCompression::Zstd => Box::new(async_compression::tokio::write::ZstdEncoder::new(writer)),
+        #[cfg(feature = "bzip2")]
+        CsvCompression::Bzip2 => Box::new(async_compression::tokio::write::BzEncoder::new(writer)),
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("disabled codecs are rejected before writing"),
+    }
+}
+
+/// Encode a record batch as CSV text
+///
+/// The batch that starts a file is preceded by the header, if the table has
+/// one, and by empty records standing in for the `skip_rows_after_header`
+/// rows, so that readers skip those instead of the first rows written.
+pub(crate) fn encode_batch(
+    batch: &RecordBatch,
+    options: &CsvFormatOptions,
+    start_of_file: bool,
+) -> Result<Vec<u8>> {
+    let mut builder = csv::WriterBuilder::new();
+    builder.delimiter(options.delimiter);
+    if let Some(terminator) = options.terminator {
+        builder.terminator(csv::Terminator::Any(terminator));
+    }
+    let mut writer = builder.from_writer(Vec::new());
+
+    let csv_err = |e: csv::Error| DataFusionError::Execution(format!("Failed to encode CSV: {}", e));
+
+    if start_of_file && options.has_header {
+        let schema = batch.schema();
+        writer
+            .write_record(schema.fields().iter().map(|f| f.name()))
+            .map_err(csv_err)?;
+    }
+    if start_of_file {
+        let placeholder = vec![""; batch.num_columns()];
+        for _ in 0..options.skip_rows_after_header {
+            writer.write_record(&placeholder).map_err(csv_err)?;
+        }
+    }
+
+    // Dates and timestamps are written in the strftime format their column is
+    // read with, so they parse back instead of becoming null
+    let formatters = batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, array)| {
+            let format = options.timestamp_format(field.name());
+            let format_options = FormatOptions::default()
+                .with_date_format(format)
+                .with_datetime_format(format)
+                .with_timestamp_format(format)
+                .with_timestamp_tz_format(format);
+            ArrayFormatter::try_new(array.as_ref(), &format_options)
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    // Fractional numbers use the table's decimal separator, so they read back
+    // unchanged instead of `1.234` becoming 1234 under a decimal comma
+    let decimal_separator = Some(options.decimal_separator as char).filter(|&c| c != '.');
+    let fractional: Vec<bool> = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            decimal_separator.is_some()
+                && (field.data_type().is_floating()
+                    || matches!(field.data_type(), DataType::Decimal128(..) | DataType::Decimal256(..)))
+        })
+        .collect();
+
+    // Booleans use the table's first true and false values, so they read back
+    // as booleans when those are spelled e.g. `Y` and `N`
+    let booleans: Vec<Option<&BooleanArray>> =
+        batch.columns().iter().map(|array| array.as_boolean_opt()).collect();
+    let true_value = options.true_values.first().map_or("true", String::as_str);
+    let false_value = options.false_values.first().map_or("false", String::as_str);
+
+    let mut record: Vec<String> = Vec::with_capacity(formatters.len());
+    for row in 0..batch.num_rows() {
+        record.clear();
+        for ((formatter, &fractional), boolean) in formatters.iter().zip(&fractional).zip(&booleans) {
+            if let Some(array) = boolean.filter(|array| array.is_valid(row)) {
+                let value = if array.value(row) { true_value } else { false_value };
+                record.push(value.to_string());
+                continue;
+            }
+            let value = formatter.value(row).try_to_string()?;
+            record.push(match decimal_separator {
+                Some(separator) if fractional => value.replace('.', separator.encode_utf8(&mut [0; 4])),
+                _ => value,
+            });
+        }
+        writer.write_record(&record).map_err(csv_err)?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| DataFusionError::Execution(format!("Failed to encode CSV: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_format::CsvFormat;
+    use crate::SessionContextCsvExt;
+    use datafusion::datasource::listing::{
+        ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+    };
+    use datafusion::prelude::SessionContext;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    async fn count_rows(ctx: &SessionContext, table: &str) -> Result<i64> {
+        let batches = ctx
+            .sql(&format!("SELECT count(*) FROM {}", table))
+            .await?
+            .collect()
+            .await?;
+        Ok(batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap()
+            .value(0))
+    }
+
+    #[tokio::test]
+    async fn test_insert_into_and_overwrite() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let mut file = File::create(temp_dir.path().join("initial.csv")).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,Alice").unwrap();
+        writeln!(file, "2,Bob").unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let ctx = SessionContext::new();
+        ctx.register_csv_file("people", &table_path).await?;
+
+        ctx.sql("INSERT INTO people VALUES (3, 'Charlie')")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(count_rows(&ctx, "people").await?, 3);
+
+        ctx.sql("INSERT OVERWRITE people VALUES (10, 'Dora'), (11, 'Eve')")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(count_rows(&ctx, "people").await?, 2);
+        assert!(!temp_dir.path().join("initial.csv").exists());
+
+        // No temporary files are left behind once writes complete
+        for entry in std::fs::read_dir(temp_dir.path()).unwrap() {
+            let name = entry.unwrap().file_name();
+            assert!(name.to_str().unwrap().ends_with(".csv"), "unexpected file {:?}", name);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_decimal_comma() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("prices.csv"), "item;price\nnut;1.234,50\n").unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_delimiter(b';').with_decimal_separator(b',');
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_options("prices", &table_path, options).await?;
+
+        ctx.sql("INSERT INTO prices VALUES ('bolt', 1.234)").await?.collect().await?;
+        let batches = ctx
+            .sql("SELECT item, price FROM prices ORDER BY item")
+            .await?
+            .collect()
+            .await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches).unwrap().to_string();
+        let expected = [
+            "+------+--------+",
+            "| item | price  |",
+            "+------+--------+",
+            "| bolt | 1.234  |",
+            "| nut  | 1234.5 |",
+            "+------+--------+",
+        ];
+        assert_eq!(formatted, expected.join("\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_custom_terminator() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("initial.csv"), "id,name;1,a;2,b;").unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_terminator(Some(b';'));
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+
+        ctx.sql("INSERT INTO t VALUES (3, 'c')").await?.collect().await?;
+        assert_eq!(count_rows(&ctx, "t").await?, 3);
+
+        let written = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.file_name().unwrap() != "initial.csv")
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(written).unwrap(), "id,name;3,c;");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_skip_rows_after_header() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("initial.csv"), "id,name\nnumber,text\n1,a\n").unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_skip_rows_after_header(1);
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+
+        ctx.sql("INSERT INTO t VALUES (2, 'b')").await?.collect().await?;
+        assert_eq!(count_rows(&ctx, "t").await?, 2);
+
+        let written = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.file_name().unwrap() != "initial.csv")
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(written).unwrap(), "id,name\n,\n2,b\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_custom_booleans() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("initial.csv"), "id,active\n1,Y\n2,N\n").unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new()
+            .with_true_values(vec!["Y".to_string()])
+            .with_false_values(vec!["N".to_string()]);
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+
+        ctx.sql("INSERT INTO t VALUES (3, true), (4, false), (5, NULL)")
+            .await?
+            .collect()
+            .await?;
+        let batches = ctx
+            .sql("SELECT id, active FROM t ORDER BY id")
+            .await?
+            .collect()
+            .await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches).unwrap().to_string();
+        let expected = [
+            "+----+--------+",
+            "| id | active |",
+            "+----+--------+",
+            "| 1  | true   |",
+            "| 2  | false  |",
+            "| 3  | true   |",
+            "| 4  | false  |",
+            "| 5  |        |",
+            "+----+--------+",
+        ];
+        assert_eq!(formatted, expected.join("\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_timestamp_format() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let initial = "ts,day\n31/01/2024 10:00,31/01/2024\n";
+        std::fs::write(temp_dir.path().join("initial.csv"), initial).unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new()
+            .with_timestamp_format("%d/%m/%Y %H:%M")
+            .with_column_timestamp_format("day", "%d/%m/%Y");
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_options("t", &table_path, options).await?;
+
+        ctx.sql("INSERT INTO t VALUES (TIMESTAMP '2024-02-01 11:30:00', DATE '2024-02-01')")
+            .await?
+            .collect()
+            .await?;
+        let batches = ctx
+            .sql("SELECT ts, day FROM t ORDER BY ts")
+            .await?
+            .collect()
+            .await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches).unwrap().to_string();
+        let expected = [
+            "+---------------------+------------+",
+            "| ts                  | day        |",
+            "+---------------------+------------+",
+            "| 2024-01-31T10:00:00 | 2024-01-31 |",
+            "| 2024-02-01T11:30:00 | 2024-02-01 |",
+            "+---------------------+------------+",
+        ];
+        assert_eq!(formatted, expected.join("\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_preamble() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let initial = "Instrument,XRF-200\nOperator,J. Doe\nid,value\n1,10\n";
+        std::fs::write(temp_dir.path().join("initial.csv"), initial).unwrap();
+
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_preamble_rows(2);
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_options("t", &table_path, options.clone()).await?;
+
+        ctx.sql("INSERT INTO t VALUES (2, 20)").await?.collect().await?;
+        assert_eq!(count_rows(&ctx, "t").await?, 2);
+
+        // The preamble outlives the files it was copied from
+        ctx.sql("INSERT OVERWRITE t VALUES (3, 30)").await?.collect().await?;
+        assert_eq!(count_rows(&ctx, "t").await?, 1);
+        let written = std::fs::read_dir(temp_dir.path()).unwrap().next().unwrap().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(written.path()).unwrap(),
+            "Instrument,XRF-200\nOperator,J. Doe\nid,value\n3,30\n"
+        );
+        let preamble = ctx.csv_preamble(&table_path, options).await?;
+        assert_eq!(preamble.get("Instrument").map(String::as_str), Some("XRF-200"));
+
+        Ok(())
+    }
+
+    /// Local store that refuses to delete files named `locked.csv`
+    #[derive(Debug)]
+    struct LockedStore {
+        inner: object_store::local::LocalFileSystem,
+    }
+
+    impl fmt::Display for LockedStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "LockedStore")
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for LockedStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: object_store::PutPayload,
+            opts: object_store::PutOptions,
+        ) -> object_store::Result<object_store::PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: object_store::PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn object_store::MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: object_store::GetOptions,
+        ) -> object_store::Result<object_store::GetResult> {
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn delete(&self, location: &Path) -> object_store::Result<()> {
+            if location.filename() == Some("locked.csv") {
+                return Err(object_store::Error::Generic {
+                    store: "LockedStore",
+                    source: "permission denied".into(),
+                });
+            }
+            self.inner.delete(location).await
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> futures::stream::BoxStream<'_, object_store::Result<object_store::ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<object_store::ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn rename(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.rename(from, to).await
+        }
+
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overwrite_reports_undeleted_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "id,name\n1,x\n").unwrap();
+        std::fs::write(temp_dir.path().join("locked.csv"), "id,name\n2,y\n").unwrap();
+
+        let ctx = SessionContext::new();
+        let store = LockedStore {
+            inner: object_store::local::LocalFileSystem::new(),
+        };
+        ctx.register_object_store(&url::Url::parse("file://").unwrap(), Arc::new(store));
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        ctx.register_csv_file("t", &table_path).await?;
+
+        let err = ctx
+            .sql("INSERT OVERWRITE t VALUES (3, 'z')")
+            .await?
+            .collect()
+            .await
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("could not delete 1 of the previous files"), "{}", message);
+        assert!(message.contains("locked.csv"), "{}", message);
+        assert!(message.contains("permission denied"), "{}", message);
+
+        // The new file is in place and the undeleted file's rows still show
+        assert!(!temp_dir.path().join("a.csv").exists());
+        assert_eq!(count_rows(&ctx, "t").await?, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_compressed() -> Result<()> {
+        let codecs = [CsvCompression::Gzip, CsvCompression::Zstd, CsvCompression::Bzip2];
+        for compression in codecs.into_iter().filter(|c| c.check_enabled().is_ok()) {
+            let temp_dir = TempDir::new().unwrap();
+            let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+            let ctx = SessionContext::new();
+            ctx.sql("CREATE TABLE source AS VALUES (1, 'Alice'), (2, 'Bob')")
+                .await?
+                .collect()
+                .await?;
+
+            // An empty directory has nothing to infer from, so supply the schema
+            let options = CsvFormatOptions::new().with_compression(compression);
+            let schema = Arc::new(ctx.table("source").await?.schema().as_arrow().clone());
+            let listing_options = ListingOptions::new(Arc::new(CsvFormat::new(options.clone())))
+                .with_file_extension(options.file_extension_with_compression());
+            let config = ListingTableConfig::new(ListingTableUrl::parse(&table_path)?)
+                .with_listing_options(listing_options)
+                .with_schema(schema);
+            ctx.register_table("compressed", Arc::new(ListingTable::try_new(config)?))?;
+
+            ctx.sql("INSERT INTO compressed SELECT * FROM source")
+                .await?
+                .collect()
+                .await?;
+            assert_eq!(count_rows(&ctx, "compressed").await?, 2);
+
+            let entry = std::fs::read_dir(temp_dir.path()).unwrap().next().unwrap().unwrap();
+            let name = entry.file_name().into_string().unwrap();
+            assert!(name.ends_with(&format!(".csv{}", compression.file_suffix())));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_refreshes_cached_listing() -> Result<()> {
+        use crate::{csv_cache_manager_config, CsvSchemaCache};
+        use datafusion::execution::runtime_env::RuntimeEnvBuilder;
+        use datafusion::prelude::SessionConfig;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut file = File::create(temp_dir.path().join("initial.csv")).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,Alice").unwrap();
+
+        let runtime = RuntimeEnvBuilder::new()
+            .with_cache_manager(csv_cache_manager_config())
+            .build_arc()?;
+        let schema_cache = Arc::new(CsvSchemaCache::new());
+        let config = SessionConfig::new().with_extension(schema_cache.clone());
+        let ctx = SessionContext::new_with_config_rt(config, runtime.clone());
+
+        // Two tables over the same directory share one listing and one inferred schema
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        ctx.register_csv_file("people", &table_path).await?;
+        ctx.register_csv_file("people_copy", &table_path).await?;
+        assert_eq!(schema_cache.len(), 1);
+        assert_eq!(count_rows(&ctx, "people").await?, 1);
+        let list_cache = runtime.cache_manager.get_list_files_cache().unwrap();
+        assert_eq!(list_cache.len(), 1);
+
+        ctx.sql("INSERT INTO people VALUES (2, 'Bob')")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(count_rows(&ctx, "people").await?, 2);
+        assert_eq!(count_rows(&ctx, "people_copy").await?, 2);
+
+        Ok(())
+    }
+}
@@ -4,34 +4,68 @@
 //! using our independent CSV reader implementation.
 
 use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 
-use arrow_schema::SchemaRef;
+use arrow_schema::{Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::catalog::Session;
 use datafusion::datasource::listing::{
     ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
 };
-use datafusion::datasource::physical_plan::FileScanConfig;
-use datafusion::datasource::TableProvider;
-use datafusion::error::Result;
+use datafusion::datasource::listing::PartitionedFile;
+use datafusion::config::ConfigOptions;
+use datafusion::datasource::physical_plan::{FileGroupPartitioner, FileScanConfig};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::SessionState;
+use datafusion::execution::object_store::ObjectStoreUrl;
 use datafusion::execution::TaskContext;
-use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
+use datafusion::logical_expr::{Expr, TableProviderFilterPushDown};
+use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use datafusion::physical_plan::{
-    DisplayAs, DisplayFormatType, ExecutionMode, ExecutionPlan, PlanProperties, SendableRecordBatchStream,
+    DisplayAs, DisplayFormatType, ExecutionMode, ExecutionPlan, PhysicalExpr, PlanProperties,
+    SendableRecordBatchStream, Statistics,
 };
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion::physical_plan::limit::GlobalLimitExec;
 use datafusion_common::project_schema;
 use datafusion_physical_expr::EquivalenceProperties;
-use object_store::http::HttpBuilder;
+use futures::TryStreamExt;
+use object_store::{ObjectMeta, ObjectStore};
 use url::Url;
 
-use crate::file_format::{detect_file_extension, CsvFormat, CsvFormatOptions};
-use crate::physical_exec::CsvOpener;
+use crate::cloud::HttpConfig;
+use crate::file_format::{
+    detect_file_extension, infer_schema_from_store, CsvCompression, CsvFormat, CsvFormatOptions,
+    ParseErrorPolicy, RawRecord,
+};
+use crate::object_store_reader::{estimate_object_row_count, expand_path_template, wrap_registered_store};
+use crate::physical_exec::{self, CsvOpener, CsvScanMetrics, FilePrefetcher, ScanPredicate};
+use crate::statistics;
+
+/// Ordering used to pick a single file from a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatestFileBy {
+    /// Newest last-modified time, ties broken by name
+    ModifiedTime,
+    /// Greatest path in lexicographic order (e.g. date-stamped file names)
+    Name,
+}
 
 /// CSV source builder for creating table providers
 pub struct CsvSourceBuilder {
     path: String,
+    additional_paths: Vec<String>,
     options: CsvFormatOptions,
+    latest_file: Option<LatestFileBy>,
+    object_stores: Vec<(String, Arc<dyn ObjectStore>)>,
+    schema: Option<SchemaRef>,
+    collect_statistics: bool,
 }
 
 impl CsvSourceBuilder {
@@ -39,7 +73,12 @@ impl CsvSourceBuilder {
     pub fn new(path: impl Into<String>) -> Self {
         Self {
             path: path.into(),
+            additional_paths: Vec::new(),
             options: CsvFormatOptions::default(),
+            latest_file: None,
+            object_stores: Vec::new(),
+            schema: None,
+            collect_statistics: false,
         }
     }
 
@@ -61,9 +100,98 @@ impl CsvSourceBuilder {
         self
     }
 
+    /// Read the files with `schema` instead of inferring one
+    ///
+    /// No file is sampled when the table is built, which saves reading huge or
+    /// slow remote files and fixes types inference would guess wrong. Fields
+    /// are matched to columns by position; virtual columns are appended.
+    pub fn with_schema(mut self, schema: SchemaRef) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Drop rows before Arrow conversion unless `filter` returns `true`
+    pub fn with_row_filter(
+        mut self,
+        filter: impl Fn(&RawRecord) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.options = self.options.with_row_filter(filter);
+        self
+    }
+
+    /// Add another path to the same table
+    ///
+    /// Paths may live on different object stores (e.g. a local staging
+    /// directory, an S3 archive and an HTTPS mirror); each file is read from
+    /// the store its path belongs to. All paths must share one schema.
+    pub fn with_additional_path(mut self, path: impl Into<String>) -> Self {
+        self.additional_paths.push(path.into());
+        self
+    }
+
+    /// Read paths under `url` (e.g. `"s3://bucket"`) from `store`
+    ///
+    /// The store is registered with the session when the table is built and
+    /// takes the place of any store the crate would otherwise create from the
+    /// URL scheme, which is useful for in-memory, encrypted or mocked stores.
+    pub fn with_object_store(mut self, url: impl Into<String>, store: Arc<dyn ObjectStore>) -> Self {
+        self.object_stores.push((url.into(), store));
+        self
+    }
+
+    /// Read only the newest file matching the path, rather than all of them
+    ///
+    /// The path may be a directory or a glob such as `/dropzone/sales_*.csv`.
+    /// The file is chosen once, when the table is built.
+    pub fn with_latest_file(mut self, by: LatestFileBy) -> Self {
+        self.latest_file = Some(by);
+        self
+    }
+
+    /// Read every file once when the table is built, to plan with exact statistics
+    ///
+    /// Row counts, null counts and min/max values let the optimizer answer
+    /// `COUNT(*)`, `MIN` and `MAX` without scanning and order joins, as after
+    /// [`analyze_csv_table`](crate::SessionContextCsvExt::analyze_csv_table).
+    /// Files changed or added later fall back to estimated row counts.
+    pub fn with_collect_statistics(mut self, collect: bool) -> Self {
+        self.collect_statistics = collect;
+        self
+    }
+
     /// Build the table provider
     pub async fn build(self, state: &SessionState) -> Result<Arc<dyn TableProvider>> {
-        create_csv_table_provider(state, &self.path, self.options).await
+        let collect_statistics = self.collect_statistics;
+        let table = self.build_table(state).await?;
+        if !collect_statistics {
+            return Ok(table);
+        }
+        Ok(statistics::analyze_provider(state, table.clone()).await?.unwrap_or(table))
+    }
+
+    async fn build_table(self, state: &SessionState) -> Result<Arc<dyn TableProvider>> {
+        for (url, store) in &self.object_stores {
+            let url = Url::parse(url).map_err(|e| {
+                DataFusionError::Configuration(format!("Invalid object store URL '{}': {}", url, e))
+            })?;
+            state.runtime_env().register_object_store(&url, store.clone());
+        }
+
+        let schema = self.schema.map(|schema| self.options.with_virtual_columns(schema));
+        match self.latest_file {
+            Some(_) if !self.additional_paths.is_empty() => Err(DataFusionError::Plan(
+                "Latest-file selection cannot be combined with additional paths".to_string(),
+            )),
+            Some(by) => {
+                let path = latest_file_path(state, &self.path, &self.options, by).await?;
+                build_csv_table_provider(state, &[path], self.options, schema).await
+            }
+            None => {
+                let mut paths = vec![self.path];
+                paths.extend(self.additional_paths);
+                build_csv_table_provider(state, &paths, self.options, schema).await
+            }
+        }
     }
 }
 
@@ -73,67 +201,476 @@ pub async fn create_csv_table_provider(
     path: &str,
     options: CsvFormatOptions,
 ) -> Result<Arc<dyn TableProvider>> {
-    // Register HTTP object store if the URL is HTTP/HTTPS
-    if path.starts_with("http://") || path.starts_with("https://") {
-        register_http_object_store(state, path)?;
+    create_csv_table_provider_from_paths(state, &[path], options).await
+}
+
+/// Create a single CSV table provider over several paths
+///
+/// Paths on the same object store are listed by one [`ListingTable`]. When
+/// paths span stores, the table scans each store separately and combines the
+/// file groups into a single [`CsvExec`] that reads every group from its own store.
+pub async fn create_csv_table_provider_from_paths(
+    state: &SessionState,
+    paths: &[impl AsRef<str>],
+    options: CsvFormatOptions,
+) -> Result<Arc<dyn TableProvider>> {
+    build_csv_table_provider(state, paths, options, None).await
+}
+
+/// Create one table over several CSV files, checking their schemas agree
+///
+/// Each path's schema is inferred on its own. Columns must match by name and
+/// position. A column inferred as `Int64` in one file and `Float64` in another
+/// is read as `Float64`; any other type disagreement is read as `Utf8`.
+pub async fn create_unioned_csv_table_provider(
+    state: &SessionState,
+    paths: &[impl AsRef<str>],
+    options: CsvFormatOptions,
+) -> Result<Arc<dyn TableProvider>> {
+    let mut merged: Option<(&str, SchemaRef)> = None;
+    for path in paths.iter().map(AsRef::as_ref) {
+        let schema = create_csv_table_provider(state, path, options.clone()).await?.schema();
+        merged = Some(match merged {
+            None => (path, schema),
+            Some((first_path, first_schema)) => {
+                (first_path, merge_schemas(first_path, &first_schema, path, &schema)?)
+            }
+        });
+    }
+
+    let schema = merged.map(|(_, schema)| schema);
+    build_csv_table_provider(state, paths, options, schema).await
+}
+
+/// Combine the schemas of two files read into the same table
+fn merge_schemas(left_path: &str, left: &Schema, right_path: &str, right: &Schema) -> Result<SchemaRef> {
+    let names = |schema: &Schema| schema.fields().iter().map(|f| f.name().clone()).collect::<Vec<_>>();
+    if names(left) != names(right) {
+        return Err(DataFusionError::Plan(format!(
+            "Cannot combine CSV files with different columns: '{}' has [{}] but '{}' has [{}]",
+            left_path,
+            names(left).join(", "),
+            right_path,
+            names(right).join(", ")
+        )));
+    }
+
+    let fields: Vec<Field> = left
+        .fields()
+        .iter()
+        .zip(right.fields())
+        .map(|(l, r)| {
+            let data_type = physical_exec::widen_type(l.data_type(), r.data_type());
+            Field::new(l.name(), data_type, l.is_nullable() || r.is_nullable())
+        })
+        .collect();
+    Ok(Arc::new(Schema::new(fields)))
+}
+
+/// Build a table over `paths`, inferring the schema unless one is given
+async fn build_csv_table_provider(
+    state: &SessionState,
+    paths: &[impl AsRef<str>],
+    options: CsvFormatOptions,
+    schema: Option<SchemaRef>,
+) -> Result<Arc<dyn TableProvider>> {
+    let paths: Vec<Cow<str>> = paths.iter().map(|path| directory_path(path.as_ref())).collect();
+    let Some(first_path) = paths.first().map(AsRef::as_ref) else {
+        return Err(DataFusionError::Plan(
+            "At least one CSV path is required".to_string(),
+        ));
+    };
+
+    let mut table_paths = Vec::new();
+    for path in paths.iter().map(AsRef::as_ref) {
+        // Register HTTP object store if the URL is HTTP/HTTPS
+        if path.starts_with("http://") || path.starts_with("https://") {
+            register_http_object_store(state, path, options.http_config.as_ref())?;
+        }
+        crate::cloud::register_cloud_object_store(state, path)?;
+
+        // Date and integer ranges such as `dt={2024-01-01..2024-01-31}` expand into
+        // one listing prefix each, so only the matching directories are listed
+        for expanded in expand_path_template(path)? {
+            table_paths.push(ListingTableUrl::parse(expanded)?);
+        }
+    }
+
+    // Serve repeated listings of these directories from a cache, and queue
+    // requests beyond the cap inside the store
+    for table_url in &table_paths {
+        let list_cache = options
+            .list_cache_ttl
+            .filter(|_| table_url.is_collection())
+            .map(|ttl| (table_url.prefix().clone(), ttl));
+        let max_requests = options.max_concurrent_requests;
+        if list_cache.is_some() || max_requests.is_some() {
+            let store_url = table_url.object_store();
+            wrap_registered_store(state.runtime_env(), &store_url, list_cache, max_requests).await?;
+        }
+    }
+
+    let mut options = options;
+    let extension = listing_extension(first_path, &mut options);
+    let partition_columns = options.partition_columns.clone();
+
+    let format = CsvFormat::new(options).with_store_url(table_paths[0].object_store());
+    let listing_options = ListingOptions::new(Arc::new(format))
+        .with_file_extension(&extension)
+        .with_table_partition_cols(partition_columns);
+
+    let schema = match schema {
+        Some(schema) => schema,
+        None => {
+            // Infer from the first path that has any files; some expanded prefixes may be empty
+            let mut schema = Arc::new(Schema::empty());
+            for table_url in &table_paths {
+                schema = listing_options.infer_schema(state, table_url).await?;
+                if !schema.fields().is_empty() {
+                    break;
+                }
+            }
+            schema
+        }
+    };
+
+    // Group paths by the object store they are read from, keeping first-seen order
+    let mut store_groups: Vec<(ObjectStoreUrl, Vec<ListingTableUrl>)> = Vec::new();
+    for table_url in table_paths {
+        let store_url = table_url.object_store();
+        match store_groups.iter_mut().find(|(url, _)| *url == store_url) {
+            Some((_, urls)) => urls.push(table_url),
+            None => store_groups.push((store_url, vec![table_url])),
+        }
+    }
+
+    let mut tables = store_groups
+        .into_iter()
+        .map(|(_, urls)| {
+            let config = ListingTableConfig::new_with_multi_paths(urls)
+                .with_listing_options(listing_options.clone())
+                .with_schema(schema.clone());
+            ListingTable::try_new(config)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if tables.len() == 1 {
+        return Ok(Arc::new(tables.remove(0)));
+    }
+    Ok(Arc::new(MultiStoreCsvTable::new(tables)))
+}
+
+/// A CSV table whose files are spread over several object stores
+///
+/// Holds one [`ListingTable`] per store. Scans plan each table separately and
+/// merge the resulting [`CsvExec`]s, recording which store each file group
+/// comes from.
+#[derive(Debug)]
+pub struct MultiStoreCsvTable {
+    tables: Vec<ListingTable>,
+}
+
+impl MultiStoreCsvTable {
+    pub(crate) fn new(tables: Vec<ListingTable>) -> Self {
+        Self { tables }
     }
 
-    let table_url = ListingTableUrl::parse(path)?;
+    /// The table over each store
+    pub(crate) fn tables(&self) -> &[ListingTable] {
+        &self.tables
+    }
+}
+
+#[async_trait]
+impl TableProvider for MultiStoreCsvTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.tables[0].schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        // Every store shares the same options and partition columns
+        self.tables[0].supports_filters_pushdown(filters)
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut plans = Vec::with_capacity(self.tables.len());
+        for table in &self.tables {
+            plans.push(table.scan(state, projection, filters, limit).await?);
+        }
+
+        // Stores with no matching files plan an empty scan and contribute nothing
+        let execs: Vec<&CsvExec> = plans.iter().filter_map(find_csv_exec).collect();
+
+        let merged = match execs.as_slice() {
+            [] => return Ok(plans.swap_remove(0)),
+            [exec] => (*exec).clone(),
+            execs => CsvExec::merge(execs),
+        };
+        let options = merged.options.clone();
+        Ok(cap_rows(Arc::new(merged), &options))
+    }
+}
+
+/// A CSV table that carries its own object store
+///
+/// Unlike providers from [`create_csv_table_provider`], it is constructed
+/// without a [`SessionState`] and never consults the session's object store
+/// registry, so it can be embedded in custom catalogs and tooling that does
+/// not own a DataFusion session. Partition columns are not supported.
+#[derive(Debug)]
+pub struct StandaloneCsvTable {
+    store: Arc<dyn ObjectStore>,
+    table_url: ListingTableUrl,
+    extension: String,
+    schema: SchemaRef,
+    options: CsvFormatOptions,
+}
+
+impl StandaloneCsvTable {
+    /// Create a table over `path` in `store`, inferring the schema from the first file
+    pub async fn try_new(store: Arc<dyn ObjectStore>, path: &str, options: CsvFormatOptions) -> Result<Self> {
+        let mut table = Self::try_new_with_schema(store, path, Arc::new(Schema::empty()), options)?;
+        let files = table.list_files().await?;
+        let schema = infer_schema_from_store(&table.store, &files, &table.options, None).await?;
+        table.schema = table.options.with_virtual_columns(schema);
+        Ok(table)
+    }
+
+    /// Create a table over `path` in `store` with a known schema
+    pub fn try_new_with_schema(
+        store: Arc<dyn ObjectStore>,
+        path: &str,
+        schema: SchemaRef,
+        options: CsvFormatOptions,
+    ) -> Result<Self> {
+        let table_url = ListingTableUrl::parse(path)?;
+        let mut options = options;
+        let extension = listing_extension(path, &mut options);
+        Ok(Self {
+            store,
+            table_url,
+            extension,
+            schema,
+            options,
+        })
+    }
+
+    /// List the files this table reads, in path order
+    async fn list_files(&self) -> Result<Vec<ObjectMeta>> {
+        let prefix = self.table_url.prefix();
+        let mut files: Vec<ObjectMeta> = if self.table_url.is_collection() {
+            self.store.list(Some(prefix)).try_collect().await?
+        } else {
+            vec![self.store.head(prefix).await?]
+        };
+
+        files.retain(|meta| {
+            meta.location.as_ref().ends_with(&self.extension) && self.table_url.contains(&meta.location, true)
+        });
+        files.sort_by(|a, b| a.location.cmp(&b.location));
+        Ok(files)
+    }
+}
+
+#[async_trait]
+impl TableProvider for StandaloneCsvTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let files = self.list_files().await?;
+        if files.is_empty() {
+            let schema = project_schema(&self.schema, projection)?;
+            return Ok(Arc::new(EmptyExec::new(schema)));
+        }
+
+        // Spread files evenly over the session's target partitions
+        let group_size = files.len().div_ceil(state.config().target_partitions().max(1));
+        let file_groups = files
+            .chunks(group_size)
+            .map(|chunk| chunk.iter().cloned().map(PartitionedFile::from).collect())
+            .collect();
+
+        let config = FileScanConfig::new(self.table_url.object_store(), self.schema.clone())
+            .with_file_groups(file_groups)
+            .with_projection(projection.cloned())
+            .with_limit(limit);
+
+        let exec = CsvExec::new(config, self.options.clone()).with_object_store(self.store.clone());
+        Ok(cap_rows(Arc::new(exec), &self.options))
+    }
+}
+
+/// `path` with a trailing `/` when it names a local directory
+///
+/// A directory is then listed for all its CSV files, even when its name
+/// contains a dot that would otherwise be read as a file extension.
+fn directory_path(path: &str) -> Cow<'_, str> {
+    let local = !path.contains("://");
+    if local && !path.ends_with('/') && std::path::Path::new(path).is_dir() {
+        Cow::Owned(format!("{}/", path))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Resolve the file extension a table over `path` lists, detecting compression
+/// from the path into `options` along the way
+fn listing_extension(path: &str, options: &mut CsvFormatOptions) -> String {
+    let detected_extension = detect_file_extension(path);
+
+    // Auto-detect compression from the path (e.g. `data.csv.gz`)
+    if options.compression == CsvCompression::Uncompressed
+        && let Some(compression) = detected_extension
+            .as_deref()
+            .and_then(CsvCompression::from_extension)
+    {
+        options.compression = compression;
+    }
 
     // Auto-detect file extension if not explicitly set as non-csv
-    let extension = if options.file_extension == ".csv" {
-        detect_file_extension(path)
+    if options.file_extension == ".csv" {
+        detected_extension
             .map(|ext| if ext.starts_with('.') { ext } else { format!(".{}", ext) })
-            .unwrap_or_else(|| ".csv".to_string())
+            .unwrap_or_else(|| options.file_extension_with_compression())
     } else {
-        options.file_extension_with_dot()
+        options.file_extension_with_compression()
+    }
+}
+
+/// Find the newest file matching `pattern`, returning its full URL
+async fn latest_file_path(
+    state: &SessionState,
+    pattern: &str,
+    options: &CsvFormatOptions,
+    by: LatestFileBy,
+) -> Result<String> {
+    let (store_url, _, files, _) = list_matching_files(state, pattern, options).await?;
+
+    let latest = match by {
+        LatestFileBy::ModifiedTime => files
+            .into_iter()
+            .max_by(|a, b| (a.last_modified, &a.location).cmp(&(b.last_modified, &b.location))),
+        LatestFileBy::Name => files.into_iter().max_by(|a, b| a.location.cmp(&b.location)),
     };
 
-    let format = CsvFormat::new(options);
-    let listing_options = ListingOptions::new(Arc::new(format))
-        .with_file_extension(&extension);
+    let latest = latest.ok_or_else(|| {
+        DataFusionError::Plan(format!("No CSV files match '{}'", pattern))
+    })?;
+    Ok(format!("{}{}", store_url.as_str(), latest.location))
+}
 
-    let config = ListingTableConfig::new(table_url)
-        .with_listing_options(listing_options)
-        .infer_schema(state)
-        .await?;
+/// Estimate the total number of records in the files at `path`
+pub(crate) async fn estimate_csv_row_count(
+    state: &SessionState,
+    path: &str,
+    options: &CsvFormatOptions,
+) -> Result<u64> {
+    let (_, store, files, options) = list_matching_files(state, path, options).await?;
+    let mut total = 0;
+    for file in &files {
+        total += estimate_object_row_count(&store, file, &options).await?;
+    }
+    Ok(total)
+}
 
-    let table = ListingTable::try_new(config)?;
+/// Read the preamble of the first CSV file at `path`
+pub(crate) async fn read_csv_preamble(
+    state: &SessionState,
+    path: &str,
+    options: &CsvFormatOptions,
+) -> Result<HashMap<String, String>> {
+    let (_, store, files, options) = list_matching_files(state, path, options).await?;
+    let Some(first) = files.first() else {
+        return Err(DataFusionError::Plan(format!("No CSV files match '{}'", path)));
+    };
+    let bytes = physical_exec::fetch_decompressed(&store, &first.location, &options).await?;
+    physical_exec::parse_preamble(&bytes, &options)
+}
 
-    Ok(Arc::new(table))
+/// List the files a table over `pattern` would read
+///
+/// Also returns the options with compression detected from the pattern.
+pub(crate) async fn list_matching_files(
+    state: &SessionState,
+    pattern: &str,
+    options: &CsvFormatOptions,
+) -> Result<(ObjectStoreUrl, Arc<dyn ObjectStore>, Vec<ObjectMeta>, CsvFormatOptions)> {
+    if pattern.starts_with("http://") || pattern.starts_with("https://") {
+        register_http_object_store(state, pattern, options.http_config.as_ref())?;
+    }
+    crate::cloud::register_cloud_object_store(state, pattern)?;
+
+    let table_url = ListingTableUrl::parse(pattern)?;
+    let store_url = table_url.object_store();
+    let store = state.runtime_env().object_store(&store_url)?;
+    let mut options = options.clone();
+    let extension = listing_extension(pattern, &mut options);
+
+    let files: Vec<ObjectMeta> = table_url
+        .list_all_files(state, store.as_ref(), &extension)
+        .await?
+        .try_collect()
+        .await?;
+    Ok((store_url, store, files, options))
 }
 
 /// Register HTTP object store for the given URL
-fn register_http_object_store(state: &SessionState, url_str: &str) -> Result<()> {
+///
+/// Without `config`, a store already registered for the host (e.g. via
+/// [`CsvSourceBuilder::with_object_store`]) is left in place.
+fn register_http_object_store(state: &SessionState, url_str: &str, config: Option<&HttpConfig>) -> Result<()> {
     let url = Url::parse(url_str).map_err(|e| {
         datafusion_common::DataFusionError::Execution(format!("Failed to parse URL: {}", e))
     })?;
 
+    let store_url = ObjectStoreUrl::parse(&url[..url::Position::BeforePath])?;
+    if config.is_none() && state.runtime_env().object_store(store_url).is_ok() {
+        return Ok(());
+    }
+
     // Extract the base URL (scheme + host + port)
-    let base_url = format!(
-        "{}://{}",
-        url.scheme(),
-        url.host_str().ok_or_else(|| {
-            datafusion_common::DataFusionError::Execution("URL has no host".to_string())
-        })?
-    );
-
-    // Build HTTP object store
-    let http_store = HttpBuilder::new()
-        .with_url(base_url.clone())
-        .build()
-        .map_err(|e| {
-            datafusion_common::DataFusionError::Execution(format!(
-                "Failed to create HTTP object store: {}",
-                e
-            ))
-        })?;
+    if url.host_str().is_none() {
+        return Err(datafusion_common::DataFusionError::Execution("URL has no host".to_string()));
+    }
+    let base_url = &url[..url::Position::BeforePath];
 
     // Register the object store
-    let object_store_url = Url::parse(&base_url).unwrap();
-    state
-        .runtime_env()
-        .register_object_store(&object_store_url, Arc::new(http_store));
+    let object_store_url = Url::parse(base_url).unwrap();
+    let store = config.cloned().unwrap_or_default().build(base_url)?;
+    state.runtime_env().register_object_store(&object_store_url, store);
 
     Ok(())
 }
@@ -149,16 +686,26 @@ pub struct CsvExec {
     metrics: ExecutionPlanMetricsSet,
     /// Plan properties
     properties: PlanProperties,
+    /// Object store of each file group, when groups come from different stores
+    group_stores: Vec<ObjectStoreUrl>,
+    /// Store to read from instead of the session's registry
+    object_store: Option<Arc<dyn ObjectStore>>,
+    /// Rows dropped by `SkipRow` across all partitions, for `max_errors`
+    rejected_rows: Arc<AtomicUsize>,
+    /// Pushed-down filter checked on the raw rows before they are fully built
+    predicate: Option<Arc<ScanPredicate>>,
 }
 
 impl CsvExec {
-    pub fn new(config: FileScanConfig, options: CsvFormatOptions) -> Self {
-        // Calculate the projected schema
-        let projected_schema = if let Some(ref proj) = config.projection {
-            project_schema(&config.file_schema, Some(proj)).unwrap()
-        } else {
-            config.file_schema.clone()
-        };
+    pub fn new(mut config: FileScanConfig, options: CsvFormatOptions) -> Self {
+        select_files(&mut config.file_groups, &mut config.statistics, &config.file_schema, &options);
+        Self::with_config(config, options)
+    }
+
+    /// Plan `config`'s files as they are, without watermark filtering or ordering
+    fn with_config(config: FileScanConfig, options: CsvFormatOptions) -> Self {
+        // Projected schema, including any partition columns
+        let (projected_schema, _, _) = config.project();
 
         let properties = PlanProperties::new(
             EquivalenceProperties::new(projected_schema.clone()),
@@ -171,14 +718,158 @@ impl CsvExec {
             options,
             metrics: ExecutionPlanMetricsSet::new(),
             properties,
+            group_stores: Vec::new(),
+            object_store: None,
+            rejected_rows: Arc::new(AtomicUsize::new(0)),
+            predicate: None,
         }
     }
 
+    /// Skip rows failing the cheap parts of `predicate`, a filter over the table schema
+    ///
+    /// Filter columns are built first and the other columns only for rows
+    /// that pass. Rows are not guaranteed to match, so the filter must still
+    /// be applied above the scan.
+    pub(crate) fn with_predicate(mut self, predicate: Option<&Arc<dyn PhysicalExpr>>) -> Result<Self> {
+        self.predicate = match predicate {
+            Some(predicate) => ScanPredicate::try_new(predicate, &self.config.file_schema)?.map(Arc::new),
+            None => None,
+        };
+        Ok(self)
+    }
+
+    /// Read all files from `store` rather than the store registered for the URL
+    pub(crate) fn with_object_store(mut self, store: Arc<dyn ObjectStore>) -> Self {
+        self.object_store = Some(store);
+        self
+    }
+
+    /// Combine scans of the same table on different object stores into one plan
+    ///
+    /// File groups are concatenated and each remembers the store it is read from.
+    fn merge(execs: &[&CsvExec]) -> Self {
+        let mut config = execs[0].config.clone();
+        config.file_groups.clear();
+        config.statistics = Statistics::new_unknown(&config.file_schema);
+
+        let mut group_stores = Vec::new();
+        for exec in execs {
+            config.file_groups.extend(exec.config.file_groups.iter().cloned());
+            group_stores.extend(std::iter::repeat_n(
+                exec.config.object_store_url.clone(),
+                exec.config.file_groups.len(),
+            ));
+        }
+
+        // Each scan already selected and ordered its own files
+        let mut merged = Self::with_config(config, execs[0].options.clone());
+        merged.group_stores = group_stores;
+        merged.predicate = execs[0].predicate.clone();
+        merged
+    }
+
+    /// The scan's files, options, per-group stores and pushed-down filter, for [`CsvPhysicalCodec`]
+    ///
+    /// [`CsvPhysicalCodec`]: crate::CsvPhysicalCodec
+    #[cfg(feature = "proto")]
+    pub(crate) fn parts(&self) -> (&FileScanConfig, &CsvFormatOptions, &[ObjectStoreUrl], Option<&ScanPredicate>) {
+        (&self.config, &self.options, &self.group_stores, self.predicate.as_deref())
+    }
+
+    /// Rebuild a scan from its [`parts`](Self::parts), keeping the files as they were planned
+    #[cfg(feature = "proto")]
+    pub(crate) fn from_parts(
+        config: FileScanConfig,
+        options: CsvFormatOptions,
+        group_stores: Vec<ObjectStoreUrl>,
+        predicate: Option<ScanPredicate>,
+    ) -> Self {
+        let mut exec = Self::with_config(config, options);
+        exec.group_stores = group_stores;
+        exec.predicate = predicate.map(Arc::new);
+        exec
+    }
+
     fn projected_schema(&self) -> SchemaRef {
-        if let Some(ref proj) = self.config.projection {
-            project_schema(&self.config.file_schema, Some(proj)).unwrap()
-        } else {
-            self.config.file_schema.clone()
+        self.properties.eq_properties.schema().clone()
+    }
+
+    /// Whether files may be split into byte ranges scanned by separate partitions
+    ///
+    /// Compressed files and files with line breaks inside values have no
+    /// findable record boundaries, and line numbers and byte offsets are only
+    /// known when reading from the start of the file, so scans that report
+    /// bad rows by line (skipping or failing on them, or capturing them in a
+    /// dead-letter sink) are not split either.
+    fn can_split_files(&self) -> bool {
+        let options = &self.options;
+        // Servers may compress responses, which ranged requests cannot be decoded from
+        let http = self.config.object_store_url.as_str().starts_with("http");
+        options.compression == CsvCompression::Uncompressed
+            && !options.newlines_in_values
+            && options.line_number_column.is_none()
+            && options.byte_offset_column.is_none()
+            && options.on_error == ParseErrorPolicy::NullValue
+            && options.dead_letter.is_none()
+            && options.prefetch_files == 0
+            && self.group_stores.is_empty()
+            && !http
+    }
+}
+
+/// Stop `plan` after `max_rows` rows in total, when set
+///
+/// The cap spans all partitions, so the capped plan has a single output partition.
+pub(crate) fn cap_rows(plan: Arc<dyn ExecutionPlan>, options: &CsvFormatOptions) -> Arc<dyn ExecutionPlan> {
+    match options.max_rows {
+        Some(max_rows) => Arc::new(GlobalLimitExec::new(
+            Arc::new(CoalescePartitionsExec::new(plan)),
+            0,
+            Some(max_rows),
+        )),
+        None => plan,
+    }
+}
+
+/// The `CsvExec` of a scan, looking through the wrapping added by [`cap_rows`]
+fn find_csv_exec(plan: &Arc<dyn ExecutionPlan>) -> Option<&CsvExec> {
+    if let Some(exec) = plan.as_any().downcast_ref::<CsvExec>() {
+        return Some(exec);
+    }
+    match plan.children().as_slice() {
+        [child] => find_csv_exec(child),
+        _ => None,
+    }
+}
+
+/// Drop files older than the `modified_since` watermark and apply `file_order`
+///
+/// Groups keep their count; a group left empty reads nothing.
+fn select_files(
+    file_groups: &mut [Vec<PartitionedFile>],
+    statistics: &mut Statistics,
+    file_schema: &Schema,
+    options: &CsvFormatOptions,
+) {
+    if let Some(watermark) = options.modified_since {
+        let before: usize = file_groups.iter().map(Vec::len).sum();
+        for group in file_groups.iter_mut() {
+            group.retain(|file| file.object_meta.last_modified > watermark);
+        }
+        let after: usize = file_groups.iter().map(Vec::len).sum();
+        if after < before {
+            *statistics = Statistics::new_unknown(file_schema);
+        }
+    }
+
+    // Re-split the sorted files into contiguous runs of the same group sizes
+    if let Some(order) = options.file_order {
+        let sizes: Vec<usize> = file_groups.iter().map(Vec::len).collect();
+        let mut files: Vec<PartitionedFile> = file_groups.iter_mut().flat_map(std::mem::take).collect();
+        files.sort_by(|a, b| order.compare(&a.object_meta, &b.object_meta));
+        let mut files = files.into_iter();
+        for (group, size) in file_groups.iter_mut().zip(sizes) {
+            group.extend(files.by_ref().take(size));
         }
     }
 }
@@ -188,7 +879,19 @@ impl DisplayAs for CsvExec {
         match t {
             DisplayFormatType::Default | DisplayFormatType::Verbose => {
                 let file_count: usize = self.config.file_groups.iter().map(|g| g.len()).sum();
-                write!(f, "CsvExec: file_groups={{count={}}}", file_count)
+                write!(f, "CsvExec: file_groups={{count={}}}", file_count)?;
+                if !self.group_stores.is_empty() {
+                    let mut stores: Vec<&str> = self.group_stores.iter().map(|url| url.as_str()).collect();
+                    stores.dedup();
+                    write!(f, ", stores=[{}]", stores.join(", "))?;
+                }
+                if let Some(limit) = self.config.limit {
+                    write!(f, ", limit={}", limit)?;
+                }
+                if let Some(predicate) = &self.predicate {
+                    write!(f, ", predicate={}", predicate.expr())?;
+                }
+                Ok(())
             }
         }
     }
@@ -222,21 +925,74 @@ impl ExecutionPlan for CsvExec {
         Ok(self)
     }
 
+    fn repartitioned(
+        &self,
+        target_partitions: usize,
+        config: &ConfigOptions,
+    ) -> Result<Option<Arc<dyn ExecutionPlan>>> {
+        if !self.can_split_files() {
+            return Ok(None);
+        }
+        let file_groups = FileGroupPartitioner::new()
+            .with_target_partitions(target_partitions)
+            .with_preserve_order_within_groups(self.options.file_order.is_some())
+            .with_repartition_file_min_size(config.optimizer.repartition_file_min_size)
+            .repartition_file_groups(&self.config.file_groups);
+
+        Ok(file_groups.map(|file_groups| {
+            let mut exec = self.clone();
+            exec.properties = exec
+                .properties
+                .with_partitioning(datafusion::physical_plan::Partitioning::UnknownPartitioning(file_groups.len()));
+            exec.config.file_groups = file_groups;
+            Arc::new(exec) as Arc<dyn ExecutionPlan>
+        }))
+    }
+
     fn execute(
         &self,
         partition: usize,
         context: Arc<TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
-        let object_store_url = self.config.object_store_url.clone();
-        let object_store = context.runtime_env().object_store(&object_store_url)?;
+        let object_store = match &self.object_store {
+            Some(store) => store.clone(),
+            None => {
+                // Each file group is read from the store it was listed on
+                let object_store_url = self
+                    .group_stores
+                    .get(partition)
+                    .unwrap_or(&self.config.object_store_url);
+                context.runtime_env().object_store(object_store_url)?
+            }
+        };
+
+        // Partition columns are appended by the FileStream, not read from the file
+        let file_columns = self.config.file_schema.fields().len();
+        let projection = self.config.projection.as_ref().map(|proj| {
+            proj.iter()
+                .copied()
+                .filter(|&idx| idx < file_columns)
+                .collect::<Vec<_>>()
+        });
 
-        let opener = CsvOpener::new(
+        let mut opener = CsvOpener::new(
             self.options.clone(),
             self.config.file_schema.clone(),
-            self.config.projection.clone(),
-            object_store,
+            projection,
+            object_store.clone(),
         )
-        .with_batch_size(self.options.batch_size);
+        .with_batch_size(self.options.batch_size)
+        .with_metrics(CsvScanMetrics::new(&self.metrics, partition))
+        .with_rejected_rows(self.rejected_rows.clone())
+        .with_predicate(self.predicate.clone());
+        if self.options.prefetch_files > 0 {
+            let files = self.config.file_groups[partition]
+                .iter()
+                .map(|file| file.object_meta.location.clone())
+                .collect();
+            let prefetcher = FilePrefetcher::new(object_store, files, self.options.prefetch_files);
+            opener = opener.with_prefetcher(Arc::new(prefetcher));
+        }
 
         // Open files using our CSV opener
         let stream = datafusion::datasource::physical_plan::FileStream::new(
@@ -246,6 +1002,880 @@ impl ExecutionPlan for CsvExec {
             &self.metrics,
         )?;
 
+        if self.options.coalesce_batches {
+            return Ok(physical_exec::coalesce_batches(Box::pin(stream), self.options.batch_size));
+        }
         Ok(Box::pin(stream))
     }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Result<Statistics> {
+        let (schema, statistics, _) = self.config.project();
+        // Rows dropped by a filter are not reflected in collected statistics
+        let statistics = if self.options.row_filter.is_some() || self.predicate.is_some() {
+            statistics.to_inexact()
+        } else {
+            statistics
+        };
+        // The limit applies to each partition
+        let fetch = self
+            .config
+            .limit
+            .map(|limit| limit.saturating_mul(self.config.file_groups.len().max(1)));
+        statistics.with_fetch(schema, fetch, 0, 1)
+    }
+
+    fn with_fetch(&self, limit: Option<usize>) -> Option<Arc<dyn ExecutionPlan>> {
+        // FileStream stops reading once the configured limit is reached
+        let mut exec = self.clone();
+        exec.config = exec.config.with_limit(limit);
+        Some(Arc::new(exec))
+    }
+
+    fn fetch(&self) -> Option<usize> {
+        self.config.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::physical_plan::collect;
+    use datafusion::functions_aggregate::expr_fn::{count, sum};
+    use datafusion::prelude::{col, SessionContext};
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_csv_exec_with_fetch() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("test.csv");
+
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,name").unwrap();
+        for i in 0..10 {
+            writeln!(file, "{},name{}", i, i).unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        let table = create_csv_table_provider(
+            &ctx.state(),
+            csv_path.to_str().unwrap(),
+            CsvFormatOptions::default(),
+        )
+        .await?;
+
+        let plan = table.scan(&ctx.state(), None, &[], None).await?;
+        assert_eq!(plan.fetch(), None);
+
+        let limited = plan.with_fetch(Some(3)).expect("CsvExec supports fetch");
+        assert_eq!(limited.fetch(), Some(3));
+
+        let batches = collect(limited, ctx.task_ctx()).await?;
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_explain_analyze_timing_metrics() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("test.csv");
+
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,Alice").unwrap();
+
+        let ctx = SessionContext::new();
+        let table = create_csv_table_provider(
+            &ctx.state(),
+            csv_path.to_str().unwrap(),
+            CsvFormatOptions::default(),
+        )
+        .await?;
+        ctx.register_table("t", table)?;
+
+        let batches = ctx.sql("EXPLAIN ANALYZE SELECT * FROM t").await?.collect().await?;
+        let plan = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        for metric in ["io_time", "decompress_time", "parse_time", "build_time", "type_mismatches"] {
+            assert!(plan.contains(metric), "missing {} in {}", metric, plan);
+        }
+        for metric in ["output_rows=1", "rows_parsed=1", "files_opened=1", "skipped_rows=0", "bytes_read=16"] {
+            assert!(plan.contains(metric), "missing {} in {}", metric, plan);
+        }
+        assert!(!plan.contains("elapsed_compute=0ns"), "{}", plan);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_batches() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..3 {
+            let mut file = File::create(temp_dir.path().join(format!("{}.csv", i))).unwrap();
+            writeln!(file, "id").unwrap();
+            writeln!(file, "{}", i * 2).unwrap();
+            writeln!(file, "{}", i * 2 + 1).unwrap();
+        }
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let config = datafusion::prelude::SessionConfig::new().with_target_partitions(1);
+        let ctx = SessionContext::new_with_config(config);
+        for coalesce in [false, true] {
+            let options = CsvFormatOptions::default()
+                .with_batch_size(4)
+                .with_coalesce_batches(coalesce);
+            let table = create_csv_table_provider(&ctx.state(), &path, options).await?;
+            let batches = ctx.read_table(table)?.collect().await?;
+            let sizes: Vec<usize> = batches.iter().map(|b| b.num_rows()).collect();
+            let expected = if coalesce { vec![4, 2] } else { vec![2, 2, 2] };
+            assert_eq!(sizes, expected);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_split_large_file() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("events.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "exported 2024-06-01").unwrap();
+        writeln!(file, "name,id").unwrap();
+        for i in 0..1000 {
+            writeln!(file, "\"event, {}\",{}", i, i).unwrap();
+        }
+        drop(file);
+
+        let config = datafusion::prelude::SessionConfig::new()
+            .with_target_partitions(4)
+            .with_repartition_file_min_size(1024);
+        let ctx = SessionContext::new_with_config(config);
+        for newlines_in_values in [false, true] {
+            let options = CsvFormatOptions::default()
+                .with_preamble_rows(1)
+                .with_newlines_in_values(newlines_in_values);
+            let table = create_csv_table_provider(&ctx.state(), csv_path.to_str().unwrap(), options).await?;
+            let df = ctx
+                .read_table(table)?
+                .aggregate(vec![], vec![count(col("name")), sum(col("id"))])?;
+
+            let plan = df.clone().create_physical_plan().await?;
+            let exec = find_csv_exec(&plan).expect("plan scans with CsvExec");
+            let partitions = exec.properties().output_partitioning().partition_count();
+            assert_eq!(partitions, if newlines_in_values { 1 } else { 4 });
+
+            // Every record is read once, by the part it starts in
+            let batches = df.collect().await?;
+            let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+                .unwrap()
+                .to_string();
+            assert!(formatted.contains("| 1000 "), "{}", formatted);
+            assert!(formatted.contains("| 499500 "), "{}", formatted);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quoted_newline_not_split() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("notes.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,note").unwrap();
+        for i in 0..1000 {
+            // Mostly quoted line breaks, so every split boundary falls inside a value
+            writeln!(file, "{},\"{}\"", i, "line\n".repeat(10)).unwrap();
+        }
+        drop(file);
+
+        let config = datafusion::prelude::SessionConfig::new()
+            .with_target_partitions(4)
+            .with_repartition_file_min_size(1024);
+        let ctx = SessionContext::new_with_config(config);
+
+        // Split boundaries land inside quoted values, so by default the file is read whole
+        let path = csv_path.to_str().unwrap();
+        let table = create_csv_table_provider(&ctx.state(), path, CsvFormatOptions::default()).await?;
+        let df = ctx.read_table(table)?;
+        let plan = df.clone().create_physical_plan().await?;
+        let exec = find_csv_exec(&plan).expect("plan scans with CsvExec");
+        assert_eq!(exec.properties().output_partitioning().partition_count(), 1);
+
+        let batches = df.aggregate(vec![], vec![count(col("note")), sum(col("id"))])?.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| 1000 "), "{}", formatted);
+        assert!(formatted.contains("| 499500 "), "{}", formatted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_split_file_bad_row_lines() -> Result<()> {
+        use crate::{CsvError, DeadLetterSink};
+
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("events.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "name,id").unwrap();
+        for i in 0..1000 {
+            if i == 900 {
+                writeln!(file, "event {},x,extra", i).unwrap();
+            } else {
+                writeln!(file, "event {},{}", i, i).unwrap();
+            }
+        }
+        drop(file);
+
+        let config = datafusion::prelude::SessionConfig::new()
+            .with_target_partitions(4)
+            .with_repartition_file_min_size(1024);
+        let ctx = SessionContext::new_with_config(config);
+        let path = csv_path.to_str().unwrap();
+
+        // Rows captured for re-processing keep their line in the file
+        let sink = DeadLetterSink::in_memory();
+        let options = CsvFormatOptions::default()
+            .with_newlines_in_values(false)
+            .with_on_error(ParseErrorPolicy::SkipRow)
+            .with_dead_letter(sink.clone());
+        let table = create_csv_table_provider(&ctx.state(), path, options).await?;
+        let plan = ctx.read_table(table.clone())?.create_physical_plan().await?;
+        let exec = find_csv_exec(&plan).expect("plan scans with CsvExec");
+        assert_eq!(exec.properties().output_partitioning().partition_count(), 1);
+        assert_eq!(ctx.read_table(table)?.count().await?, 999);
+        let lines: Vec<_> = sink.rows().iter().map(|row| row.line).collect();
+        assert_eq!(lines, vec![Some(902)]);
+
+        // A split scan failing in a later part reports no line rather than one
+        // counted from the part's start
+        let options = CsvFormatOptions::default().with_newlines_in_values(false);
+        let table = create_csv_table_provider(&ctx.state(), path, options).await?;
+        let err = ctx.read_table(table)?.collect().await.unwrap_err();
+        match CsvError::find(&err) {
+            Some(CsvError::Parse { line, file, .. }) => {
+                assert_eq!(*line, None, "{}", err);
+                assert!(file.as_deref().is_some_and(|f| f.ends_with("events.csv")), "{}", err);
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_predicate_pushdown() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("orders.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,status,note").unwrap();
+        for i in 0..10 {
+            let status = if i == 7 { "failed" } else { "ok" };
+            writeln!(file, "{},{},note {}", i, status, i).unwrap();
+        }
+        drop(file);
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::default().with_batch_size(2);
+        let table = create_csv_table_provider(&ctx.state(), csv_path.to_str().unwrap(), options).await?;
+        ctx.register_table("orders", table)?;
+
+        // Batches whose rows all fail the predicate are skipped, not ended on
+        let df = ctx
+            .sql("SELECT id, note FROM orders WHERE status = 'failed' AND random() < 2")
+            .await?;
+        let plan = df.clone().create_physical_plan().await?;
+        let exec = find_csv_exec(&plan).expect("plan scans with CsvExec");
+        let display = datafusion::physical_plan::displayable(exec).one_line().to_string();
+        assert!(display.contains("predicate=status@0 = failed"), "{}", display);
+
+        let batches = df.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| 7  | note 7 |"), "{}", formatted);
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..5 {
+            let mut file = File::create(temp_dir.path().join(format!("{}.csv", i))).unwrap();
+            writeln!(file, "id").unwrap();
+            writeln!(file, "{}", i).unwrap();
+        }
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let config = datafusion::prelude::SessionConfig::new().with_target_partitions(1);
+        let ctx = SessionContext::new_with_config(config);
+        let options = CsvFormatOptions::default().with_prefetch_files(2);
+        let table = create_csv_table_provider(&ctx.state(), &path, options).await?;
+        ctx.register_table("t", table)?;
+
+        let batches = ctx.sql("SELECT id FROM t ORDER BY id").await?.collect().await?;
+        let ids: Vec<i64> = batches
+            .iter()
+            .flat_map(|b| {
+                let ids = b.column(0).as_any().downcast_ref::<arrow_array::Int64Array>().unwrap();
+                ids.values().to_vec()
+            })
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+
+        // Stopping early drops downloads that were started ahead
+        let batches = ctx.sql("SELECT id FROM t LIMIT 1").await?.collect().await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_modified_column() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("arrivals.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id").unwrap();
+        writeln!(file, "1").unwrap();
+        writeln!(file, "2").unwrap();
+        let modified = std::fs::metadata(&csv_path).unwrap().modified().unwrap();
+        let modified = chrono::DateTime::<chrono::Utc>::from(modified).timestamp_micros();
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::default()
+            .with_file_modified_column("_modified")
+            .with_schema_drift_check(true);
+        let table = create_csv_table_provider(&ctx.state(), csv_path.to_str().unwrap(), options).await?;
+        ctx.register_table("t", table)?;
+
+        let batches = ctx
+            .sql("SELECT id, _modified FROM t WHERE _modified > '2000-01-01T00:00:00Z'")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(batches[0].num_rows(), 2);
+        let column = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(column.value(1), modified);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_modified_since() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let old_path = temp_dir.path().join("old.csv");
+        std::fs::write(&old_path, "id\n1\n").unwrap();
+        let modified = std::fs::metadata(&old_path).unwrap().modified().unwrap();
+        let watermark = chrono::DateTime::<chrono::Utc>::from(modified);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(temp_dir.path().join("new.csv"), "id\n2\n").unwrap();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::default().with_modified_since(watermark);
+        let table = create_csv_table_provider(&ctx.state(), &path, options).await?;
+        ctx.register_table("landing", table)?;
+
+        let batches = ctx.sql("SELECT id FROM landing").await?.collect().await?;
+        let ids: Vec<i64> = batches
+            .iter()
+            .flat_map(|b| {
+                let ids = b.column(0).as_any().downcast_ref::<arrow_array::Int64Array>().unwrap();
+                ids.values().to_vec()
+            })
+            .collect();
+        assert_eq!(ids, vec![2]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_order() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["b", "d", "a", "c"] {
+            std::fs::write(temp_dir.path().join(format!("{}.csv", name)), format!("name\n{}\n", name)).unwrap();
+        }
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::default().with_file_order(crate::FileOrder::NameDescending);
+        let table = create_csv_table_provider(&ctx.state(), &path, options).await?;
+        let plan = table.scan(&ctx.state(), None, &[], None).await?;
+        let exec = plan.as_any().downcast_ref::<CsvExec>().unwrap();
+        let files: Vec<&str> = exec
+            .config
+            .file_groups
+            .iter()
+            .flatten()
+            .map(|f| f.object_meta.location.filename().unwrap())
+            .collect();
+        assert_eq!(files, vec!["d.csv", "c.csv", "b.csv", "a.csv"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_rows() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..4 {
+            let mut file = File::create(temp_dir.path().join(format!("{}.csv", i))).unwrap();
+            writeln!(file, "id").unwrap();
+            for j in 0..10 {
+                writeln!(file, "{}", i * 10 + j).unwrap();
+            }
+        }
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::default().with_max_rows(15);
+        let table = create_csv_table_provider(&ctx.state(), &path, options).await?;
+        ctx.register_table("t", table)?;
+
+        let batches = ctx.sql("SELECT count(*) FROM t").await?.collect().await?;
+        let count = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap();
+        assert_eq!(count.value(0), 15);
+
+        // A smaller LIMIT still applies
+        let batches = ctx.sql("SELECT id FROM t LIMIT 3").await?.collect().await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builder_row_filter() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("log.csv");
+
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "level,message").unwrap();
+        writeln!(file, "DEBUG,starting").unwrap();
+        writeln!(file, "ERROR,disk full").unwrap();
+        writeln!(file, "DEBUG,retrying").unwrap();
+
+        let ctx = SessionContext::new();
+        let table = CsvSourceBuilder::new(csv_path.to_str().unwrap())
+            .with_row_filter(|record| record.get_by_name("level") != Some("DEBUG"))
+            .build(&ctx.state())
+            .await?;
+
+        let plan = table.scan(&ctx.state(), None, &[], None).await?;
+        let batches = collect(plan, ctx.task_ctx()).await?;
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builder_collect_statistics() -> Result<()> {
+        use datafusion_common::stats::Precision;
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "id,city\n3,Oslo\n,Rome\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.csv"), "id,city\n7,Lima\n").unwrap();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let ctx = SessionContext::new();
+        let table = CsvSourceBuilder::new(&path)
+            .with_collect_statistics(true)
+            .build(&ctx.state())
+            .await?;
+
+        let plan = table.scan(&ctx.state(), None, &[], None).await?;
+        let statistics = plan.statistics()?;
+        assert_eq!(statistics.num_rows, Precision::Exact(3));
+        let id = &statistics.column_statistics[0];
+        assert_eq!(id.null_count, Precision::Exact(1));
+        assert_eq!(id.min_value, Precision::Exact(datafusion_common::ScalarValue::Int64(Some(3))));
+        assert_eq!(id.max_value, Precision::Exact(datafusion_common::ScalarValue::Int64(Some(7))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_cache_ttl() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let mut file = File::create(temp_dir.path().join("a.csv")).unwrap();
+        writeln!(file, "id").unwrap();
+        writeln!(file, "1").unwrap();
+
+        let ctx = SessionContext::new();
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_list_cache_ttl(Some(std::time::Duration::from_secs(60)));
+        let table = create_csv_table_provider(&ctx.state(), &table_path, options).await?;
+        ctx.register_table("events", table)?;
+
+        let count = || async {
+            let batches = ctx.sql("SELECT * FROM events").await?.collect().await?;
+            Ok::<usize, datafusion::error::DataFusionError>(batches.iter().map(|b| b.num_rows()).sum())
+        };
+        assert_eq!(count().await?, 1);
+
+        // A file dropped in by another writer is not seen until the listing expires
+        let mut file = File::create(temp_dir.path().join("b.csv")).unwrap();
+        writeln!(file, "id").unwrap();
+        writeln!(file, "2").unwrap();
+        assert_eq!(count().await?, 1);
+
+        // Inserting through the table refreshes the listing
+        ctx.sql("INSERT INTO events VALUES (3)").await?.collect().await?;
+        assert_eq!(count().await?, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..4 {
+            let mut file = File::create(temp_dir.path().join(format!("{}.csv", i))).unwrap();
+            writeln!(file, "id").unwrap();
+            writeln!(file, "{}", i).unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_max_concurrent_requests(Some(2));
+        let table = create_csv_table_provider(&ctx.state(), &table_path, options).await?;
+        ctx.register_table("events", table)?;
+
+        let store_url = ObjectStoreUrl::local_filesystem();
+        let store = ctx.runtime_env().object_store(&store_url)?;
+        assert!(store.to_string().starts_with("CachedListStore(LimitStore(2,"), "{}", store);
+
+        let batches = ctx.sql("SELECT * FROM events").await?.collect().await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+
+        // Tables registered later on the store update its wrapper instead of nesting it
+        let options = CsvFormatOptions::new()
+            .with_max_concurrent_requests(Some(2))
+            .with_list_cache_ttl(Some(std::time::Duration::from_secs(60)));
+        for name in ["again", "once_more"] {
+            let table = create_csv_table_provider(&ctx.state(), &table_path, options.clone()).await?;
+            ctx.register_table(name, table)?;
+        }
+        let store = ctx.runtime_env().object_store(&store_url)?.to_string();
+        assert!(store.starts_with("CachedListStore(LimitStore(2,"), "{}", store);
+        assert_eq!(store.matches("LimitStore(").count(), 1, "{}", store);
+        assert_eq!(store.matches("CachedListStore(").count(), 1, "{}", store);
+
+        let batches = ctx.sql("SELECT * FROM events").await?.collect().await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+
+        // A conflicting cap is rejected rather than replacing the first one
+        let options = CsvFormatOptions::new().with_max_concurrent_requests(Some(3));
+        let err = create_csv_table_provider(&ctx.state(), &table_path, options.clone())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already caps concurrent requests at 2"), "{}", err);
+
+        // Caps belong to the runtime, so another session can pick its own
+        let other = SessionContext::new();
+        create_csv_table_provider(&other.state(), &table_path, options).await?;
+        let store = other.runtime_env().object_store(&store_url)?;
+        assert!(store.to_string().starts_with("CachedListStore(LimitStore(3,"), "{}", store);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_latest_file() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        for (name, value) in [("snapshot_2024-03-01.csv", 3), ("snapshot_2024-01-01.csv", 1)] {
+            let mut file = File::create(temp_dir.path().join(name)).unwrap();
+            writeln!(file, "version").unwrap();
+            writeln!(file, "{}", value).unwrap();
+            file.sync_all().unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let mut other = File::create(temp_dir.path().join("notes.txt")).unwrap();
+        writeln!(other, "ignored").unwrap();
+
+        let ctx = SessionContext::new();
+        let pattern = format!("{}/snapshot_*.csv", temp_dir.path().to_str().unwrap());
+        for (by, expected) in [(LatestFileBy::Name, 3), (LatestFileBy::ModifiedTime, 1)] {
+            let table = CsvSourceBuilder::new(&pattern)
+                .with_latest_file(by)
+                .build(&ctx.state())
+                .await?;
+            let plan = table.scan(&ctx.state(), None, &[], None).await?;
+            let batches = collect(plan, ctx.task_ctx()).await?;
+            let versions = batches[0]
+                .column(0)
+                .as_any()
+                .downcast_ref::<arrow_array::Int64Array>()
+                .unwrap();
+            assert_eq!(versions.values(), &[expected]);
+        }
+
+        let missing = CsvSourceBuilder::new(format!("{}/none_*.csv", temp_dir.path().to_str().unwrap()))
+            .with_latest_file(LatestFileBy::Name)
+            .build(&ctx.state())
+            .await;
+        assert!(missing.unwrap_err().to_string().contains("No CSV files match"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_date_range_path() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        // 2024-01-01 has no partition directory; 2024-01-05 is outside the range
+        for (day, value) in [("2024-01-02", 2), ("2024-01-03", 3), ("2024-01-05", 5)] {
+            let dir = temp_dir.path().join(format!("dt={}", day));
+            std::fs::create_dir(&dir).unwrap();
+            let mut file = File::create(dir.join("events.csv")).unwrap();
+            writeln!(file, "value").unwrap();
+            writeln!(file, "{}", value).unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        let path = format!(
+            "{}/dt={{2024-01-01..2024-01-03}}/*.csv",
+            temp_dir.path().to_str().unwrap()
+        );
+        let table = create_csv_table_provider(&ctx.state(), &path, CsvFormatOptions::default()).await?;
+        ctx.register_table("events", table)?;
+
+        let batches = ctx
+            .sql("SELECT sum(value) FROM events")
+            .await?
+            .collect()
+            .await?;
+        let sum = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap();
+        assert_eq!(sum.value(0), 5);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_typed_partition_columns() -> Result<()> {
+        use arrow_schema::DataType;
+
+        let temp_dir = TempDir::new().unwrap();
+        for (year, dt, amount) in [(2023, "2023-12-31", 10), (2024, "2024-01-15", 20), (2024, "2024-02-01", 30)] {
+            let dir = temp_dir.path().join(format!("year={}/dt={}", year, dt));
+            std::fs::create_dir_all(&dir).unwrap();
+            let mut file = File::create(dir.join("sales.csv")).unwrap();
+            writeln!(file, "amount").unwrap();
+            writeln!(file, "{}", amount).unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new()
+            .with_partition_column("year", DataType::Int32)
+            .with_partition_column("dt", DataType::Date32);
+        let table_path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let table = create_csv_table_provider(&ctx.state(), &table_path, options).await?;
+        ctx.register_table("sales", table)?;
+
+        let df = ctx
+            .sql("SELECT year, dt, amount FROM sales WHERE dt >= DATE '2024-01-01' AND year > 2023 ORDER BY dt")
+            .await?;
+        assert_eq!(df.schema().field(0).data_type(), &DataType::Int32);
+        assert_eq!(df.schema().field(1).data_type(), &DataType::Date32);
+
+        let batches = df.collect().await?;
+        let amounts = batches[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap();
+        assert_eq!(amounts.values(), &[20, 30]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multi_store_table() -> Result<()> {
+        use object_store::memory::InMemory;
+        use object_store::path::Path;
+        use object_store::{ObjectStore, PutPayload};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut file = File::create(temp_dir.path().join("staging.csv")).unwrap();
+        writeln!(file, "id,source").unwrap();
+        writeln!(file, "1,staging").unwrap();
+
+        let archive = Arc::new(InMemory::new());
+        archive
+            .put(
+                &Path::from("archive/2023.csv"),
+                PutPayload::from_static(b"id,source\n2,archive\n3,archive\n"),
+            )
+            .await
+            .unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_object_store(&Url::parse("memory://").unwrap(), archive);
+
+        let table = CsvSourceBuilder::new(format!("{}/", temp_dir.path().to_str().unwrap()))
+            .with_additional_path("memory:///archive/")
+            .build(&ctx.state())
+            .await?;
+        assert!(table.as_any().is::<MultiStoreCsvTable>());
+        ctx.register_table("events", table)?;
+
+        let batches = ctx
+            .sql("SELECT source, count(*) AS n FROM events GROUP BY source ORDER BY source")
+            .await?
+            .collect()
+            .await?;
+        let sources = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        let counts = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap();
+        assert_eq!(sources.value(0), "archive");
+        assert_eq!(counts.values(), &[2, 1]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_builder_with_object_store() -> Result<()> {
+        use object_store::memory::InMemory;
+        use object_store::path::Path;
+        use object_store::PutPayload;
+
+        // A test double standing in for a remote HTTPS endpoint
+        let store = Arc::new(InMemory::new());
+        store
+            .put(&Path::from("exports/users.csv"), PutPayload::from_static(b"id,name\n1,Alice\n2,Bob\n"))
+            .await
+            .unwrap();
+
+        let ctx = SessionContext::new();
+        let table = CsvSourceBuilder::new("https://data.example.com/exports/users.csv")
+            .with_object_store("https://data.example.com", store)
+            .build(&ctx.state())
+            .await?;
+
+        let plan = table.scan(&ctx.state(), None, &[], None).await?;
+        let batches = collect(plan, ctx.task_ctx()).await?;
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 2);
+
+        let invalid = CsvSourceBuilder::new("data.csv")
+            .with_object_store("not a url", Arc::new(InMemory::new()))
+            .build(&ctx.state())
+            .await;
+        assert!(invalid.unwrap_err().to_string().contains("Invalid object store URL"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_content_encoding() -> Result<()> {
+        use object_store::memory::InMemory;
+        use object_store::path::Path;
+        use object_store::{Attribute, Attributes, PutOptions};
+
+        // A server returning `Content-Encoding: gzip` for a plain `.csv` URL
+        let store = Arc::new(InMemory::new());
+        let body = crate::testing::compress(b"id,name\n1,Alice\n2,Bob\n", CsvCompression::Gzip).await?;
+        let mut attributes = Attributes::new();
+        attributes.insert(Attribute::ContentEncoding, "gzip".into());
+        let options = PutOptions {
+            attributes,
+            ..Default::default()
+        };
+        store.put_opts(&Path::from("users.csv"), body.into(), options).await.unwrap();
+
+        let ctx = SessionContext::new();
+        let table = CsvSourceBuilder::new("https://data.example.com/users.csv")
+            .with_object_store("https://data.example.com", store)
+            .build(&ctx.state())
+            .await?;
+        ctx.register_table("users", table)?;
+        let batches = ctx.sql("SELECT name FROM users").await?.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| Alice |"), "{}", formatted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_standalone_table() -> Result<()> {
+        use arrow_schema::{DataType, Field};
+        use object_store::memory::InMemory;
+        use object_store::path::Path;
+        use object_store::PutPayload;
+
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        for (name, contents) in [
+            ("tables/orders/a.csv", "id,amount\n1,9.5\n"),
+            ("tables/orders/b.csv", "id,amount\n2,20.0\n3,1.25\n"),
+        ] {
+            store.put(&Path::from(name), PutPayload::from_static(contents.as_bytes())).await.unwrap();
+        }
+
+        // Built without any session; the querying context has no store for this URL
+        let table = StandaloneCsvTable::try_new(store.clone(), "memory:///tables/orders/", CsvFormatOptions::default())
+            .await?;
+        assert_eq!(table.schema().field(1).data_type(), &DataType::Float64);
+
+        let ctx = SessionContext::new();
+        ctx.register_table("orders", Arc::new(table))?;
+        let batches = ctx.sql("SELECT count(*) FROM orders").await?.collect().await?;
+        let count = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap();
+        assert_eq!(count.value(0), 3);
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, true),
+            Field::new("amount", DataType::Utf8, true),
+        ]));
+        let table = StandaloneCsvTable::try_new_with_schema(
+            store,
+            "memory:///tables/orders/b.csv",
+            schema,
+            CsvFormatOptions::default(),
+        )?;
+        let plan = table.scan(&ctx.state(), Some(&vec![1]), &[], None).await?;
+        let batches = collect(plan, ctx.task_ctx()).await?;
+        let amounts = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(amounts.value(1), "1.25");
+
+        Ok(())
+    }
 }
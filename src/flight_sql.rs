@@ -0,0 +1,440 @@
+//! Arrow Flight SQL server for a session's tables
+//!
+//! [`CsvFlightSqlService`] answers Flight SQL requests from a
+//! [`SessionContext`], so BI tools and other processes can query the CSV
+//! tables registered in it over the network, through the Flight SQL JDBC or
+//! ADBC drivers, without copying the files:
+//!
+//! ```no_run
+//! use datafusion::prelude::*;
+//! use datafusion_csv::{CsvFlightSqlService, SessionContextCsvExt};
+//!
+//! # async fn example() -> datafusion_common::Result<()> {
+//! let ctx = SessionContext::new();
+//! ctx.register_csv_file("orders", "data/orders.csv").await?;
+//! CsvFlightSqlService::new(ctx).serve("0.0.0.0:50051".parse().unwrap()).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Clients run SQL queries, directly or as prepared statements without
+//! parameters, and list catalogs, schemas and tables. The session is
+//! read-only: statements that would create, drop or modify tables are
+//! rejected. There is no authentication; put the server behind a proxy that
+//! provides it when exposing it beyond a trusted network.
+
+// `FlightSqlService` fixes `Status` as the error type, so helpers share it
+#![allow(clippy::result_large_err)]
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow::record_batch::RecordBatch;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::FlightServiceServer;
+use arrow_flight::sql::metadata::{SqlInfoData, SqlInfoDataBuilder};
+use arrow_flight::sql::server::FlightSqlService;
+use arrow_flight::sql::{
+    ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest, ActionCreatePreparedStatementResult,
+    Any, CommandGetCatalogs, CommandGetDbSchemas, CommandGetSqlInfo, CommandGetTables,
+    CommandPreparedStatementQuery, CommandStatementQuery, ProstMessageExt, SqlInfo, TicketStatementQuery,
+};
+use arrow_flight::{Action, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo, IpcMessage, SchemaAsIpc, Ticket};
+use arrow_schema::{ArrowError, Schema, SchemaRef};
+use bytes::Bytes;
+use datafusion::datasource::TableType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SQLOptions;
+use datafusion::prelude::{DataFrame, SessionContext};
+use futures::{Stream, TryStreamExt};
+use prost::Message;
+use tonic::{Request, Response, Status};
+
+type DoGetStream = Pin<Box<dyn Stream<Item = std::result::Result<FlightData, Status>> + Send + 'static>>;
+
+/// Flight SQL service answering queries against a [`SessionContext`]
+///
+/// Every table registered in the context, at any time, is visible to clients.
+#[derive(Clone)]
+pub struct CsvFlightSqlService {
+    ctx: SessionContext,
+}
+
+impl CsvFlightSqlService {
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+
+    /// The gRPC service, to add to a `tonic` server alongside other services
+    pub fn into_server(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Serve Flight SQL on `addr` until the server fails
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        tonic::transport::Server::builder()
+            .add_service(self.into_server())
+            .serve(addr)
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))
+    }
+
+    /// Plan a read-only query
+    async fn plan(&self, sql: &str) -> std::result::Result<DataFrame, Status> {
+        let options = SQLOptions::new()
+            .with_allow_ddl(false)
+            .with_allow_dml(false)
+            .with_allow_statements(false);
+        self.ctx.sql_with_options(sql, options).await.map_err(status)
+    }
+
+    /// Run a query, streaming its batches as they are produced
+    async fn execute(&self, sql: &str) -> std::result::Result<Response<DoGetStream>, Status> {
+        let stream = self.plan(sql).await?.execute_stream().await.map_err(status)?;
+        let schema = stream.schema();
+        let batches = stream.map_err(|e| FlightError::ExternalError(Box::new(e)));
+        Ok(encode(schema, batches))
+    }
+
+    fn sql_info(&self) -> std::result::Result<SqlInfoData, Status> {
+        let mut builder = SqlInfoDataBuilder::new();
+        builder.append(SqlInfo::FlightSqlServerName, "datafusion-csv");
+        builder.append(SqlInfo::FlightSqlServerVersion, env!("CARGO_PKG_VERSION"));
+        builder.append(SqlInfo::FlightSqlServerArrowVersion, "1.3");
+        builder.append(SqlInfo::FlightSqlServerReadOnly, true);
+        builder.build().map_err(Status::from)
+    }
+}
+
+#[tonic::async_trait]
+impl FlightSqlService for CsvFlightSqlService {
+    type FlightService = CsvFlightSqlService;
+
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let df = self.plan(&query.query).await?;
+        // The statement is planned again when fetched, so the ticket carries its text
+        let ticket = TicketStatementQuery {
+            statement_handle: query.query.into(),
+        };
+        flight_info(df.schema().as_arrow(), ticket.as_any(), request.into_inner())
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<DoGetStream>, Status> {
+        self.execute(&statement_text(&ticket.statement_handle)?).await
+    }
+
+    async fn do_action_create_prepared_statement(
+        &self,
+        query: ActionCreatePreparedStatementRequest,
+        _request: Request<Action>,
+    ) -> std::result::Result<ActionCreatePreparedStatementResult, Status> {
+        let df = self.plan(&query.query).await?;
+        let message: IpcMessage = SchemaAsIpc::new(df.schema().as_arrow(), &IpcWriteOptions::default())
+            .try_into()
+            .map_err(arrow_status)?;
+        // Handles are the statement text, so no server state outlives a request
+        Ok(ActionCreatePreparedStatementResult {
+            prepared_statement_handle: query.query.into(),
+            dataset_schema: message.0,
+            parameter_schema: Bytes::new(),
+        })
+    }
+
+    async fn do_action_close_prepared_statement(
+        &self,
+        _query: ActionClosePreparedStatementRequest,
+        _request: Request<Action>,
+    ) -> std::result::Result<(), Status> {
+        Ok(())
+    }
+
+    async fn get_flight_info_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let df = self.plan(&statement_text(&query.prepared_statement_handle)?).await?;
+        flight_info(df.schema().as_arrow(), query.as_any(), request.into_inner())
+    }
+
+    async fn do_get_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<DoGetStream>, Status> {
+        self.execute(&statement_text(&query.prepared_statement_handle)?).await
+    }
+
+    async fn get_flight_info_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let schema = query.into_builder().schema();
+        flight_info(&schema, query.as_any(), request.into_inner())
+    }
+
+    async fn do_get_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        for catalog in self.ctx.catalog_names() {
+            builder.append(catalog);
+        }
+        encode_batch(builder.build())
+    }
+
+    async fn get_flight_info_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        flight_info(&schema, query.as_any(), request.into_inner())
+    }
+
+    async fn do_get_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        for catalog_name in self.ctx.catalog_names() {
+            let Some(catalog) = self.ctx.catalog(&catalog_name) else {
+                continue;
+            };
+            for schema_name in catalog.schema_names() {
+                builder.append(&catalog_name, schema_name);
+            }
+        }
+        encode_batch(builder.build())
+    }
+
+    async fn get_flight_info_tables(
+        &self,
+        query: CommandGetTables,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        flight_info(&schema, query.as_any(), request.into_inner())
+    }
+
+    async fn do_get_tables(
+        &self,
+        query: CommandGetTables,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<DoGetStream>, Status> {
+        // The builder applies the request's catalog, schema, name and type filters
+        let mut builder = query.into_builder();
+        for catalog_name in self.ctx.catalog_names() {
+            let Some(catalog) = self.ctx.catalog(&catalog_name) else {
+                continue;
+            };
+            for schema_name in catalog.schema_names() {
+                let Some(schema) = catalog.schema(&schema_name) else {
+                    continue;
+                };
+                for table_name in schema.table_names() {
+                    let Some(table) = schema.table(&table_name).await.map_err(status)? else {
+                        continue;
+                    };
+                    let table_type = match table.table_type() {
+                        TableType::Base => "TABLE",
+                        TableType::View => "VIEW",
+                        TableType::Temporary => "LOCAL TEMPORARY",
+                    };
+                    builder
+                        .append(&catalog_name, &schema_name, &table_name, table_type, &table.schema())
+                        .map_err(Status::from)?;
+                }
+            }
+        }
+        encode_batch(builder.build())
+    }
+
+    async fn get_flight_info_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder(&self.sql_info()?).schema();
+        flight_info(&schema, query.as_any(), request.into_inner())
+    }
+
+    async fn do_get_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<DoGetStream>, Status> {
+        encode_batch(query.into_builder(&self.sql_info()?).build())
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+/// Describe a result with `schema`, fetched by redeeming `ticket`
+fn flight_info(
+    schema: &Schema,
+    ticket: Any,
+    descriptor: FlightDescriptor,
+) -> std::result::Result<Response<FlightInfo>, Status> {
+    let endpoint = FlightEndpoint::new().with_ticket(Ticket::new(ticket.encode_to_vec()));
+    let info = FlightInfo::new()
+        .try_with_schema(schema)
+        .map_err(arrow_status)?
+        .with_endpoint(endpoint)
+        .with_descriptor(descriptor);
+    Ok(Response::new(info))
+}
+
+fn encode(
+    schema: SchemaRef,
+    batches: impl Stream<Item = std::result::Result<RecordBatch, FlightError>> + Send + 'static,
+) -> Response<DoGetStream> {
+    let stream = FlightDataEncoderBuilder::new()
+        .with_schema(schema)
+        .build(batches)
+        .map_err(Status::from);
+    Response::new(Box::pin(stream))
+}
+
+fn encode_batch(
+    batch: std::result::Result<RecordBatch, FlightError>,
+) -> std::result::Result<Response<DoGetStream>, Status> {
+    let batch = batch.map_err(Status::from)?;
+    Ok(encode(batch.schema(), futures::stream::once(async move { Ok(batch) })))
+}
+
+/// The SQL text carried by a ticket or prepared statement handle
+fn statement_text(handle: &Bytes) -> std::result::Result<String, Status> {
+    String::from_utf8(handle.to_vec()).map_err(|_| Status::invalid_argument("Statement handle is not UTF-8 SQL"))
+}
+
+/// Report planning errors as the client's fault and anything else as the server's
+fn status(e: DataFusionError) -> Status {
+    match e {
+        DataFusionError::SQL(..) | DataFusionError::Plan(_) | DataFusionError::SchemaError(..) => {
+            Status::invalid_argument(e.to_string())
+        }
+        e => Status::internal(e.to_string()),
+    }
+}
+
+fn arrow_status(e: ArrowError) -> Status {
+    Status::internal(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+    use arrow_flight::decode::FlightRecordBatchStream;
+    use arrow_flight::flight_service_server::FlightService;
+    use arrow_flight::sql::client::FlightSqlServiceClient;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    use crate::SessionContextCsvExt;
+
+    /// Plan `command` and fetch its result through the service, as a client would
+    async fn fetch(service: &CsvFlightSqlService, command: Any) -> std::result::Result<Vec<RecordBatch>, Status> {
+        let descriptor = FlightDescriptor::new_cmd(command.encode_to_vec());
+        let info = service.get_flight_info(Request::new(descriptor)).await?.into_inner();
+        let ticket = info.endpoint[0].ticket.clone().expect("endpoint has a ticket");
+        let stream = service.do_get(Request::new(ticket)).await?.into_inner();
+        FlightRecordBatchStream::new_from_flight_data(stream.map_err(FlightError::from))
+            .try_collect()
+            .await
+            .map_err(Status::from)
+    }
+
+    #[tokio::test]
+    async fn test_flight_sql_queries() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("orders.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,amount").unwrap();
+        for i in 0..10 {
+            writeln!(file, "{},{}", i, i * 10).unwrap();
+        }
+        let ctx = SessionContext::new();
+        ctx.register_csv_file("orders", csv_path.to_str().unwrap()).await?;
+        let service = CsvFlightSqlService::new(ctx);
+
+        let query = CommandStatementQuery {
+            query: "SELECT sum(amount) FROM orders WHERE id < 5".to_string(),
+            transaction_id: None,
+        };
+        let batches = fetch(&service, query.as_any()).await.unwrap();
+        let sum = batches[0].column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(sum.value(0), 100);
+
+        let tables = CommandGetTables {
+            table_name_filter_pattern: Some("ord%".to_string()),
+            ..Default::default()
+        };
+        let batches = fetch(&service, tables.as_any()).await.unwrap();
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), 1);
+
+        // The session is read-only
+        let drop = CommandStatementQuery {
+            query: "DROP TABLE orders".to_string(),
+            transaction_id: None,
+        };
+        let err = fetch(&service, drop.as_any()).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument, "{}", err);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_flight_sql_over_network() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("orders.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,amount").unwrap();
+        for i in 0..10 {
+            writeln!(file, "{},{}", i, i * 10).unwrap();
+        }
+        let ctx = SessionContext::new();
+        ctx.register_csv_file("orders", csv_path.to_str().unwrap()).await?;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tonic::transport::Server::builder()
+            .add_service(CsvFlightSqlService::new(ctx).into_server())
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
+        let server = tokio::spawn(server);
+
+        let channel = tonic::transport::Endpoint::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = FlightSqlServiceClient::new(channel);
+        let info = client
+            .execute("SELECT sum(amount) FROM orders WHERE id >= 5".to_string(), None)
+            .await
+            .unwrap();
+        let ticket = info.endpoint[0].ticket.clone().expect("endpoint has a ticket");
+        let batches: Vec<RecordBatch> = client.do_get(ticket).await.unwrap().try_collect().await.unwrap();
+        let sum = batches[0].column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(sum.value(0), 350);
+
+        server.abort();
+        Ok(())
+    }
+}
@@ -8,8 +8,23 @@
 //! The crate is organized into several modules following the datafusion-orc pattern:
 //! - `file_format` - CSV format configuration and options
 //! - `file_source` - CSV source builders and table providers
+//! - `file_sink` - CSV data sink for `INSERT INTO` / `INSERT OVERWRITE`
+//! - `dead_letter` - Capture of rows dropped by the `SkipRow` error policy
+//! - `diagnostics` - Data-quality profiling and validation of CSV files
+//! - `dml` - UPDATE/DELETE by rewriting a table's files
+//! - `error` - Structured `CsvError` for read failures
+//! - `codec` - Protobuf serialization of CSV scans with the `proto` feature
+//! - `flight_sql` - Arrow Flight SQL server with the `flight-sql` feature
+//! - `cache` - Listing, statistics and schema caches shared across queries
+//! - `cloud` - Object stores for HTTP, `s3://`, `gs://` and `az://`/`abfss://` URLs
 //! - `physical_exec` - Physical execution configuration
 //! - `object_store_reader` - Object store integration utilities
+//! - `sidecar` - `.schema.json` sidecar files describing a CSV file's schema
+//! - `substrait` - Substrait plans that carry their CSV tables, with the `substrait` feature
+//! - `statistics` - Exact statistics collection for query planning
+//! - `table_functions` - SQL table functions such as `csv_sample`
+//! - `telemetry` - Per-file scan metrics emitted with the `tracing` feature
+//! - `testing` - Synthetic CSV data generator for validating pipelines
 //!
 //! # Example
 //!
@@ -32,23 +47,62 @@
 //! }
 //! ```
 
+mod cache;
+mod cloud;
+#[cfg(feature = "proto")]
+mod codec;
+mod dead_letter;
+mod diagnostics;
+mod dml;
+mod error;
 mod file_format;
+mod file_sink;
 mod file_source;
+#[cfg(feature = "flight-sql")]
+mod flight_sql;
 mod object_store_reader;
+#[cfg(any(feature = "proto", feature = "substrait"))]
+mod options_json;
 mod physical_exec;
+mod sidecar;
+mod statistics;
+#[cfg(feature = "substrait")]
+pub mod substrait;
+mod table_functions;
+mod telemetry;
+pub mod testing;
 
 // Re-export public types
-pub use file_format::CsvFormatOptions;
-pub use file_source::CsvSourceBuilder;
-pub use object_store_reader::CsvFileMetadata;
+pub use cache::{csv_cache_manager_config, CsvSchemaCache};
+pub use cloud::{AzureConfig, GcsConfig, HttpConfig, S3Config};
+#[cfg(feature = "proto")]
+pub use codec::CsvPhysicalCodec;
+pub use dead_letter::{DeadLetterSink, RejectedRow};
+pub use diagnostics::{CsvIssue, CsvIssueKind};
+pub use error::CsvError;
+pub use file_format::{
+    BinaryColumn, BinaryEncoding, Categories, ColumnParser, ColumnParserFn, ControlCharPolicy, CsvCompression, CsvFormatOptions,
+    FileOrder, IntegerOverflowPolicy, ParseErrorPolicy, RawRecord, schema_fingerprint, RowFilter, RowFilterFn,
+    StringOverflowPolicy, UnseenCategoryPolicy, ValueTransform,
+};
+#[cfg(feature = "flight-sql")]
+pub use flight_sql::CsvFlightSqlService;
+pub use file_source::{CsvSourceBuilder, LatestFileBy, MultiStoreCsvTable, StandaloneCsvTable};
+pub use object_store_reader::{estimate_row_count, list_csv_files, CsvFileMetadata};
+pub use table_functions::CsvSampleFunc;
+
+use std::sync::Arc;
 
 use datafusion::prelude::*;
 use datafusion_common::Result;
 
 /// Extension trait for SessionContext to add convenient CSV registration methods
+#[allow(async_fn_in_trait)]
 pub trait SessionContextCsvExt {
     /// Register a CSV file as a table with default options
     ///
+    /// `path` may also be a directory, whose CSV files become one table.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -58,6 +112,7 @@ pub trait SessionContextCsvExt {
     /// # async fn example() -> datafusion_common::Result<()> {
     /// let ctx = SessionContext::new();
     /// ctx.register_csv_file("users", "data/users.csv").await?;
+    /// ctx.register_csv_file("events", "data/events/").await?;
     /// # Ok(())
     /// # }
     /// ```
@@ -110,6 +165,92 @@ pub trait SessionContextCsvExt {
         options: CsvFormatOptions,
     ) -> Result<()>;
 
+    /// Register a CSV file with a known schema, skipping inference
+    ///
+    /// Fields are matched to the file's columns by position, and values are
+    /// parsed as the field's type.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use arrow_schema::{DataType, Field, Schema};
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::{CsvFormatOptions, SessionContextCsvExt};
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let schema = Arc::new(Schema::new(vec![
+    ///     Field::new("id", DataType::Int64, false),
+    ///     Field::new("zip", DataType::Utf8, true),
+    /// ]));
+    ///
+    /// ctx.register_csv_with_schema("addresses", "huge.csv", schema, CsvFormatOptions::new())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn register_csv_with_schema(
+        &self,
+        name: &str,
+        path: &str,
+        schema: arrow_schema::SchemaRef,
+        options: CsvFormatOptions,
+    ) -> Result<()>;
+
+    /// Register a CSV file on S3 as a table, connecting with `config`
+    ///
+    /// The store built from `config` is registered for the path's bucket,
+    /// replacing any registered before. Needs the `s3` feature; without
+    /// explicit settings, plain `register_csv_file` reads `s3://` paths with
+    /// credentials from the environment.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::{S3Config, SessionContextCsvExt};
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let config = S3Config::new()
+    ///     .with_region("eu-west-1")
+    ///     .with_credentials("AKIA...", "secret");
+    /// ctx.register_csv_s3("orders", "s3://landing/orders/", config).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn register_csv_s3(&self, name: &str, path: &str, config: S3Config) -> Result<()>;
+
+    /// Register a CSV file as a table, reading it from `store`
+    ///
+    /// The store is registered for the scheme and host of `path` (e.g.
+    /// `s3://bucket`) instead of one the crate would create from the URL, which
+    /// suits MinIO clients, in-memory stores in tests and other custom stores.
+    /// Other tables under that URL read from it too.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::SessionContextCsvExt;
+    /// use object_store::memory::InMemory;
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let store = Arc::new(InMemory::new());
+    /// ctx.register_csv_with_store("orders", "s3://mock/orders.csv", store).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn register_csv_with_store(
+        &self,
+        name: &str,
+        path: &str,
+        store: Arc<dyn object_store::ObjectStore>,
+    ) -> Result<()>;
+
     /// Read a CSV file into a DataFrame with default options
     ///
     /// # Example
@@ -144,6 +285,255 @@ pub trait SessionContextCsvExt {
     /// ```
     async fn read_csv_with_options(&self, path: &str, options: CsvFormatOptions)
         -> Result<DataFrame>;
+
+    /// Read several CSV files into a single DataFrame
+    ///
+    /// Files must have the same columns in the same order. Numeric columns
+    /// inferred as integers in some files and floats in others are read as
+    /// floats; other type differences are read as strings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::SessionContextCsvExt;
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let df = ctx.read_csv_files(&["data/jan.csv", "data/feb.csv"]).await?;
+    /// df.show().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn read_csv_files(&self, paths: &[&str]) -> Result<DataFrame>;
+
+    /// Read CSV text held in memory into a DataFrame
+    ///
+    /// The text goes through the same schema inference and parsing as files,
+    /// which makes it handy for tests, examples and notebooks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::SessionContextCsvExt;
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let df = ctx.read_csv_str("a,b\n1,2\n3,4").await?;
+    /// df.show().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn read_csv_str(&self, data: &str) -> Result<DataFrame>;
+
+    /// Estimate the number of rows in a CSV file or directory without scanning it
+    ///
+    /// A few chunks of each file are read to measure the average row size,
+    /// which is extrapolated from the file size. Small files are counted exactly.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::SessionContextCsvExt;
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let rows = ctx.estimate_row_count("data/events.csv").await?;
+    /// println!("~{:.1}M rows", rows as f64 / 1e6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn estimate_row_count(&self, path: &str) -> Result<u64>;
+
+    /// Fingerprint the schema inferred for a CSV file with `options`
+    ///
+    /// See [`schema_fingerprint`]; store the result and compare it on the
+    /// next run to notice when an upstream producer changes its columns.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::{CsvFormatOptions, SessionContextCsvExt};
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let fingerprint = ctx
+    ///     .csv_schema_fingerprint("data/users.csv", CsvFormatOptions::default())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn csv_schema_fingerprint(&self, path: &str, options: CsvFormatOptions) -> Result<String>;
+
+    /// Profile the data quality of the CSV files at `path`
+    ///
+    /// Returns one row per column with `column`, `data_type`, `rows`,
+    /// `null_count`, `type_violations` (non-null values that do not parse as
+    /// the inferred type), `min`, `max`, `distinct_estimate` and up to five
+    /// sample `bad_values`. Every row of every file is read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::{CsvFormatOptions, SessionContextCsvExt};
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let profile = ctx.profile_csv("incoming/orders.csv", CsvFormatOptions::default()).await?;
+    /// println!("{}", arrow::util::pretty::pretty_format_batches(&[profile])?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn profile_csv(&self, path: &str, options: CsvFormatOptions) -> Result<arrow::record_batch::RecordBatch>;
+
+    /// Check the CSV files at `path` for problems without producing query results
+    ///
+    /// Reports ragged rows, values that are not valid UTF-8 and values that do
+    /// not parse as their column's inferred type, each with its file and line
+    /// number. An empty list means a scan with `options` reads every value.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::{CsvFormatOptions, SessionContextCsvExt};
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// for issue in ctx.validate_csv("incoming/orders.csv", CsvFormatOptions::default()).await? {
+    ///     println!("{}:{}: {}", issue.file, issue.line, issue.message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn validate_csv(&self, path: &str, options: CsvFormatOptions) -> Result<Vec<CsvIssue>>;
+
+    /// Infer the schema of the CSV files at `path` and save it as a sidecar
+    ///
+    /// Writes `<file>.schema.json` next to the first file, listing each
+    /// column's `name`, Arrow `type` and `nullable` flag. Edit it to pin the
+    /// contract of a recurring feed, then read with
+    /// [`CsvFormatOptions::with_schema_sidecar`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::{CsvFormatOptions, SessionContextCsvExt};
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// ctx.write_csv_schema_sidecar("feeds/orders.csv", CsvFormatOptions::default())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn write_csv_schema_sidecar(&self, path: &str, options: CsvFormatOptions) -> Result<()>;
+
+    /// Delete rows matching a SQL predicate from a CSV table
+    ///
+    /// The table's files are rewritten without the deleted rows, so the table
+    /// must be registered from a directory path. Tables whose scans leave rows
+    /// out, such as those capped with `with_max_rows`, pre-filtered with
+    /// `with_row_filter`, skipping bad rows with `ParseErrorPolicy::SkipRow` or
+    /// reading only files newer than `with_modified_since`, are refused. Returns the number of deleted rows.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::SessionContextCsvExt;
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// ctx.register_csv_file("users", "data/users/").await?;
+    /// let deleted = ctx.delete_csv_rows("users", "country = 'USA'").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn delete_csv_rows(&self, table: &str, predicate: &str) -> Result<u64>;
+
+    /// Update rows of a CSV table, assigning SQL expressions to columns
+    ///
+    /// Rows matching `predicate` (or all rows when `None`) get the assigned
+    /// values; the table's files are then rewritten. Tables refused by
+    /// [`delete_csv_rows`](Self::delete_csv_rows) are refused here too. Returns
+    /// the number of updated rows.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::SessionContextCsvExt;
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// ctx.register_csv_file("users", "data/users/").await?;
+    /// ctx.update_csv_rows("users", &[("country", "'US'")], Some("country = 'USA'"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn update_csv_rows(
+        &self,
+        table: &str,
+        assignments: &[(&str, &str)],
+        predicate: Option<&str>,
+    ) -> Result<u64>;
+
+    /// Collect exact statistics for a registered CSV table
+    ///
+    /// Reads every file of the table once, like SQL's `ANALYZE TABLE`, and
+    /// re-registers the table with each file's row count, null counts and
+    /// min/max values. The optimizer uses them for later queries; files that
+    /// change afterwards are planned without statistics until analyzed again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::SessionContextCsvExt;
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// ctx.register_csv_file("orders", "data/orders/").await?;
+    /// ctx.analyze_csv_table("orders").await?;
+    /// // Answered from statistics, without reading the files
+    /// ctx.sql("SELECT count(*) FROM orders").await?.show().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn analyze_csv_table(&self, table: &str) -> Result<()>;
+
+    /// Read the `key,value` preamble of the first CSV file at `path`
+    ///
+    /// `options` must set [`CsvFormatOptions::with_preamble_rows`]. Tables
+    /// registered with the same options also carry the pairs as schema
+    /// metadata.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use datafusion::prelude::*;
+    /// use datafusion_csv::{CsvFormatOptions, SessionContextCsvExt};
+    ///
+    /// # async fn example() -> datafusion_common::Result<()> {
+    /// let ctx = SessionContext::new();
+    /// let options = CsvFormatOptions::new().with_preamble_rows(3);
+    /// let preamble = ctx.csv_preamble("runs/run-42.csv", options).await?;
+    /// println!("instrument: {:?}", preamble.get("Instrument"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn csv_preamble(
+        &self,
+        path: &str,
+        options: CsvFormatOptions,
+    ) -> Result<std::collections::HashMap<String, String>>;
 }
 
 impl SessionContextCsvExt for SessionContext {
@@ -168,7 +558,45 @@ impl SessionContextCsvExt for SessionContext {
         path: &str,
         options: CsvFormatOptions,
     ) -> Result<()> {
+        let rejects = options.dead_letter.clone().filter(DeadLetterSink::is_in_memory);
         let table = file_source::create_csv_table_provider(&self.state(), path, options).await?;
+        register_with_rejects(self, name, table, rejects)
+    }
+
+    async fn register_csv_with_schema(
+        &self,
+        name: &str,
+        path: &str,
+        schema: arrow_schema::SchemaRef,
+        options: CsvFormatOptions,
+    ) -> Result<()> {
+        let rejects = options.dead_letter.clone().filter(DeadLetterSink::is_in_memory);
+        let table = CsvSourceBuilder::new(path)
+            .with_options(options)
+            .with_schema(schema)
+            .build(&self.state())
+            .await?;
+        register_with_rejects(self, name, table, rejects)
+    }
+
+    async fn register_csv_s3(&self, name: &str, path: &str, config: S3Config) -> Result<()> {
+        cloud::register_s3_object_store(&self.state(), path, &config)?;
+        let table = file_source::create_csv_table_provider(&self.state(), path, CsvFormatOptions::default()).await?;
+        self.register_table(name, table)?;
+        Ok(())
+    }
+
+    async fn register_csv_with_store(
+        &self,
+        name: &str,
+        path: &str,
+        store: Arc<dyn object_store::ObjectStore>,
+    ) -> Result<()> {
+        let store_url = datafusion::datasource::listing::ListingTableUrl::parse(path)?.object_store();
+        let table = CsvSourceBuilder::new(path)
+            .with_object_store(store_url.as_str(), store)
+            .build(&self.state())
+            .await?;
         self.register_table(name, table)?;
         Ok(())
     }
@@ -186,6 +614,90 @@ impl SessionContextCsvExt for SessionContext {
         let table = file_source::create_csv_table_provider(&self.state(), path, options).await?;
         self.read_table(table)
     }
+
+    async fn read_csv_files(&self, paths: &[&str]) -> Result<DataFrame> {
+        let options = CsvFormatOptions::default();
+        let table = file_source::create_unioned_csv_table_provider(&self.state(), paths, options).await?;
+        self.read_table(table)
+    }
+
+    async fn read_csv_str(&self, data: &str) -> Result<DataFrame> {
+        use object_store::memory::InMemory;
+        use object_store::{path::Path, ObjectStore, PutPayload};
+
+        let store = Arc::new(InMemory::new());
+        store
+            .put(&Path::from("inline.csv"), PutPayload::from(data.to_string()))
+            .await
+            .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?;
+
+        let options = CsvFormatOptions::default();
+        let table = StandaloneCsvTable::try_new(store, "memory:///inline.csv", options).await?;
+        self.read_table(Arc::new(table))
+    }
+
+    async fn estimate_row_count(&self, path: &str) -> Result<u64> {
+        let state = self.state();
+        file_source::estimate_csv_row_count(&state, path, &CsvFormatOptions::default()).await
+    }
+
+    async fn csv_schema_fingerprint(&self, path: &str, options: CsvFormatOptions) -> Result<String> {
+        let state = self.state();
+        let table = file_source::create_csv_table_provider(&state, path, options).await?;
+        Ok(schema_fingerprint(&table.schema()))
+    }
+
+    async fn profile_csv(&self, path: &str, options: CsvFormatOptions) -> Result<arrow::record_batch::RecordBatch> {
+        diagnostics::profile_csv(&self.state(), path, options).await
+    }
+
+    async fn validate_csv(&self, path: &str, options: CsvFormatOptions) -> Result<Vec<CsvIssue>> {
+        diagnostics::validate_csv(&self.state(), path, options).await
+    }
+
+    async fn write_csv_schema_sidecar(&self, path: &str, options: CsvFormatOptions) -> Result<()> {
+        sidecar::write_sidecar(&self.state(), path, options).await
+    }
+
+    async fn delete_csv_rows(&self, table: &str, predicate: &str) -> Result<u64> {
+        dml::delete_rows(self, table, predicate).await
+    }
+
+    async fn update_csv_rows(
+        &self,
+        table: &str,
+        assignments: &[(&str, &str)],
+        predicate: Option<&str>,
+    ) -> Result<u64> {
+        dml::update_rows(self, table, assignments, predicate).await
+    }
+
+    async fn analyze_csv_table(&self, table: &str) -> Result<()> {
+        statistics::analyze_table(self, table).await
+    }
+
+    async fn csv_preamble(
+        &self,
+        path: &str,
+        options: CsvFormatOptions,
+    ) -> Result<std::collections::HashMap<String, String>> {
+        file_source::read_csv_preamble(&self.state(), path, &options).await
+    }
+}
+
+/// Register `table` as `name`, plus its `<name>__rejects` table for an in-memory sink
+fn register_with_rejects(
+    ctx: &SessionContext,
+    name: &str,
+    table: Arc<dyn datafusion::datasource::TableProvider>,
+    rejects: Option<DeadLetterSink>,
+) -> Result<()> {
+    ctx.register_table(name, table)?;
+    if let Some(sink) = rejects {
+        let rejects = dead_letter::RejectsTable::new(sink);
+        ctx.register_table(format!("{}__rejects", name), Arc::new(rejects))?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -241,6 +753,123 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_csv_directory() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        // A dot in the directory name must not be taken for a file extension
+        let events = temp_dir.path().join("events.v2");
+        std::fs::create_dir_all(events.join("2024-02")).unwrap();
+        std::fs::write(events.join("2024-01.csv"), "id,kind\n1,click\n2,view\n").unwrap();
+        std::fs::write(events.join("2024-02/part-0.csv"), "id,kind\n3,click\n").unwrap();
+        std::fs::write(events.join("README.md"), "not,csv\n").unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_csv_file("events", events.to_str().unwrap()).await?;
+
+        // Only the directory's own CSV files are read; subdirectories are not
+        // partitions of this table
+        let batches = ctx
+            .sql("SELECT kind, count(*) AS n FROM events GROUP BY kind ORDER BY kind")
+            .await?
+            .collect()
+            .await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches).unwrap().to_string();
+        let expected = [
+            "+-------+---+",
+            "| kind  | n |",
+            "+-------+---+",
+            "| click | 1 |",
+            "| view  | 1 |",
+            "+-------+---+",
+        ];
+        assert_eq!(formatted, expected.join("\n"));
+
+        // Unless the session lists them too
+        let config = SessionConfig::new().set_bool("datafusion.execution.listing_table_ignore_subdirectory", false);
+        let ctx = SessionContext::new_with_config(config);
+        ctx.register_csv_file("events", events.to_str().unwrap()).await?;
+        let df = ctx.sql("SELECT * FROM events").await?;
+        assert_eq!(df.count().await?, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_csv_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let jan_path = temp_dir.path().join("jan.csv");
+        let feb_path = temp_dir.path().join("feb.csv");
+        let other_path = temp_dir.path().join("other.csv");
+
+        let mut file = File::create(&jan_path).unwrap();
+        writeln!(file, "id,amount").unwrap();
+        writeln!(file, "1,100").unwrap();
+        writeln!(file, "2,200").unwrap();
+
+        let mut file = File::create(&feb_path).unwrap();
+        writeln!(file, "id,amount").unwrap();
+        writeln!(file, "3,2.5").unwrap();
+
+        let mut file = File::create(&other_path).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,Alice").unwrap();
+
+        let ctx = SessionContext::new();
+        let df = ctx
+            .read_csv_files(&[jan_path.to_str().unwrap(), feb_path.to_str().unwrap()])
+            .await?;
+        assert_eq!(
+            df.schema().field_with_unqualified_name("amount")?.data_type(),
+            &arrow_schema::DataType::Float64
+        );
+        let batches = df.collect().await?;
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 3);
+
+        let err = ctx
+            .read_csv_files(&[jan_path.to_str().unwrap(), other_path.to_str().unwrap()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("different columns"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_csv_str() -> Result<()> {
+        let ctx = SessionContext::new();
+        let df = ctx.read_csv_str("a,b\n1,x\n3,y\n").await?;
+        assert_eq!(
+            df.schema().field_with_unqualified_name("a")?.data_type(),
+            &arrow_schema::DataType::Int64
+        );
+
+        let batches = df.filter(col("a").gt(lit(1)))?.collect().await?;
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_line_endings() -> Result<()> {
+        let ctx = SessionContext::new();
+        for data in [
+            "id,name\r1,Alice\r2,Bob\r3,Carl\r",
+            "id,name\r\n1,Alice\n2,\"B\r\nob\"\r\n3,Carl",
+            "id,name\n1,Alice\r2,Bob\r\n3,Carl\n",
+        ] {
+            let batches = ctx.read_csv_str(data).await?.collect().await?;
+            let batch = &batches[0];
+            assert_eq!(batch.num_columns(), 2, "{:?}", data);
+            assert_eq!(batch.num_rows(), 3, "{:?}", data);
+            let names = batch.column(1).as_any().downcast_ref::<arrow_array::StringArray>().unwrap();
+            assert_eq!(names.value(2), "Carl");
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_custom_delimiter() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
@@ -265,6 +894,37 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_csv_with_schema() -> Result<()> {
+        use arrow_schema::{DataType, Field, Schema};
+
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("addresses.csv");
+        std::fs::write(&csv_path, "id,zip\n1,02134\n2,10001\n").unwrap();
+
+        // Inference would read the zip codes as integers and drop the leading zero
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("zip", DataType::Utf8, true),
+        ]));
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_schema(
+            "addresses",
+            csv_path.to_str().unwrap(),
+            schema.clone(),
+            CsvFormatOptions::new(),
+        )
+        .await?;
+
+        let df = ctx.sql("SELECT id, zip FROM addresses ORDER BY id").await?;
+        assert_eq!(df.schema().field(0).data_type(), &DataType::Int32);
+        let batches = df.collect().await?;
+        let zips = batches[0].column(1).as_any().downcast_ref::<arrow_array::StringArray>().unwrap();
+        assert_eq!(zips.value(0), "02134");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_custom_options() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
@@ -288,4 +948,511 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_estimate_row_count() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["a.csv", "b.csv"] {
+            let mut file = File::create(temp_dir.path().join(name)).unwrap();
+            writeln!(file, "id,name").unwrap();
+            writeln!(file, "1,Alice").unwrap();
+            writeln!(file, "2,Bob").unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+        assert_eq!(ctx.estimate_row_count(&path).await?, 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_csv_schema_fingerprint() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("users.csv");
+        let path = csv_path.to_str().unwrap();
+        let ctx = SessionContext::new();
+
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,Alice").unwrap();
+        let original = ctx.csv_schema_fingerprint(path, CsvFormatOptions::default()).await?;
+        // Pinned so that accidental changes to the hash are caught
+        assert_eq!(original, "4268fcf568d85fdb");
+
+        // New rows with the same shape keep the fingerprint
+        writeln!(file, "2,Bob").unwrap();
+        let unchanged = ctx.csv_schema_fingerprint(path, CsvFormatOptions::default()).await?;
+        assert_eq!(original, unchanged);
+
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1.5,Alice").unwrap();
+        let retyped = ctx.csv_schema_fingerprint(path, CsvFormatOptions::default()).await?;
+        assert_ne!(original, retyped);
+
+        // Types are spelled by the crate, not by arrow's formatting
+        use arrow_schema::{DataType, Field, Schema, TimeUnit};
+        let schema = Schema::new(vec![
+            Field::new("at", DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), true),
+            Field::new("price", DataType::Decimal128(10, 2), true),
+            Field::new("size", Categories::data_type(), true),
+        ]);
+        assert_eq!(schema_fingerprint(&schema), "2c360d6188575ac4");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_union_schema() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut file = File::create(temp_dir.path().join("2024-01.csv")).unwrap();
+        writeln!(file, "id,amount").unwrap();
+        writeln!(file, "1,100").unwrap();
+
+        // A later export adds a column and reorders the existing ones
+        let mut file = File::create(temp_dir.path().join("2024-02.csv")).unwrap();
+        writeln!(file, "region,id,amount").unwrap();
+        writeln!(file, "EU,2,2.5").unwrap();
+
+        let ctx = SessionContext::new();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_union_schema(true);
+        ctx.register_csv_with_options("sales", &path, options).await?;
+
+        let df = ctx.sql("SELECT id, amount, region FROM sales ORDER BY id").await?;
+        assert_eq!(
+            df.schema().field_with_unqualified_name("amount")?.data_type(),
+            &arrow_schema::DataType::Float64
+        );
+        let batches = df.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| 1  | 100.0  |        |"));
+        assert!(formatted.contains("| 2  | 2.5    | EU     |"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_columns_matched_by_header() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "id,city,zip\n1,Oslo,4790\n").unwrap();
+        // Reordered, and missing the zip column
+        std::fs::write(temp_dir.path().join("b.csv"), "city,id\nLima,2\n").unwrap();
+
+        let ctx = SessionContext::new();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+        ctx.register_csv_file("cities", &path).await?;
+
+        let batches = ctx.sql("SELECT id, city, zip FROM cities ORDER BY id").await?.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| 1  | Oslo | 4790 |"), "{}", formatted);
+        assert!(formatted.contains("| 2  | Lima |      |"), "{}", formatted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_analyze_csv_table() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "id,city\n3,Oslo\n,Rome\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.csv"), "id,city\n7,Lima\n").unwrap();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let ctx = SessionContext::new();
+        ctx.register_csv_file("cities", &path).await?;
+        ctx.analyze_csv_table("cities").await?;
+
+        // Exact statistics answer COUNT(*), MIN and MAX without a scan
+        let df = ctx.sql("SELECT count(*), min(id), max(id) FROM cities").await?;
+        let plan = df.clone().create_physical_plan().await?;
+        let displayed = datafusion::physical_plan::displayable(plan.as_ref()).indent(true).to_string();
+        assert!(!displayed.contains("CsvExec"), "{}", displayed);
+        let formatted = arrow::util::pretty::pretty_format_batches(&df.collect().await?)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| 3        | 3              | 7              |"), "{}", formatted);
+
+        let table = ctx.table_provider("cities").await?;
+        let scan = table.scan(&ctx.state(), None, &[], None).await?;
+        let statistics = scan.statistics()?;
+        assert_eq!(
+            statistics.column_statistics[0].null_count,
+            datafusion_common::stats::Precision::Exact(1)
+        );
+
+        assert!(ctx.analyze_csv_table("missing").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_estimated_statistics() -> Result<()> {
+        use datafusion::datasource::file_format::FileFormat;
+        use datafusion_common::stats::Precision;
+
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "id,city\n3,Oslo\n,Rome\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.csv"), "id,city\n7,Lima\n").unwrap();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        // Without an analyze pass, row counts are estimated from the files
+        let ctx = SessionContext::new();
+        ctx.register_csv_file("cities", &path).await?;
+        let table = ctx.table_provider("cities").await?;
+        let scan = table.scan(&ctx.state(), None, &[], None).await?;
+        let statistics = scan.statistics()?;
+        assert_eq!(statistics.num_rows, Precision::Inexact(3));
+        assert_eq!(statistics.column_statistics[0].null_count, Precision::Absent);
+
+        // Compressed files are not sampled
+        let options = CsvFormatOptions::new().with_compression(CsvCompression::Gzip);
+        let format = file_format::CsvFormat::new(options);
+        let store: Arc<dyn object_store::ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let meta = object_store::ObjectMeta {
+            location: "a.csv.gz".into(),
+            last_modified: chrono::Utc::now(),
+            size: 1024,
+            e_tag: None,
+            version: None,
+        };
+        let statistics = format.infer_stats(&ctx.state(), &store, table.schema(), &meta).await?;
+        assert_eq!(statistics.num_rows, Precision::Absent);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_two_pass() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut file = File::create(temp_dir.path().join("a.csv")).unwrap();
+        writeln!(file, "id,code").unwrap();
+        for i in 0..2000 {
+            writeln!(file, "{},{}", i, i).unwrap();
+        }
+        writeln!(file, "2000,X1").unwrap();
+
+        let mut file = File::create(temp_dir.path().join("b.csv")).unwrap();
+        writeln!(file, "id,code").unwrap();
+        writeln!(file, "2000.5,7").unwrap();
+
+        let ctx = SessionContext::new();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+        let options = CsvFormatOptions::new().with_two_pass(true);
+        ctx.register_csv_with_options("t", &path, options).await?;
+
+        let df = ctx.sql("SELECT id, code FROM t WHERE id >= 2000 ORDER BY id").await?;
+        let schema = df.schema().clone();
+        assert_eq!(
+            schema.field_with_unqualified_name("id")?.data_type(),
+            &arrow_schema::DataType::Float64
+        );
+        assert_eq!(
+            schema.field_with_unqualified_name("code")?.data_type(),
+            &arrow_schema::DataType::Utf8
+        );
+        let batches = df.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| 2000.0 | X1   |"), "{}", formatted);
+        assert!(formatted.contains("| 2000.5 | 7    |"), "{}", formatted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_categorical_columns() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let mut file = File::create(temp_dir.path().join("a.csv")).unwrap();
+        writeln!(file, "id,status").unwrap();
+        writeln!(file, "1,closed").unwrap();
+        writeln!(file, "2,open").unwrap();
+        let mut file = File::create(temp_dir.path().join("b.csv")).unwrap();
+        writeln!(file, "id,status").unwrap();
+        writeln!(file, "3,pending").unwrap();
+        writeln!(file, "4,open").unwrap();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_categorical(
+            "status",
+            ["open", "closed"],
+            UnseenCategoryPolicy::Null,
+        );
+        ctx.register_csv_with_options("tickets", &path, options).await?;
+
+        let batches = ctx.sql("SELECT status FROM tickets").await?.collect().await?;
+        for batch in &batches {
+            assert_eq!(batch.schema().field(0).data_type(), &Categories::data_type());
+            let dictionary = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<arrow_array::DictionaryArray<arrow_array::types::Int32Type>>()
+                .unwrap();
+            assert_eq!(dictionary.values().len(), 2);
+        }
+
+        let batches = ctx
+            .sql("SELECT status, count(*) AS n FROM tickets GROUP BY status ORDER BY status")
+            .await?
+            .collect()
+            .await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| closed | 1 |"), "{}", formatted);
+        assert!(formatted.contains("| open   | 2 |"), "{}", formatted);
+        assert!(formatted.contains("|        | 1 |"), "{}", formatted);
+
+        let options = CsvFormatOptions::new().with_categorical(
+            "status",
+            ["open", "closed"],
+            UnseenCategoryPolicy::Error,
+        );
+        ctx.register_csv_with_options("strict", &path, options).await?;
+        let err = ctx.sql("SELECT * FROM strict").await?.collect().await.unwrap_err();
+        assert!(err.to_string().contains("field 2 ('status')"), "{}", err);
+        assert!(err.to_string().contains("'pending' is not one of the column's categories"), "{}", err);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_rejects_table() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("scores.csv");
+        std::fs::write(&csv_path, b"id,score\n1,10\n2,abc\n3,30,extra\n4,\xff\n5,50\n").unwrap();
+
+        let ctx = SessionContext::new();
+        let sink = DeadLetterSink::in_memory();
+        let options = CsvFormatOptions::new()
+            .with_schema_infer_max_rec(Some(1))
+            .with_on_error(ParseErrorPolicy::SkipRow)
+            .with_dead_letter(sink.clone());
+        ctx.register_csv_with_options("scores", csv_path.to_str().unwrap(), options).await?;
+
+        let batches = ctx.sql("SELECT sum(score) FROM scores").await?.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches).unwrap().to_string();
+        assert!(formatted.contains("| 60 "), "{}", formatted);
+
+        let rows = sink.rows();
+        assert_eq!(rows.iter().map(|r| r.line).collect::<Vec<_>>(), vec![Some(3), Some(4), Some(5)]);
+        assert_eq!(rows[1].raw, "3,30,extra");
+        assert!(rows[0].error.contains("'abc' is not a valid Int64"), "{}", rows[0].error);
+
+        let batches = ctx
+            .sql("SELECT line, raw FROM scores__rejects ORDER BY line")
+            .await?
+            .collect()
+            .await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches).unwrap().to_string();
+        let expected = [
+            "+------+------------+",
+            "| line | raw        |",
+            "+------+------------+",
+            "| 3    | 2,abc      |",
+            "| 4    | 3,30,extra |",
+            "| 5    | 4,\u{fffd}        |",
+            "+------+------------+",
+        ];
+        assert_eq!(formatted, expected.join("\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_errors() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.csv"), "id,score\n1,10\n2,abc\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.csv"), "id,score\n3,30\n4,40,extra\n").unwrap();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        // The limit counts bad rows across both files
+        let scan = |max_errors| {
+            let path = path.clone();
+            async move {
+                let ctx = SessionContext::new();
+                let options = CsvFormatOptions::new()
+                    .with_schema_infer_max_rec(Some(1))
+                    .with_on_error(ParseErrorPolicy::SkipRow)
+                    .with_max_errors(max_errors);
+                ctx.read_csv_with_options(&path, options).await?.count().await
+            }
+        };
+        assert_eq!(scan(2).await?, 2);
+        let err = scan(1).await.unwrap_err();
+        assert!(err.to_string().contains("Scan aborted after 2 bad rows (max_errors is 1)"), "{}", err);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_preamble() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("run.csv");
+        std::fs::write(
+            &csv_path,
+            "Instrument,XRF-200\nOperator, J. Doe \nCalibrated\nsample,ppm\nA1,12.5\nA2,9\n",
+        )
+        .unwrap();
+        let path = csv_path.to_str().unwrap();
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_preamble_rows(3);
+        let preamble = ctx.csv_preamble(path, options.clone()).await?;
+        assert_eq!(preamble.get("Instrument").map(String::as_str), Some("XRF-200"));
+        assert_eq!(preamble.get("Operator").map(String::as_str), Some("J. Doe"));
+        assert_eq!(preamble.get("Calibrated").map(String::as_str), Some(""));
+
+        ctx.register_csv_with_options("run", path, options).await?;
+        let schema = ctx.table_provider("run").await?.schema();
+        assert_eq!(schema.metadata().get("Instrument").map(String::as_str), Some("XRF-200"));
+        assert_eq!(schema.field(1).data_type(), &arrow_schema::DataType::Float64);
+        let batches = ctx.sql("SELECT sum(ppm) AS total FROM run").await?.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches).unwrap().to_string();
+        assert!(formatted.contains("| 21.5  |"), "{}", formatted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_schema_drift_check() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let path = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        let mut file = File::create(temp_dir.path().join("a.csv")).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,Alice").unwrap();
+
+        let mut file = File::create(temp_dir.path().join("b.csv")).unwrap();
+        writeln!(file, "name,id").unwrap();
+        writeln!(file, "Bob,2").unwrap();
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_schema_drift_check(true);
+        ctx.register_csv_with_options("people", &path, options.clone()).await?;
+        let err = ctx.sql("SELECT * FROM people").await?.collect().await.unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Schema drift in"), "{}", message);
+        assert!(message.contains("b.csv"), "{}", message);
+        assert!(message.contains("expected [id, name], found [name, id]"), "{}", message);
+
+        let mut file = File::create(temp_dir.path().join("b.csv")).unwrap();
+        writeln!(file, "id,full_name").unwrap();
+        writeln!(file, "2,Bob").unwrap();
+
+        ctx.register_csv_with_options("renamed", &path, options).await?;
+        let err = ctx.sql("SELECT * FROM renamed").await?.collect().await.unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("unexpected columns [full_name]; missing columns [name]"),
+            "{}",
+            message
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_string_length() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("test.csv");
+
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,notes").unwrap();
+        writeln!(file, "1,short").unwrap();
+        writeln!(file, "2,a much longer note").unwrap();
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_max_string_length("notes", 6);
+        ctx.register_csv_with_options("truncated", csv_path.to_str().unwrap(), options)
+            .await?;
+
+        let batches = ctx
+            .sql("SELECT max(length(notes)) AS len FROM truncated")
+            .await?
+            .collect()
+            .await?;
+        let len = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Int32Array>()
+            .unwrap()
+            .value(0);
+        assert_eq!(len, 6);
+
+        let options = CsvFormatOptions::new()
+            .with_max_string_length("notes", 6)
+            .with_string_overflow_policy(StringOverflowPolicy::Error);
+        ctx.register_csv_with_options("strict", csv_path.to_str().unwrap(), options)
+            .await?;
+        let result = ctx.sql("SELECT * FROM strict").await?.collect().await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_scan_memory() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("test.csv");
+
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,name").unwrap();
+        for i in 0..100 {
+            writeln!(file, "{},name{}", i, i).unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        let options = CsvFormatOptions::new().with_max_scan_memory(Some(64));
+        ctx.register_csv_with_options("limited", csv_path.to_str().unwrap(), options)
+            .await?;
+        let err = ctx
+            .sql("SELECT * FROM limited")
+            .await?
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("per-scan memory limit"), "{}", err);
+
+        let options = CsvFormatOptions::new().with_max_scan_memory(Some(1024 * 1024));
+        ctx.register_csv_with_options("roomy", csv_path.to_str().unwrap(), options)
+            .await?;
+        let batches = ctx.sql("SELECT * FROM roomy").await?.collect().await?;
+        assert_eq!(batches[0].num_rows(), 100);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_csv_with_store() -> Result<()> {
+        use object_store::{memory::InMemory, path::Path, ObjectStore};
+
+        let store = Arc::new(InMemory::new());
+        store
+            .put(&Path::from("orders.csv"), "id,total\n1,9.5\n2,3.0\n".into())
+            .await
+            .unwrap();
+
+        // No S3 credentials or `s3` feature are needed for an injected store
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_store("orders", "s3://mock/orders.csv", store).await?;
+        let batches = ctx.sql("SELECT sum(total) FROM orders").await?.collect().await?;
+        let total = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Float64Array>()
+            .unwrap()
+            .value(0);
+        assert_eq!(total, 12.5);
+
+        Ok(())
+    }
 }
@@ -4,7 +4,29 @@
 //! object store backends (local filesystem, S3, cloud storage, etc.)
 //! through DataFusion's object store abstraction.
 
-use datafusion_common::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use datafusion::execution::object_store::ObjectStoreUrl;
+use datafusion::execution::runtime_env::RuntimeEnv;
+use datafusion_common::{DataFusionError, Result};
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use object_store::limit::LimitStore;
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore, PutMultipartOpts,
+    PutOptions, PutPayload, PutResult,
+};
+
+use crate::error::CsvError;
+use crate::file_format::{CsvCompression, CsvFormatOptions};
+use crate::physical_exec;
 
 /// Metadata about a CSV file in an object store
 #[derive(Debug, Clone)]
@@ -13,8 +35,10 @@ pub struct CsvFileMetadata {
     pub location: String,
     /// File size in bytes
     pub size: usize,
-    /// Last modified timestamp (if available)
+    /// Last modified timestamp in milliseconds since the Unix epoch (if available)
     pub last_modified: Option<i64>,
+    /// Entity tag reported by the store (if available)
+    pub e_tag: Option<String>,
 }
 
 impl CsvFileMetadata {
@@ -24,6 +48,7 @@ impl CsvFileMetadata {
             location: location.into(),
             size,
             last_modified: None,
+            e_tag: None,
         }
     }
 
@@ -33,13 +58,536 @@ impl CsvFileMetadata {
         self
     }
 
+    /// Set entity tag
+    pub fn with_e_tag(mut self, e_tag: impl Into<String>) -> Self {
+        self.e_tag = Some(e_tag.into());
+        self
+    }
+
     /// Check if file is empty
     pub fn is_empty(&self) -> bool {
         self.size == 0
     }
 }
 
+impl From<&ObjectMeta> for CsvFileMetadata {
+    fn from(meta: &ObjectMeta) -> Self {
+        Self {
+            location: meta.location.to_string(),
+            size: meta.size,
+            last_modified: Some(meta.last_modified.timestamp_millis()),
+            e_tag: meta.e_tag.clone(),
+        }
+    }
+}
+
+/// List the CSV files under `prefix` that a table with `options` would scan
+///
+/// Files are matched recursively on the configured extension (including any
+/// compression suffix) and returned sorted by location.
+pub async fn list_csv_files(
+    store: &Arc<dyn ObjectStore>,
+    prefix: &Path,
+    options: &CsvFormatOptions,
+) -> Result<Vec<CsvFileMetadata>> {
+    let extension = options.file_extension_with_compression();
+
+    let mut files: Vec<CsvFileMetadata> = store
+        .list(Some(prefix))
+        .map_err(|e| DataFusionError::from(CsvError::Store(e)))
+        .try_filter(|meta| futures::future::ready(meta.location.as_ref().ends_with(&extension)))
+        .map_ok(|meta| CsvFileMetadata::from(&meta))
+        .try_collect()
+        .await?;
+
+    files.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(files)
+}
+
+/// Bytes read from each sampled chunk when estimating row counts
+const ESTIMATE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Number of chunks sampled when estimating row counts
+const ESTIMATE_CHUNKS: usize = 4;
+
+/// Estimate the number of records in a CSV file without reading all of it
+///
+/// A few chunks spread across the file are read to measure the average record
+/// size, which is extrapolated from the file size. Files no larger than the
+/// sampled chunks are counted exactly. Quoted values spanning lines inflate
+/// the estimate. Compressed files cannot be estimated.
+pub async fn estimate_row_count(
+    store: &Arc<dyn ObjectStore>,
+    location: &Path,
+    options: &CsvFormatOptions,
+) -> Result<u64> {
+    let meta = store
+        .head(location)
+        .await
+        .map_err(CsvError::Store)?;
+    estimate_object_row_count(store, &meta, options).await
+}
+
+/// [`estimate_row_count`] for a file whose metadata is already known
+pub(crate) async fn estimate_object_row_count(
+    store: &Arc<dyn ObjectStore>,
+    meta: &ObjectMeta,
+    options: &CsvFormatOptions,
+) -> Result<u64> {
+    if options.compression != CsvCompression::Uncompressed {
+        return Err(DataFusionError::Plan(format!(
+            "Cannot estimate the row count of compressed file '{}'",
+            meta.location
+        )));
+    }
+
+    let size = meta.size;
+    if size <= ESTIMATE_CHUNK_SIZE * ESTIMATE_CHUNKS {
+        let bytes = store
+            .get(&meta.location)
+            .await
+            .map_err(CsvError::Store)?
+            .bytes()
+            .await
+            .map_err(CsvError::Store)?;
+        let input = physical_exec::prepare_input(&bytes, options);
+        let count = physical_exec::reader_builder(options)
+            .flexible(true)
+            .from_reader(input.as_ref())
+            .into_byte_records()
+            .count();
+        return Ok((count as u64).saturating_sub(options.skip_rows_after_header as u64));
+    }
+
+    // Evenly spaced chunks, the first at the start of the file and the last at its end
+    let ranges: Vec<Range<usize>> = (0..ESTIMATE_CHUNKS)
+        .map(|i| {
+            let start = (size - ESTIMATE_CHUNK_SIZE) * i / (ESTIMATE_CHUNKS - 1);
+            start..start + ESTIMATE_CHUNK_SIZE
+        })
+        .collect();
+    let chunks = store
+        .get_ranges(&meta.location, &ranges)
+        .await
+        .map_err(CsvError::Store)?;
+
+    // Preamble, header and skipped rows come before the first record
+    let terminator = sampled_terminator(&chunks[0], options);
+    let leading =
+        options.preamble_rows + usize::from(options.has_header) + options.skip_rows_after_header;
+    let mut header_len = 0;
+    let mut records = 0;
+    let mut spanned = 0;
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut newlines: Vec<usize> = chunk
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == terminator)
+            .map(|(pos, _)| pos)
+            .collect();
+        if i == 0 && leading > 0 {
+            if newlines.len() < leading {
+                // The leading lines fill the whole chunk
+                header_len = chunk.len();
+                newlines.clear();
+            } else {
+                header_len = newlines[leading - 1] + 1;
+                newlines.drain(..leading - 1);
+            }
+        } else if i == 0 {
+            // The file starts on a record boundary; count from there
+            records += usize::from(!newlines.is_empty());
+            spanned += newlines.first().map_or(0, |pos| pos + 1);
+        }
+        if newlines.len() > 1 {
+            let first = newlines.remove(0);
+            records += newlines.len();
+            spanned += newlines[newlines.len() - 1] - first;
+        }
+    }
+
+    if records == 0 {
+        // Every sampled record is longer than a chunk
+        return Ok((size / ESTIMATE_CHUNK_SIZE) as u64);
+    }
+    let average = spanned as f64 / records as f64;
+    Ok(((size - header_len) as f64 / average).round() as u64)
+}
+
+/// Byte ending the records of a file sampled by [`estimate_object_row_count`]
+///
+/// Without a configured terminator records end at `\n`, `\r\n` or a bare
+/// `\r`; a file whose first chunk has no `\n` is taken to use bare `\r`.
+fn sampled_terminator(first_chunk: &[u8], options: &CsvFormatOptions) -> u8 {
+    match options.terminator {
+        Some(terminator) => terminator,
+        None if !first_chunk.contains(&b'\n') && first_chunk.contains(&b'\r') => b'\r',
+        None => b'\n',
+    }
+}
+
+/// Object store wrapper caching LIST results for a set of prefixes
+///
+/// Listings of each prefix are served from memory until its TTL elapses. Any
+/// write, delete, copy or rename under a prefix drops its cached listing so
+/// that `INSERT`s through this store are visible immediately. Requests may
+/// also be capped with a [`LimitStore`]; all other calls are passed through
+/// to the inner store unchanged.
+pub(crate) struct CachedListStore {
+    /// Store as registered before it was wrapped
+    base: Arc<dyn ObjectStore>,
+    /// `base`, behind a `LimitStore` when requests are capped
+    inner: Arc<dyn ObjectStore>,
+    max_requests: Option<usize>,
+    listings: Arc<Mutex<HashMap<Path, CachedListing>>>,
+}
+
+/// TTL of a cached prefix and its listing, once listed
+#[derive(Debug)]
+struct CachedListing {
+    ttl: Duration,
+    listed: Option<(Instant, Arc<Vec<ObjectMeta>>)>,
+}
+
+impl CachedListStore {
+    /// Wrap `base`, sharing `listings` with any earlier wrapper of it
+    fn new(
+        base: Arc<dyn ObjectStore>,
+        listings: Arc<Mutex<HashMap<Path, CachedListing>>>,
+        max_requests: Option<usize>,
+    ) -> Self {
+        let inner: Arc<dyn ObjectStore> = match max_requests {
+            Some(max_requests) => Arc::new(LimitStore::new(base.clone(), max_requests.max(1))),
+            None => base.clone(),
+        };
+        Self {
+            base,
+            inner,
+            max_requests,
+            listings,
+        }
+    }
+
+    /// Cache listings of `prefix` for `ttl`, replacing any earlier TTL for it
+    fn cache_prefix(&self, prefix: Path, ttl: Duration) {
+        let mut listings = self.listings.lock().unwrap();
+        listings.insert(prefix, CachedListing { ttl, listed: None });
+    }
+
+    /// Return the cached listing of `prefix` if it is still fresh
+    fn fresh_listing(&self, prefix: &Path) -> Option<Arc<Vec<ObjectMeta>>> {
+        let listings = self.listings.lock().unwrap();
+        let listing = listings.get(prefix)?;
+        listing
+            .listed
+            .as_ref()
+            .filter(|(listed_at, _)| listed_at.elapsed() < listing.ttl)
+            .map(|(_, files)| files.clone())
+    }
+
+    /// Drop the cached listings of the prefixes `location` falls under
+    fn invalidate(&self, location: &Path) {
+        for (prefix, listing) in self.listings.lock().unwrap().iter_mut() {
+            if location.prefix_matches(prefix) {
+                listing.listed = None;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CachedListStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let listings = self.listings.lock().unwrap();
+        let ttls: HashMap<&Path, Duration> = listings.iter().map(|(prefix, l)| (prefix, l.ttl)).collect();
+        f.debug_struct("CachedListStore")
+            .field("inner", &self.inner)
+            .field("ttls", &ttls)
+            .finish()
+    }
+}
+
+impl fmt::Display for CachedListStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let listings = self.listings.lock().unwrap();
+        let mut prefixes: Vec<&str> = listings.keys().map(Path::as_ref).collect();
+        prefixes.sort_unstable();
+        write!(f, "CachedListStore({}, prefixes=[{}])", self.inner, prefixes.join(", "))
+    }
+}
+
+/// Location a [`CachedListStore`] answers rangeless reads of with a [`WrapperState`]
+///
+/// `ObjectStore` has no `as_any`, so this is how a registered store is
+/// recognised as one of ours. Other stores fail the read without a request.
+const WRAPPER_PROBE: &str = "__datafusion_csv_wrapper__";
+
+/// State of a registered [`CachedListStore`], returned as the probe's error
+#[derive(Debug)]
+struct WrapperState {
+    base: Arc<dyn ObjectStore>,
+    max_requests: Option<usize>,
+    listings: Arc<Mutex<HashMap<Path, CachedListing>>>,
+}
+
+impl fmt::Display for WrapperState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "datafusion-csv store wrapper over {}", self.base)
+    }
+}
+
+impl std::error::Error for WrapperState {}
+
+/// The state of `store` if it is a [`CachedListStore`]
+async fn wrapper_state(store: &Arc<dyn ObjectStore>) -> Option<WrapperState> {
+    match store.get_ranges(&Path::from(WRAPPER_PROBE), &[]).await {
+        Err(object_store::Error::Generic { source, .. }) => {
+            source.downcast::<WrapperState>().ok().map(|state| *state)
+        }
+        _ => None,
+    }
+}
+
+/// Cache listings of a prefix and cap requests for the store registered under `url`
+///
+/// The runtime's store is wrapped once. A later table on the same store adds
+/// its prefix to the cached listings instead of wrapping the wrapper again;
+/// listings of other prefixes are not cached. A store's cap cannot be
+/// changed once set, so a table asking for a different one is rejected.
+pub(crate) async fn wrap_registered_store(
+    runtime: &RuntimeEnv,
+    url: &ObjectStoreUrl,
+    list_cache: Option<(Path, Duration)>,
+    max_requests: Option<usize>,
+) -> Result<()> {
+    let current = runtime.object_store(url)?;
+    let Some(state) = wrapper_state(&current).await else {
+        let store = CachedListStore::new(current, Arc::default(), max_requests);
+        if let Some((prefix, ttl)) = list_cache {
+            store.cache_prefix(prefix, ttl);
+        }
+        runtime.register_object_store(url.as_ref(), Arc::new(store));
+        return Ok(());
+    };
+
+    if let (Some(existing), Some(requested)) = (state.max_requests, max_requests)
+        && existing != requested
+    {
+        return Err(DataFusionError::Configuration(format!(
+            "Object store '{}' already caps concurrent requests at {}; \
+             a table on it cannot cap them at {}",
+            url, existing, requested
+        )));
+    }
+    if let Some((prefix, ttl)) = list_cache {
+        state.listings.lock().unwrap().insert(prefix, CachedListing { ttl, listed: None });
+    }
+    // Adding a cap to an uncapped store means rebuilding its wrapper
+    if state.max_requests.is_none() && max_requests.is_some() {
+        let store = CachedListStore::new(state.base, state.listings, max_requests);
+        runtime.register_object_store(url.as_ref(), Arc::new(store));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl ObjectStore for CachedListStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        let result = self.inner.put_opts(location, payload, opts).await;
+        self.invalidate(location);
+        result
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        self.invalidate(location);
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> object_store::Result<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> object_store::Result<Bytes> {
+        self.inner.get_range(location, range).await
+    }
+
+    async fn get_ranges(
+        &self,
+        location: &Path,
+        ranges: &[Range<usize>],
+    ) -> object_store::Result<Vec<Bytes>> {
+        if ranges.is_empty() && location.as_ref() == WRAPPER_PROBE {
+            return Err(object_store::Error::Generic {
+                store: "CachedListStore",
+                source: Box::new(WrapperState {
+                    base: self.base.clone(),
+                    max_requests: self.max_requests,
+                    listings: self.listings.clone(),
+                }),
+            });
+        }
+        self.inner.get_ranges(location, ranges).await
+    }
+
+    async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        let result = self.inner.delete(location).await;
+        self.invalidate(location);
+        result
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        let Some(prefix) = prefix.filter(|prefix| self.listings.lock().unwrap().contains_key(*prefix)) else {
+            return self.inner.list(prefix);
+        };
+
+        if let Some(files) = self.fresh_listing(prefix) {
+            return stream::iter(files.as_ref().clone().into_iter().map(Ok)).boxed();
+        }
+
+        let listing = self.inner.list(Some(prefix));
+        let prefix = prefix.clone();
+        stream::once(async move {
+            let files: Vec<ObjectMeta> = listing.try_collect().await?;
+            if let Some(listing) = self.listings.lock().unwrap().get_mut(&prefix) {
+                listing.listed = Some((Instant::now(), Arc::new(files.clone())));
+            }
+            Ok::<_, object_store::Error>(stream::iter(files.into_iter().map(Ok)))
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        let result = self.inner.copy(from, to).await;
+        self.invalidate(to);
+        result
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        let result = self.inner.rename(from, to).await;
+        self.invalidate(from);
+        self.invalidate(to);
+        result
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        let result = self.inner.copy_if_not_exists(from, to).await;
+        self.invalidate(to);
+        result
+    }
+
+    async fn rename_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        let result = self.inner.rename_if_not_exists(from, to).await;
+        self.invalidate(from);
+        self.invalidate(to);
+        result
+    }
+}
+
+/// Upper bound on the number of paths a templated path may expand into
+const MAX_TEMPLATE_PATHS: usize = 10_000;
+
+/// Expand `{start..end}` ranges in a path into one concrete path per value
+///
+/// Ranges are inclusive and may be dates (`{2024-01-01..2024-01-31}`, one per
+/// day) or integers (`{1..12}`, zero-padded when `start` is, as in `{01..12}`).
+/// Several ranges in one path expand to every combination. Paths without a
+/// range are returned unchanged.
+pub(crate) fn expand_path_template(path: &str) -> Result<Vec<String>> {
+    let Some((open, close)) = find_range(path) else {
+        return Ok(vec![path.to_string()]);
+    };
+
+    let values = expand_range(&path[open + 1..close])?;
+    let suffixes = expand_path_template(&path[close + 1..])?;
+    if values.len().saturating_mul(suffixes.len()) > MAX_TEMPLATE_PATHS {
+        return Err(DataFusionError::Plan(format!(
+            "Path template '{}' expands to more than {} paths",
+            path, MAX_TEMPLATE_PATHS
+        )));
+    }
+
+    let prefix = &path[..open];
+    Ok(values
+        .iter()
+        .flat_map(|value| suffixes.iter().map(move |suffix| format!("{}{}{}", prefix, value, suffix)))
+        .collect())
+}
+
+/// Locate the first `{..}` group containing a `..` range
+fn find_range(path: &str) -> Option<(usize, usize)> {
+    let mut search_from = 0;
+    while let Some(offset) = path[search_from..].find('{') {
+        let open = search_from + offset;
+        let close = open + path[open..].find('}')?;
+        if path[open + 1..close].contains("..") {
+            return Some((open, close));
+        }
+        search_from = open + 1;
+    }
+    None
+}
+
+/// Expand the inside of a `{start..end}` group into its values
+fn expand_range(range: &str) -> Result<Vec<String>> {
+    let invalid = || {
+        DataFusionError::Plan(format!(
+            "Invalid path range '{{{}}}': expected dates (YYYY-MM-DD) or integers with start <= end",
+            range
+        ))
+    };
+
+    let (start, end) = range.split_once("..").ok_or_else(invalid)?;
+    let (start, end) = (start.trim(), end.trim());
+
+    if let (Ok(first), Ok(last)) = (
+        chrono::NaiveDate::parse_from_str(start, "%Y-%m-%d"),
+        chrono::NaiveDate::parse_from_str(end, "%Y-%m-%d"),
+    ) {
+        if first > last {
+            return Err(invalid());
+        }
+        return Ok(first
+            .iter_days()
+            .take_while(|day| *day <= last)
+            .take(MAX_TEMPLATE_PATHS + 1)
+            .map(|day| day.format("%Y-%m-%d").to_string())
+            .collect());
+    }
+
+    let (first, last) = match (start.parse::<u64>(), end.parse::<u64>()) {
+        (Ok(first), Ok(last)) if first <= last => (first, last),
+        _ => return Err(invalid()),
+    };
+    let width = if start.len() > 1 && start.starts_with('0') {
+        start.len()
+    } else {
+        0
+    };
+    Ok((first..=last)
+        .take(MAX_TEMPLATE_PATHS + 1)
+        .map(|value| format!("{:0width$}", value, width = width))
+        .collect())
+}
+
 /// Helper to construct object store URLs
+#[allow(dead_code)]
 pub fn make_url(scheme: &str, bucket: &str, path: &str) -> String {
     if scheme.is_empty() {
         // Local filesystem
@@ -51,6 +599,7 @@ pub fn make_url(scheme: &str, bucket: &str, path: &str) -> String {
 }
 
 /// Parse an object store URL into components
+#[allow(dead_code)]
 pub fn parse_url(url: &str) -> Result<(Option<String>, String)> {
     if url.contains("://") {
         let parts: Vec<&str> = url.splitn(2, "://").collect();
@@ -80,12 +629,183 @@ mod tests {
         assert_eq!(meta.location, "test.csv");
         assert_eq!(meta.size, 1024);
         assert_eq!(meta.last_modified, Some(1234567890));
+        assert_eq!(meta.e_tag, None);
         assert!(!meta.is_empty());
 
         let empty_meta = CsvFileMetadata::new("empty.csv", 0);
         assert!(empty_meta.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_list_csv_files() {
+        use object_store::memory::InMemory;
+
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        for (name, contents) in [
+            ("data/b.csv", "id\n1\n2\n"),
+            ("data/a.csv", "id\n1\n"),
+            ("data/readme.txt", "notes"),
+            ("other/c.csv", "id\n"),
+        ] {
+            store
+                .put(&Path::from(name), PutPayload::from_static(contents.as_bytes()))
+                .await
+                .unwrap();
+        }
+
+        let files = list_csv_files(&store, &Path::from("data"), &CsvFormatOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].location, "data/a.csv");
+        assert_eq!(files[0].size, 5);
+        assert_eq!(files[1].location, "data/b.csv");
+        assert!(files[1].last_modified.is_some());
+        assert!(files[1].e_tag.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_row_count() {
+        use object_store::memory::InMemory;
+
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let options = CsvFormatOptions::default();
+
+        // Small files are counted exactly, including quoted line breaks
+        let small = Path::from("small.csv");
+        store
+            .put(&small, PutPayload::from_static(b"id,note\n1,\"a\nb\"\n2,c\n"))
+            .await
+            .unwrap();
+        assert_eq!(estimate_row_count(&store, &small, &options).await.unwrap(), 2);
+
+        let mut contents = String::from("id,name,value\n");
+        for i in 0..100_000 {
+            contents.push_str(&format!("{},name_{},{}\n", i, i % 97, i * 3));
+        }
+        let large = Path::from("large.csv");
+        store.put(&large, PutPayload::from(contents)).await.unwrap();
+
+        let estimate = estimate_row_count(&store, &large, &options).await.unwrap();
+        assert!((90_000..110_000).contains(&estimate), "estimated {} rows", estimate);
+
+        let gzip = options.with_compression(CsvCompression::Gzip);
+        assert!(estimate_row_count(&store, &large, &gzip).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_row_count_line_layout() {
+        use object_store::memory::InMemory;
+
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let rows: Vec<String> = (0..20_000).map(|i| format!("{:05},{:08}", i, i * 7)).collect();
+        let estimate = |name: &'static str, contents: String, options: CsvFormatOptions| {
+            let store = store.clone();
+            async move {
+                let path = Path::from(name);
+                store.put(&path, PutPayload::from(contents)).await.unwrap();
+                estimate_row_count(&store, &path, &options).await.unwrap()
+            }
+        };
+
+        // Bare `\r` and custom terminators are sampled like `\n`
+        let contents = format!("id,value\r{}\r", rows.join("\r"));
+        let cr_only = estimate("cr.csv", contents, CsvFormatOptions::default()).await;
+        assert_eq!(cr_only, 20_000);
+        let contents = format!("id,value;{};", rows.join(";"));
+        let options = CsvFormatOptions::default().with_terminator(Some(b';'));
+        assert_eq!(estimate("semicolon.csv", contents, options).await, 20_000);
+
+        // Long preamble and skipped rows are not counted as records
+        let long_line = "x".repeat(10_000);
+        let contents = format!(
+            "Note,{long}\nNote,{long}\nid,value\nunits,{long}\n{}\n",
+            rows.join("\n"),
+            long = long_line
+        );
+        let options = CsvFormatOptions::default()
+            .with_preamble_rows(2)
+            .with_skip_rows_after_header(1);
+        assert_eq!(estimate("preamble.csv", contents, options).await, 20_000);
+    }
+
+    #[tokio::test]
+    async fn test_cached_list_store() {
+        use object_store::memory::InMemory;
+
+        let inner: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let prefix = Path::from("data");
+        inner
+            .put(&Path::from("data/a.csv"), PutPayload::from_static(b"id\n1\n"))
+            .await
+            .unwrap();
+
+        let cached = CachedListStore::new(inner.clone(), Arc::default(), None);
+        cached.cache_prefix(prefix.clone(), Duration::from_secs(60));
+        async fn count(store: &CachedListStore) -> usize {
+            let prefix = Path::from("data");
+            store.list(Some(&prefix)).try_collect::<Vec<_>>().await.unwrap().len()
+        }
+        assert_eq!(count(&cached).await, 1);
+
+        // Files added behind the wrapper's back are hidden until the TTL expires
+        inner
+            .put(&Path::from("data/b.csv"), PutPayload::from_static(b"id\n2\n"))
+            .await
+            .unwrap();
+        assert_eq!(count(&cached).await, 1);
+
+        // Writes through the wrapper invalidate the listing
+        cached
+            .put(&Path::from("data/c.csv"), PutPayload::from_static(b"id\n3\n"))
+            .await
+            .unwrap();
+        assert_eq!(count(&cached).await, 3);
+
+        let expiring = CachedListStore::new(inner.clone(), Arc::default(), None);
+        expiring.cache_prefix(prefix.clone(), Duration::ZERO);
+        assert_eq!(count(&expiring).await, 3);
+        inner.delete(&Path::from("data/a.csv")).await.unwrap();
+        assert_eq!(count(&expiring).await, 2);
+
+        // Only the cached prefixes are served from memory
+        inner
+            .put(&Path::from("other/a.csv"), PutPayload::from_static(b"id\n1\n"))
+            .await
+            .unwrap();
+        let other = Path::from("other");
+        let listed = cached.list(Some(&other)).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        inner
+            .put(&Path::from("other/b.csv"), PutPayload::from_static(b"id\n2\n"))
+            .await
+            .unwrap();
+        let listed = cached.list(Some(&other)).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(listed.len(), 2);
+    }
+
+    #[test]
+    fn test_expand_path_template() {
+        assert_eq!(
+            expand_path_template("s3://bucket/events/dt={2024-02-28..2024-03-01}/*.csv").unwrap(),
+            vec![
+                "s3://bucket/events/dt=2024-02-28/*.csv",
+                "s3://bucket/events/dt=2024-02-29/*.csv",
+                "s3://bucket/events/dt=2024-03-01/*.csv",
+            ]
+        );
+        assert_eq!(
+            expand_path_template("logs/{2023..2024}/{09..10}/").unwrap(),
+            vec!["logs/2023/09/", "logs/2023/10/", "logs/2024/09/", "logs/2024/10/"]
+        );
+        assert_eq!(expand_path_template("data/{a,b}.csv").unwrap(), vec!["data/{a,b}.csv"]);
+
+        assert!(expand_path_template("dt={2024-01-31..2024-01-01}/").is_err());
+        assert!(expand_path_template("dt={x..y}/").is_err());
+        assert!(expand_path_template("{0..99999}/{0..9}/").is_err());
+    }
+
     #[test]
     fn test_make_url() {
         assert_eq!(make_url("", "", "data/test.csv"), "data/test.csv");
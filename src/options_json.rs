@@ -0,0 +1,444 @@
+//! JSON encoding of [`CsvFormatOptions`] for plans sent to other processes
+//!
+//! Used by the `proto` codec and the Substrait glue to ship a table's
+//! options alongside a plan. Settings that only exist in this process, such
+//! as closures and dead-letter sinks, cannot be encoded.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use arrow_schema::DataType;
+use chrono::DateTime;
+use datafusion::error::{DataFusionError, Result};
+use serde_json::{json, Map, Value};
+
+use crate::cloud::HttpConfig;
+use crate::file_format::{
+    BinaryEncoding, ControlCharPolicy, CsvCompression, CsvFormatOptions, FileOrder, IntegerOverflowPolicy,
+    ParseErrorPolicy, StringOverflowPolicy, UnseenCategoryPolicy,
+};
+
+/// Render `options` as JSON, failing on settings that only exist in this process
+pub(crate) fn options_to_json(options: &CsvFormatOptions) -> Result<Value> {
+    let unsendable = |setting: &str| {
+        Err(DataFusionError::NotImplemented(format!(
+            "CSV scans with {} cannot be serialized",
+            setting
+        )))
+    };
+    if !options.column_parsers.is_empty() {
+        let mut columns: Vec<&str> = options.column_parsers.keys().map(String::as_str).collect();
+        columns.sort_unstable();
+        return unsendable(&format!("column parsers ({})", columns.join(", ")));
+    }
+    if options.row_filter.is_some() {
+        return unsendable("a row filter");
+    }
+    if options.dead_letter.is_some() {
+        return unsendable("a dead-letter sink");
+    }
+
+    let value_transforms: Map<String, Value> = options
+        .value_transforms
+        .iter()
+        .map(|(column, transforms)| {
+            let transforms = transforms
+                .iter()
+                .map(|t| json!({ "pattern": t.pattern.as_str(), "replacement": t.replacement }))
+                .collect();
+            (column.clone(), Value::Array(transforms))
+        })
+        .collect();
+    let categorical_columns: Map<String, Value> = options
+        .categorical_columns
+        .iter()
+        .map(|(column, categories)| {
+            let values: Vec<&str> = categories.values().collect();
+            let policy = format!("{:?}", categories.policy());
+            (column.clone(), json!({ "values": values, "policy": policy }))
+        })
+        .collect();
+    let binary_columns: Map<String, Value> = options
+        .binary_columns
+        .iter()
+        .map(|(column, binary)| {
+            let encoding = format!("{:?}", binary.encoding());
+            (column.clone(), json!({ "encoding": encoding, "byte_width": binary.byte_width() }))
+        })
+        .collect();
+    let partition_columns: Vec<Value> = options
+        .partition_columns
+        .iter()
+        .map(|(name, data_type)| json!({ "name": name, "type": data_type.to_string() }))
+        .collect();
+    let control_chars = match options.control_chars {
+        ControlCharPolicy::Replace(c) => json!({ "Replace": c.to_string() }),
+        policy => json!(format!("{:?}", policy)),
+    };
+
+    // Built key by key, as one `json!` over every setting exceeds the macro recursion limit
+    let mut object = Map::new();
+    object.insert("has_header".into(), json!(options.has_header));
+    object.insert("delimiter".into(), json!(options.delimiter));
+    object.insert("schema_infer_max_rec".into(), json!(options.schema_infer_max_rec));
+    object.insert("batch_size".into(), json!(options.batch_size));
+    object.insert("file_extension".into(), json!(options.file_extension));
+    object.insert("max_string_lengths".into(), json!(options.max_string_lengths));
+    object.insert("string_overflow_policy".into(), json!(format!("{:?}", options.string_overflow_policy)));
+    object.insert("integer_overflow_policy".into(), json!(format!("{:?}", options.integer_overflow_policy)));
+    object.insert("on_error".into(), json!(format!("{:?}", options.on_error)));
+    object.insert("max_errors".into(), json!(options.max_errors));
+    object.insert("header_type_hints".into(), json!(options.header_type_hints));
+    object.insert("skip_rows_after_header".into(), json!(options.skip_rows_after_header));
+    object.insert("compression".into(), json!(format!("{:?}", options.compression)));
+    object.insert("decimal_separator".into(), json!(options.decimal_separator));
+    object.insert("thousands_separator".into(), json!(options.thousands_separator));
+    object.insert("currency_symbols".into(), json!(options.currency_symbols));
+    object.insert("epoch_timestamps".into(), json!(options.epoch_timestamps));
+    object.insert("decimal_inference".into(), json!(options.decimal_inference));
+    object.insert("narrow_integers".into(), json!(options.narrow_integers));
+    object.insert("timestamp_format".into(), json!(options.timestamp_format));
+    object.insert("column_timestamp_formats".into(), json!(options.column_timestamp_formats));
+    object.insert("whitespace_delimited".into(), json!(options.whitespace_delimited));
+    object.insert("collapse_delimiters".into(), json!(options.collapse_delimiters));
+    object.insert("escape".into(), json!(options.escape));
+    object.insert("terminator".into(), json!(options.terminator));
+    object.insert("newlines_in_values".into(), json!(options.newlines_in_values));
+    object.insert("null_values".into(), json!(options.null_values));
+    object.insert("column_null_values".into(), json!(options.column_null_values));
+    object.insert("null_regex".into(), json!(options.null_regex.as_ref().map(|regex| regex.as_str())));
+    object.insert("true_values".into(), json!(options.true_values));
+    object.insert("false_values".into(), json!(options.false_values));
+    object.insert("value_transforms".into(), json!(value_transforms));
+    object.insert("categorical_columns".into(), json!(categorical_columns));
+    object.insert("binary_columns".into(), json!(binary_columns));
+    object.insert("control_chars".into(), json!(control_chars));
+    object.insert("max_fields".into(), json!(options.max_fields));
+    object.insert("max_scan_memory".into(), json!(options.max_scan_memory));
+    object.insert("list_cache_ttl".into(), json!(options.list_cache_ttl.map(duration_to_json)));
+    object.insert("partition_columns".into(), json!(partition_columns));
+    object.insert("union_schema".into(), json!(options.union_schema));
+    object.insert("schema_drift_check".into(), json!(options.schema_drift_check));
+    object.insert("type_promotion".into(), json!(options.type_promotion));
+    object.insert("two_pass".into(), json!(options.two_pass));
+    object.insert("schema_sidecar".into(), json!(options.schema_sidecar));
+    object.insert("coalesce_batches".into(), json!(options.coalesce_batches));
+    object.insert("max_concurrent_requests".into(), json!(options.max_concurrent_requests));
+    object.insert("http_config".into(), json!(options.http_config.as_ref().map(http_config_to_json)));
+    object.insert("prefetch_files".into(), json!(options.prefetch_files));
+    object.insert("streaming_reads".into(), json!(options.streaming_reads));
+    object.insert("file_modified_column".into(), json!(options.file_modified_column));
+    object.insert("byte_offset_column".into(), json!(options.byte_offset_column));
+    object.insert("line_number_column".into(), json!(options.line_number_column));
+    object.insert("modified_since".into(), json!(options.modified_since.map(|watermark| watermark.timestamp_micros())));
+    object.insert("file_order".into(), json!(options.file_order.map(|order| format!("{:?}", order))));
+    object.insert("max_rows".into(), json!(options.max_rows));
+    object.insert("preamble_rows".into(), json!(options.preamble_rows));
+    Ok(Value::Object(object))
+}
+
+/// Read options written by [`options_to_json`]
+pub(crate) fn options_from_json(json: &Value) -> Result<CsvFormatOptions> {
+    let json = Fields(json);
+    let mut options = CsvFormatOptions {
+        has_header: json.bool("has_header")?,
+        delimiter: json.byte("delimiter")?,
+        schema_infer_max_rec: json.optional("schema_infer_max_rec", as_usize)?,
+        batch_size: json.usize("batch_size")?,
+        file_extension: json.string("file_extension")?,
+        max_string_lengths: json.map("max_string_lengths", as_usize)?,
+        string_overflow_policy: json.parse("string_overflow_policy", |name| match name {
+            "Truncate" => Some(StringOverflowPolicy::Truncate),
+            "Error" => Some(StringOverflowPolicy::Error),
+            _ => None,
+        })?,
+        integer_overflow_policy: json.parse("integer_overflow_policy", |name| match name {
+            "Null" => Some(IntegerOverflowPolicy::Null),
+            "Float64" => Some(IntegerOverflowPolicy::Float64),
+            "Decimal128" => Some(IntegerOverflowPolicy::Decimal128),
+            "Error" => Some(IntegerOverflowPolicy::Error),
+            _ => None,
+        })?,
+        on_error: json.parse("on_error", |name| match name {
+            "Fail" => Some(ParseErrorPolicy::Fail),
+            "SkipRow" => Some(ParseErrorPolicy::SkipRow),
+            "NullValue" => Some(ParseErrorPolicy::NullValue),
+            _ => None,
+        })?,
+        max_errors: json.optional("max_errors", as_usize)?,
+        header_type_hints: json.bool("header_type_hints")?,
+        skip_rows_after_header: json.usize("skip_rows_after_header")?,
+        compression: json.parse("compression", |name| match name {
+            "Uncompressed" => Some(CsvCompression::Uncompressed),
+            "Gzip" => Some(CsvCompression::Gzip),
+            "Zstd" => Some(CsvCompression::Zstd),
+            "Bzip2" => Some(CsvCompression::Bzip2),
+            _ => None,
+        })?,
+        decimal_separator: json.byte("decimal_separator")?,
+        thousands_separator: json.optional("thousands_separator", as_byte)?,
+        currency_symbols: json.strings("currency_symbols")?,
+        epoch_timestamps: json.bool("epoch_timestamps")?,
+        decimal_inference: json.bool("decimal_inference")?,
+        narrow_integers: json.bool("narrow_integers")?,
+        timestamp_format: json.optional("timestamp_format", as_string)?,
+        column_timestamp_formats: json.map("column_timestamp_formats", as_string)?,
+        whitespace_delimited: json.bool("whitespace_delimited")?,
+        collapse_delimiters: json.bool("collapse_delimiters")?,
+        escape: json.optional("escape", as_byte)?,
+        terminator: json.optional("terminator", as_byte)?,
+        newlines_in_values: json.bool("newlines_in_values")?,
+        null_values: json.strings("null_values")?,
+        column_null_values: json.map("column_null_values", |v| v.as_array()?.iter().map(as_string).collect())?,
+        true_values: json.strings("true_values")?,
+        false_values: json.strings("false_values")?,
+        control_chars: json.get("control_chars", |value| match value {
+            Value::String(name) if name == "Keep" => Some(ControlCharPolicy::Keep),
+            Value::String(name) if name == "Strip" => Some(ControlCharPolicy::Strip),
+            _ => {
+                let mut chars = value.get("Replace")?.as_str()?.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(ControlCharPolicy::Replace(c)),
+                    _ => None,
+                }
+            }
+        })?,
+        max_fields: json.optional("max_fields", as_usize)?,
+        max_scan_memory: json.optional("max_scan_memory", as_usize)?,
+        list_cache_ttl: json.optional("list_cache_ttl", as_duration)?,
+        partition_columns: json.get("partition_columns", |v| {
+            v.as_array()?
+                .iter()
+                .map(|column| {
+                    let name = as_string(column.get("name")?)?;
+                    let data_type: DataType = column.get("type")?.as_str()?.parse().ok()?;
+                    Some((name, data_type))
+                })
+                .collect()
+        })?,
+        union_schema: json.bool("union_schema")?,
+        schema_drift_check: json.bool("schema_drift_check")?,
+        type_promotion: json.bool("type_promotion")?,
+        two_pass: json.bool("two_pass")?,
+        schema_sidecar: json.bool("schema_sidecar")?,
+        coalesce_batches: json.bool("coalesce_batches")?,
+        max_concurrent_requests: json.optional("max_concurrent_requests", as_usize)?,
+        http_config: json.optional("http_config", http_config_from_json)?,
+        prefetch_files: json.usize("prefetch_files")?,
+        streaming_reads: json.bool("streaming_reads")?,
+        file_modified_column: json.optional("file_modified_column", as_string)?,
+        byte_offset_column: json.optional("byte_offset_column", as_string)?,
+        line_number_column: json.optional("line_number_column", as_string)?,
+        modified_since: json.optional("modified_since", |v| DateTime::from_timestamp_micros(v.as_i64()?))?,
+        file_order: json.optional("file_order", |v| match v.as_str()? {
+            "NameAscending" => Some(FileOrder::NameAscending),
+            "NameDescending" => Some(FileOrder::NameDescending),
+            "ModifiedAscending" => Some(FileOrder::ModifiedAscending),
+            "ModifiedDescending" => Some(FileOrder::ModifiedDescending),
+            _ => None,
+        })?,
+        max_rows: json.optional("max_rows", as_usize)?,
+        preamble_rows: json.usize("preamble_rows")?,
+        ..CsvFormatOptions::default()
+    };
+
+    // Regexes are compiled again through the builders that validate them
+    if let Some(pattern) = json.optional("null_regex", as_string)? {
+        options = options.with_null_regex(&pattern)?;
+    }
+    let value_transforms = json.map("value_transforms", |transforms| {
+        transforms
+            .as_array()?
+            .iter()
+            .map(|t| Some((as_string(t.get("pattern")?)?, as_string(t.get("replacement")?)?)))
+            .collect::<Option<Vec<_>>>()
+    })?;
+    for (column, transforms) in value_transforms {
+        for (pattern, replacement) in transforms {
+            options = options.with_value_transform(column.clone(), &pattern, replacement)?;
+        }
+    }
+
+    let categorical_columns = json.map("categorical_columns", |categories| {
+        let values: Vec<String> = categories.get("values")?.as_array()?.iter().map(as_string).collect::<Option<_>>()?;
+        let policy = match categories.get("policy")?.as_str()? {
+            "Null" => UnseenCategoryPolicy::Null,
+            "Error" => UnseenCategoryPolicy::Error,
+            _ => return None,
+        };
+        Some((values, policy))
+    })?;
+    for (column, (values, policy)) in categorical_columns {
+        options = options.with_categorical(column, values, policy);
+    }
+
+    let binary_columns = json.map("binary_columns", |binary| {
+        let encoding = match binary.get("encoding")?.as_str()? {
+            "Hex" => BinaryEncoding::Hex,
+            "Base64" => BinaryEncoding::Base64,
+            _ => return None,
+        };
+        let byte_width = match binary.get("byte_width")? {
+            Value::Null => None,
+            width => Some(i32::try_from(width.as_i64()?).ok()?),
+        };
+        Some((encoding, byte_width))
+    })?;
+    for (column, (encoding, byte_width)) in binary_columns {
+        options = match byte_width {
+            Some(width) => options.with_fixed_size_binary_column(column, encoding, width),
+            None => options.with_binary_column(column, encoding),
+        };
+    }
+
+    Ok(options)
+}
+
+fn http_config_to_json(config: &HttpConfig) -> Value {
+    json!({
+        "headers": config.headers,
+        "timeout": config.timeout.map(duration_to_json),
+        "connect_timeout": config.connect_timeout.map(duration_to_json),
+        "max_retries": config.max_retries,
+        "retry_timeout": config.retry_timeout.map(duration_to_json),
+        "initial_backoff": config.initial_backoff.map(duration_to_json),
+        "max_backoff": config.max_backoff.map(duration_to_json),
+    })
+}
+
+fn http_config_from_json(value: &Value) -> Option<HttpConfig> {
+    let duration = |key: &str| match value.get(key)? {
+        Value::Null => Some(None),
+        duration => as_duration(duration).map(Some),
+    };
+    let headers = value
+        .get("headers")?
+        .as_array()?
+        .iter()
+        .map(|header| Some((as_string(header.get(0)?)?, as_string(header.get(1)?)?)))
+        .collect::<Option<_>>()?;
+    let max_retries = match value.get("max_retries")? {
+        Value::Null => None,
+        retries => Some(as_usize(retries)?),
+    };
+    Some(HttpConfig {
+        headers,
+        timeout: duration("timeout")?,
+        connect_timeout: duration("connect_timeout")?,
+        max_retries,
+        retry_timeout: duration("retry_timeout")?,
+        initial_backoff: duration("initial_backoff")?,
+        max_backoff: duration("max_backoff")?,
+    })
+}
+
+/// Durations are sent as whole nanoseconds
+fn duration_to_json(duration: Duration) -> u64 {
+    duration.as_nanos().try_into().unwrap_or(u64::MAX)
+}
+
+fn as_duration(value: &Value) -> Option<Duration> {
+    value.as_u64().map(Duration::from_nanos)
+}
+
+fn as_usize(value: &Value) -> Option<usize> {
+    value.as_u64()?.try_into().ok()
+}
+
+fn as_byte(value: &Value) -> Option<u8> {
+    value.as_u64()?.try_into().ok()
+}
+
+fn as_string(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_string)
+}
+
+/// Typed access to the fields of encoded options, naming the field on failure
+struct Fields<'a>(&'a Value);
+
+impl Fields<'_> {
+    /// Read field `key` with `read`, which returns `None` for a malformed value
+    fn get<T>(&self, key: &str, read: impl FnOnce(&Value) -> Option<T>) -> Result<T> {
+        self.0
+            .get(key)
+            .and_then(read)
+            .ok_or_else(|| DataFusionError::Internal(format!("Encoded CSV options have an invalid '{}'", key)))
+    }
+
+    fn optional<T>(&self, key: &str, read: impl FnOnce(&Value) -> Option<T>) -> Result<Option<T>> {
+        self.get(key, |value| match value {
+            Value::Null => Some(None),
+            value => read(value).map(Some),
+        })
+    }
+
+    fn bool(&self, key: &str) -> Result<bool> {
+        self.get(key, Value::as_bool)
+    }
+
+    fn usize(&self, key: &str) -> Result<usize> {
+        self.get(key, as_usize)
+    }
+
+    fn byte(&self, key: &str) -> Result<u8> {
+        self.get(key, as_byte)
+    }
+
+    fn string(&self, key: &str) -> Result<String> {
+        self.get(key, as_string)
+    }
+
+    fn strings(&self, key: &str) -> Result<Vec<String>> {
+        self.get(key, |value| value.as_array()?.iter().map(as_string).collect())
+    }
+
+    /// A unit enum variant written by its `Debug` name
+    fn parse<T>(&self, key: &str, variant: impl FnOnce(&str) -> Option<T>) -> Result<T> {
+        self.get(key, |value| variant(value.as_str()?))
+    }
+
+    /// An object keyed by column name
+    fn map<T>(&self, key: &str, read: impl Fn(&Value) -> Option<T>) -> Result<HashMap<String, T>> {
+        self.get(key, |value| {
+            value
+                .as_object()?
+                .iter()
+                .map(|(column, value)| Some((column.clone(), read(value)?)))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_round_trip() -> Result<()> {
+        let options = CsvFormatOptions::new()
+            .with_delimiter(b';')
+            .with_decimal_separator(b',')
+            .with_null_regex("^N/?A$")?
+            .with_value_transform("price", r"^\$", "")?
+            .with_categorical("status", ["open", "closed"], UnseenCategoryPolicy::Error)
+            .with_fixed_size_binary_column("hash", BinaryEncoding::Hex, 32)
+            .with_control_chars(ControlCharPolicy::Replace('?'))
+            .with_partition_column("year", DataType::Int32)
+            .with_list_cache_ttl(Some(Duration::from_millis(1500)))
+            .with_http_config(HttpConfig::new().with_header("x-api-key", "k").with_max_retries(3))
+            .with_file_order(FileOrder::ModifiedDescending);
+
+        let json = options_to_json(&options)?;
+        let decoded = options_from_json(&serde_json::from_str(&json.to_string()).unwrap())?;
+        assert_eq!(options_to_json(&decoded)?, json);
+        assert_eq!(decoded.null_regex.as_ref().map(|r| r.as_str()), Some("^N/?A$"));
+        assert_eq!(decoded.categorical_columns["status"].values().collect::<Vec<_>>(), ["open", "closed"]);
+        assert_eq!(decoded.binary_columns["hash"].data_type(), DataType::FixedSizeBinary(32));
+        assert_eq!(decoded.partition_columns, [("year".to_string(), DataType::Int32)]);
+
+        let err = options_to_json(&CsvFormatOptions::new().with_row_filter(|_| true)).unwrap_err();
+        assert!(err.to_string().contains("row filter cannot be serialized"), "{}", err);
+
+        Ok(())
+    }
+}
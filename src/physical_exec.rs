@@ -3,23 +3,95 @@
 //! This module implements the core CSV reading and parsing logic,
 //! converting CSV data directly to Arrow RecordBatches.
 
-use std::io::Cursor;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Cursor, Read};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 
 use arrow::datatypes::SchemaRef;
 use arrow::error::ArrowError;
 use arrow::record_batch::RecordBatch;
-use arrow_array::{ArrayRef, StringArray};
-use arrow_schema::{DataType, Field, Schema};
+use arrow_array::types::Decimal128Type;
+use arrow::record_batch::RecordBatchOptions;
+use arrow_array::{ArrayRef, BooleanArray, StringArray, TimestampMicrosecondArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, TimeUnit, DECIMAL128_MAX_PRECISION};
 use bytes::Bytes;
 use datafusion::datasource::physical_plan::{FileOpener, FileOpenFuture, FileMeta};
 use datafusion::error::{DataFusionError, Result};
-use futures::stream::Stream;
-use object_store::ObjectStore;
+use datafusion::physical_plan::coalesce::{BatchCoalescer, CoalescerState};
+use datafusion::physical_plan::metrics::{Count, ExecutionPlanMetricsSet, MetricBuilder, Time};
+use datafusion::physical_plan::stream::{RecordBatchReceiverStream, RecordBatchStreamAdapter};
+use datafusion::physical_plan::{PhysicalExpr, SendableRecordBatchStream};
+use datafusion_common::cast::as_boolean_array;
+use datafusion::logical_expr::Operator;
+use datafusion_physical_expr::expressions::{
+    BinaryExpr, CastExpr, Column, InListExpr, IsNotNullExpr, IsNullExpr, LikeExpr, Literal, NegativeExpr, NotExpr,
+    TryCastExpr,
+};
+use datafusion_physical_expr::split_conjunction;
+use datafusion_physical_expr::utils::{collect_columns, reassign_predicate_columns};
+use futures::stream::{BoxStream, Stream, StreamExt, TryStreamExt};
+use object_store::{Attribute, GetOptions, GetRange, ObjectStore};
+use tokio::io::{AsyncBufRead, AsyncRead};
+use tokio::task::JoinHandle;
+use tokio_util::io::{StreamReader, SyncIoBridge};
 
-use crate::file_format::CsvFormatOptions;
+use crate::dead_letter::{raw_text, RejectedRow};
+use crate::error::{locate_error, CsvError};
+use crate::file_format::{
+    CsvCompression, CsvFormatOptions, IntegerOverflowPolicy, ParseErrorPolicy, RawRecord,
+    StringOverflowPolicy,
+};
+use crate::telemetry::FileScanTelemetry;
+
+/// Per-partition metrics of a CSV scan, reported by `EXPLAIN ANALYZE`
+///
+/// Separating I/O wait from CPU work shows whether a slow scan is
+/// network-bound or parse-bound.
+#[derive(Debug, Clone, Default)]
+pub struct CsvScanMetrics {
+    /// Time spent waiting on the object store
+    pub io_time: Time,
+    /// Time spent decompressing file contents
+    pub decompress_time: Time,
+    /// Time spent splitting input into CSV records
+    pub parse_time: Time,
+    /// Time spent converting records into Arrow arrays
+    pub build_time: Time,
+    /// Values that did not parse as their column's type and were read as null
+    pub type_mismatches: Count,
+    /// Rows dropped by [`ParseErrorPolicy::SkipRow`]
+    pub skipped_rows: Count,
+    /// Bytes fetched from the object store, before decompression
+    pub bytes_read: Count,
+    /// Records parsed, before any filtering
+    pub rows_parsed: Count,
+    /// Files (or parts of split files) opened
+    pub files_opened: Count,
+    /// CPU time spent parsing, filtering and building batches
+    pub elapsed_compute: Time,
+}
+
+impl CsvScanMetrics {
+    /// Register the metrics for `partition` in the plan's metrics set
+    pub fn new(metrics: &ExecutionPlanMetricsSet, partition: usize) -> Self {
+        Self {
+            io_time: MetricBuilder::new(metrics).subset_time("io_time", partition),
+            decompress_time: MetricBuilder::new(metrics).subset_time("decompress_time", partition),
+            parse_time: MetricBuilder::new(metrics).subset_time("parse_time", partition),
+            build_time: MetricBuilder::new(metrics).subset_time("build_time", partition),
+            type_mismatches: MetricBuilder::new(metrics).counter("type_mismatches", partition),
+            skipped_rows: MetricBuilder::new(metrics).counter("skipped_rows", partition),
+            bytes_read: MetricBuilder::new(metrics).counter("bytes_read", partition),
+            rows_parsed: MetricBuilder::new(metrics).counter("rows_parsed", partition),
+            files_opened: MetricBuilder::new(metrics).counter("files_opened", partition),
+            elapsed_compute: MetricBuilder::new(metrics).elapsed_compute(partition),
+        }
+    }
+}
 
 /// CSV file opener that implements the FileOpener trait
 #[derive(Clone)]
@@ -34,6 +106,14 @@ pub struct CsvOpener {
     batch_size: usize,
     /// Object store for reading files
     object_store: Arc<dyn ObjectStore>,
+    /// Scan timing metrics
+    metrics: CsvScanMetrics,
+    /// Background fetcher for the partition's upcoming files
+    prefetcher: Option<Arc<FilePrefetcher>>,
+    /// Rows dropped so far by every partition of the scan, checked against `max_errors`
+    rejected_rows: Arc<AtomicUsize>,
+    /// Pushed-down filter checked before whole rows are built
+    predicate: Option<Arc<ScanPredicate>>,
 }
 
 impl CsvOpener {
@@ -49,6 +129,10 @@ impl CsvOpener {
             projection,
             batch_size: 8192,
             object_store,
+            metrics: CsvScanMetrics::default(),
+            prefetcher: None,
+            rejected_rows: Arc::new(AtomicUsize::new(0)),
+            predicate: None,
         }
     }
 
@@ -56,6 +140,343 @@ impl CsvOpener {
         self.batch_size = batch_size;
         self
     }
+
+    pub fn with_metrics(mut self, metrics: CsvScanMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Share the scan-wide count of dropped rows with the other partitions
+    pub(crate) fn with_rejected_rows(mut self, rejected_rows: Arc<AtomicUsize>) -> Self {
+        self.rejected_rows = rejected_rows;
+        self
+    }
+
+    pub(crate) fn with_predicate(mut self, predicate: Option<Arc<ScanPredicate>>) -> Self {
+        self.predicate = predicate;
+        self
+    }
+
+    pub(crate) fn with_prefetcher(mut self, prefetcher: Arc<FilePrefetcher>) -> Self {
+        self.prefetcher = Some(prefetcher);
+        self
+    }
+
+    /// Schema of the batches produced, after projection
+    fn output_schema(&self) -> SchemaRef {
+        match &self.projection {
+            Some(proj) => {
+                let fields: Vec<Field> = proj.iter().map(|i| self.schema.field(*i).clone()).collect();
+                Arc::new(Schema::new(fields))
+            }
+            None => self.schema.clone(),
+        }
+    }
+}
+
+/// The part of a pushed-down filter a scan checks before building whole rows
+///
+/// Only conjuncts over file columns built from columns, literals and simple
+/// operators are kept. Each batch first builds just the columns they read,
+/// and the remaining columns only for rows that pass. The filter is applied
+/// again above the scan, so conjuncts left out are still enforced.
+#[derive(Debug)]
+pub(crate) struct ScanPredicate {
+    /// Conjunction of the kept conjuncts, over the columns of `schema`
+    expr: Arc<dyn PhysicalExpr>,
+    /// Table columns the predicate reads, in the order of `schema`
+    columns: Vec<usize>,
+    schema: SchemaRef,
+}
+
+impl ScanPredicate {
+    /// The checkable part of `predicate`, a filter over a table whose files have `file_schema`
+    pub(crate) fn try_new(predicate: &Arc<dyn PhysicalExpr>, file_schema: &Schema) -> Result<Option<Self>> {
+        let file_columns = file_schema.fields().len();
+        let Some(expr) = split_conjunction(predicate)
+            .into_iter()
+            .filter(|expr| is_cheap(expr) && collect_columns(expr).iter().all(|c| c.index() < file_columns))
+            .cloned()
+            .reduce(|left, right| Arc::new(BinaryExpr::new(left, Operator::And, right)))
+        else {
+            return Ok(None);
+        };
+
+        let mut columns: Vec<usize> = collect_columns(&expr).iter().map(Column::index).collect();
+        columns.sort_unstable();
+        let schema = Arc::new(file_schema.project(&columns)?);
+        let expr = reassign_predicate_columns(expr, &schema, false)?;
+        Ok(Some(Self { expr, columns, schema }))
+    }
+
+    pub(crate) fn expr(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.expr
+    }
+
+    #[cfg(feature = "proto")]
+    pub(crate) fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// A predicate split off by [`try_new`](Self::try_new), from its expression over `columns` of `file_schema`
+    #[cfg(feature = "proto")]
+    pub(crate) fn from_parts(expr: Arc<dyn PhysicalExpr>, columns: Vec<usize>, file_schema: &Schema) -> Result<Self> {
+        let schema = Arc::new(file_schema.project(&columns)?);
+        Ok(Self { expr, columns, schema })
+    }
+
+    /// Whether each buffered record of `stream` passes; null means it does not
+    fn evaluate(&self, stream: &CsvStream) -> Result<BooleanArray> {
+        let records = &stream.record_buffer;
+        let columns = self
+            .columns
+            .iter()
+            .map(|&idx| stream.build_column(idx, records).map(|(array, _)| array))
+            .collect::<Result<Vec<_>>>()?;
+        let batch = RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            columns,
+            &RecordBatchOptions::new().with_row_count(Some(records.len())),
+        )?;
+        let result = self.expr.evaluate(&batch)?.into_array(records.len())?;
+        Ok(as_boolean_array(&result)?.clone())
+    }
+}
+
+/// Whether `expr` is built only from columns, literals and simple operators
+///
+/// Such expressions are cheap and deterministic, so evaluating them twice is safe.
+fn is_cheap(expr: &Arc<dyn PhysicalExpr>) -> bool {
+    let any = expr.as_any();
+    let simple = any.is::<Column>()
+        || any.is::<Literal>()
+        || any.is::<BinaryExpr>()
+        || any.is::<NotExpr>()
+        || any.is::<NegativeExpr>()
+        || any.is::<IsNullExpr>()
+        || any.is::<IsNotNullExpr>()
+        || any.is::<InListExpr>()
+        || any.is::<LikeExpr>()
+        || any.is::<CastExpr>()
+        || any.is::<TryCastExpr>();
+    simple && expr.children().into_iter().all(is_cheap)
+}
+
+/// Downloads the next files of a partition while the current one is parsed
+///
+/// Opening a file starts fetching up to `lookahead` files after it in the
+/// partition's file order, so object store latency overlaps with parsing.
+pub(crate) struct FilePrefetcher {
+    store: Arc<dyn ObjectStore>,
+    files: Vec<object_store::path::Path>,
+    lookahead: usize,
+    pending: Mutex<HashMap<object_store::path::Path, JoinHandle<Result<FetchedObject>>>>,
+}
+
+impl FilePrefetcher {
+    pub(crate) fn new(
+        store: Arc<dyn ObjectStore>,
+        files: Vec<object_store::path::Path>,
+        lookahead: usize,
+    ) -> Self {
+        Self {
+            store,
+            files,
+            lookahead,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fetch `location`, reusing a download already in flight
+    async fn fetch(&self, location: &object_store::path::Path) -> Result<FetchedObject> {
+        let prefetched = {
+            let mut pending = self.pending.lock().unwrap();
+            if let Some(index) = self.files.iter().position(|f| f == location) {
+                for next in self.files.iter().skip(index + 1).take(self.lookahead) {
+                    if !pending.contains_key(next) {
+                        let store = self.store.clone();
+                        let next_location = next.clone();
+                        let download =
+                            tokio::spawn(async move { fetch_object(&store, &next_location).await });
+                        pending.insert(next.clone(), download);
+                    }
+                }
+            }
+            pending.remove(location)
+        };
+
+        match prefetched {
+            Some(download) => download.await.map_err(|e| {
+                DataFusionError::Execution(format!("Prefetch of '{}' failed: {}", location, e))
+            })?,
+            None => fetch_object(&self.store, location).await,
+        }
+    }
+}
+
+impl Drop for FilePrefetcher {
+    fn drop(&mut self) {
+        // Files a LIMIT never reached are not worth finishing
+        for download in self.pending.lock().unwrap().values() {
+            download.abort();
+        }
+    }
+}
+
+/// Contents of an object as served by its store
+struct FetchedObject {
+    bytes: Bytes,
+    /// Codec named by the response's `Content-Encoding`, if any
+    content_encoding: Option<CsvCompression>,
+}
+
+impl FetchedObject {
+    /// Codec to decompress the contents with
+    ///
+    /// A `Content-Encoding` is only applied to files configured as uncompressed:
+    /// servers commonly label `.csv.gz` files `gzip` without compressing twice.
+    fn compression(&self, options: &CsvFormatOptions) -> CsvCompression {
+        effective_compression(options, self.content_encoding)
+    }
+}
+
+/// Codec for contents configured per `options` and served with `content_encoding`
+fn effective_compression(
+    options: &CsvFormatOptions,
+    content_encoding: Option<CsvCompression>,
+) -> CsvCompression {
+    match (options.compression, content_encoding) {
+        (CsvCompression::Uncompressed, Some(encoding)) => encoding,
+        (compression, _) => compression,
+    }
+}
+
+/// Contents of an object as a stream of chunks, as served by its store
+struct ObjectChunks {
+    chunks: BoxStream<'static, Result<Bytes>>,
+    /// Size of the object in bytes
+    size: usize,
+    /// Codec named by the response's `Content-Encoding`, if any
+    content_encoding: Option<CsvCompression>,
+}
+
+/// State of a download that may be resumed
+struct Download {
+    store: Arc<dyn ObjectStore>,
+    location: object_store::path::Path,
+    e_tag: Option<String>,
+    size: usize,
+    received: usize,
+    resumes: usize,
+    chunks: BoxStream<'static, object_store::Result<Bytes>>,
+}
+
+/// Times a dropped download is resumed before the scan fails
+const MAX_DOWNLOAD_RESUMES: usize = 3;
+
+/// Stream the chunks of the object at `location`
+///
+/// Object stores retry failed requests, but not a body that breaks off
+/// midway. Such downloads are resumed with a ranged GET from the last byte
+/// received, pinned to the original ETag so a replaced object is not spliced.
+async fn stream_object(
+    store: &Arc<dyn ObjectStore>,
+    location: &object_store::path::Path,
+) -> Result<ObjectChunks> {
+    let get_result = store.get(location).await.map_err(CsvError::Store)?;
+    let content_encoding = match get_result.attributes.get(&Attribute::ContentEncoding) {
+        Some(encoding) => content_encoding(encoding, location)?,
+        None => None,
+    };
+    let size = get_result.range.end - get_result.range.start;
+    let download = Download {
+        store: store.clone(),
+        location: location.clone(),
+        e_tag: get_result.meta.e_tag.clone(),
+        size,
+        received: 0,
+        resumes: 0,
+        chunks: get_result.into_stream(),
+    };
+
+    let chunks = futures::stream::try_unfold(download, |mut download| async move {
+        loop {
+            match download.chunks.next().await {
+                Some(Ok(chunk)) => {
+                    download.received += chunk.len();
+                    return Ok(Some((chunk, download)));
+                }
+                None => return Ok(None),
+                Some(Err(_)) if download.received >= download.size => return Ok(None),
+                Some(Err(_)) if download.resumes < MAX_DOWNLOAD_RESUMES => {
+                    download.resumes += 1;
+                    let options = GetOptions {
+                        range: Some(GetRange::Offset(download.received)),
+                        if_match: download.e_tag.clone(),
+                        ..Default::default()
+                    };
+                    download.chunks = download
+                        .store
+                        .get_opts(&download.location, options)
+                        .await
+                        .map_err(CsvError::Store)?
+                        .into_stream();
+                }
+                Some(Err(e)) => return Err(CsvError::Store(e).into()),
+            }
+        }
+    });
+    Ok(ObjectChunks {
+        chunks: chunks.boxed(),
+        size,
+        content_encoding,
+    })
+}
+
+/// Download the whole object at `location`
+async fn fetch_object(
+    store: &Arc<dyn ObjectStore>,
+    location: &object_store::path::Path,
+) -> Result<FetchedObject> {
+    let object = stream_object(store, location).await?;
+    let body = object
+        .chunks
+        .try_fold(Vec::with_capacity(object.size), |mut body, chunk| async move {
+            body.extend_from_slice(&chunk);
+            Ok(body)
+        })
+        .await?;
+    Ok(FetchedObject {
+        bytes: body.into(),
+        content_encoding: object.content_encoding,
+    })
+}
+
+/// Codec for a `Content-Encoding` header value; `None` for `identity`
+fn content_encoding(
+    value: &str,
+    location: &object_store::path::Path,
+) -> Result<Option<CsvCompression>> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "" | "identity" => Ok(None),
+        "gzip" | "x-gzip" => Ok(Some(CsvCompression::Gzip)),
+        "zstd" => Ok(Some(CsvCompression::Zstd)),
+        other => Err(DataFusionError::Execution(format!(
+            "'{}' was served with unsupported Content-Encoding '{}'; expected gzip or zstd",
+            location, other
+        ))),
+    }
+}
+
+/// Download `location` and decompress it per the options or its `Content-Encoding`
+pub(crate) async fn fetch_decompressed(
+    store: &Arc<dyn ObjectStore>,
+    location: &object_store::path::Path,
+    options: &CsvFormatOptions,
+) -> Result<Bytes> {
+    let object = fetch_object(store, location).await?;
+    let compression = object.compression(options);
+    decompress(object.bytes, compression).await
 }
 
 impl FileOpener for CsvOpener {
@@ -64,19 +485,40 @@ impl FileOpener for CsvOpener {
         let object_store = self.object_store.clone();
 
         Ok(Box::pin(async move {
-            // Get async reader from object store
+            if let Some(range) = &file_meta.range {
+                let range = range.start as usize..range.end as usize;
+                return open_range(opener, object_store, file_meta, range).await;
+            }
+
+            // Options that rewrite the input need the whole file
+            let options = &opener.options;
+            if options.streaming_reads && !options.whitespace_delimited && !options.collapse_delimiters {
+                return open_streaming(opener, object_store, file_meta).await;
+            }
+
+            // Fail before downloading when the file alone exceeds the ceiling
             let location = file_meta.location();
-            let get_result = object_store.get(location).await.map_err(|e| {
-                DataFusionError::Execution(format!("Failed to read file: {}", e))
-            })?;
+            check_scan_memory(file_meta.object_meta.size, location.as_ref(), &opener.options)?;
 
-            // Read bytes from object store
-            let bytes = get_result.bytes().await.map_err(|e| {
-                DataFusionError::Execution(format!("Failed to read bytes: {}", e))
-            })?;
+            // Read the file, waiting on a prefetched download when one is in flight
+            let io_timer = opener.metrics.io_time.timer();
+            let object = match &opener.prefetcher {
+                Some(prefetcher) => prefetcher.fetch(location).await?,
+                None => fetch_object(&object_store, location).await?,
+            };
+            io_timer.done();
+
+            let decompress_timer = opener.metrics.decompress_time.timer();
+            let compression = object.compression(&opener.options);
+            let bytes_read = object.bytes.len();
+            let bytes = decompress(object.bytes, compression).await?;
+            decompress_timer.done();
+            check_scan_memory(bytes.len(), location.as_ref(), &opener.options)?;
 
             // Create streaming CSV reader
-            let stream = CsvStream::new(bytes, opener)?;
+            let mut stream = CsvStream::new(bytes, opener, location.to_string())?;
+            stream.last_modified = Some(file_meta.object_meta.last_modified.timestamp_micros());
+            stream.record_bytes_read(bytes_read);
 
             // Return the stream directly - CsvStream already returns ArrowError
             Ok(Box::pin(stream) as _)
@@ -84,6 +526,341 @@ impl FileOpener for CsvOpener {
     }
 }
 
+/// Bytes requested at a time while looking for a record boundary
+const BOUNDARY_PROBE_SIZE: usize = 64 * 1024;
+
+/// Parse the records starting within byte `range` of the object of `file_meta`
+///
+/// Each part of a split file starts at the first record beginning at or
+/// after its range start and reads past its range end to finish its last
+/// record, so neighbouring parts agree on where one ends and the next begins.
+/// Parts after the first get the file's preamble and header prepended, so
+/// they are parsed like the first.
+async fn open_range(
+    mut opener: CsvOpener,
+    object_store: Arc<dyn ObjectStore>,
+    file_meta: FileMeta,
+    range: std::ops::Range<usize>,
+) -> Result<BoxStream<'static, std::result::Result<RecordBatch, ArrowError>>> {
+    let location = file_meta.location().clone();
+    let size = file_meta.object_meta.size;
+    let terminator = opener.options.line_terminator();
+
+    let io_timer = opener.metrics.io_time.timer();
+    let start = record_start(&object_store, &location, range.start, size, terminator).await?;
+    let end = record_start(&object_store, &location, range.end, size, terminator).await?;
+    if start >= end {
+        io_timer.done();
+        return Ok(futures::stream::empty().boxed());
+    }
+    check_scan_memory(end - start, location.as_ref(), &opener.options)?;
+
+    let mut input = Vec::with_capacity(end - start);
+    if start > 0 {
+        let head_lines = opener.options.preamble_rows + usize::from(opener.options.has_header);
+        let mut head_end = 0;
+        for _ in 0..head_lines {
+            head_end = find_terminator(&object_store, &location, head_end, size, terminator).await? + 1;
+        }
+        if head_end > 0 {
+            input.extend_from_slice(&get_range(&object_store, &location, 0..head_end.min(start)).await?);
+        }
+        // Rows after the header were skipped by the first part
+        opener.options.skip_rows_after_header = 0;
+    }
+    input.extend_from_slice(&get_range(&object_store, &location, start..end).await?);
+    io_timer.done();
+
+    let bytes_read = input.len();
+    let mut stream = CsvStream::new(input.into(), opener, location.to_string())?;
+    stream.last_modified = Some(file_meta.object_meta.last_modified.timestamp_micros());
+    stream.lines_known = start == 0;
+    stream.record_bytes_read(bytes_read);
+    Ok(Box::pin(stream))
+}
+
+/// Fill in the line and field of a `CsvError::Parse` raised for a value of `data`
+///
+/// Values are converted in order, so the first record holding the failing
+/// value is the one that raised the error.
+fn locate_value(
+    error: DataFusionError,
+    data: &[Option<&str>],
+    records: &[csv::StringRecord],
+    record_idx: Option<usize>,
+) -> DataFusionError {
+    match error {
+        DataFusionError::External(source) => match source.downcast::<CsvError>() {
+            Ok(mut csv_error) => {
+                if let CsvError::Parse { line, column, value: Some(value), .. } = csv_error.as_mut()
+                    && line.is_none()
+                {
+                    let row = data.iter().position(|v| *v == Some(value.as_str()));
+                    *line = row.and_then(|row| records[row].position()).map(|p| p.line());
+                    *column = column.or(record_idx);
+                }
+                DataFusionError::External(csv_error)
+            }
+            Err(source) => DataFusionError::External(source),
+        },
+        other => other,
+    }
+}
+
+/// Drop the line number of the `CsvError` carried by `error`, if any
+///
+/// Lines in a later part of a split file count from the part's start, so
+/// reporting no line beats reporting a wrong one.
+fn forget_line(error: DataFusionError) -> DataFusionError {
+    match error {
+        DataFusionError::External(source) => match source.downcast::<CsvError>() {
+            Ok(mut csv_error) => {
+                if let CsvError::Encoding { line, .. } | CsvError::Parse { line, .. } = csv_error.as_mut() {
+                    *line = None;
+                }
+                DataFusionError::External(csv_error)
+            }
+            Err(source) => DataFusionError::External(source),
+        },
+        other => other,
+    }
+}
+
+/// Offset of the first record beginning at or after `offset`
+///
+/// Records begin at the start of the object and after each `terminator`.
+async fn record_start(
+    store: &Arc<dyn ObjectStore>,
+    location: &object_store::path::Path,
+    offset: usize,
+    size: usize,
+    terminator: u8,
+) -> Result<usize> {
+    if offset == 0 || offset >= size {
+        return Ok(offset.min(size));
+    }
+    let terminator_at = find_terminator(store, location, offset - 1, size, terminator).await?;
+    Ok((terminator_at + 1).min(size))
+}
+
+/// Offset of the first `terminator` at or after `from`, or `size` if there is none
+async fn find_terminator(
+    store: &Arc<dyn ObjectStore>,
+    location: &object_store::path::Path,
+    mut from: usize,
+    size: usize,
+    terminator: u8,
+) -> Result<usize> {
+    while from < size {
+        let end = (from + BOUNDARY_PROBE_SIZE).min(size);
+        let chunk = get_range(store, location, from..end).await?;
+        if let Some(pos) = chunk.iter().position(|&b| b == terminator) {
+            return Ok(from + pos);
+        }
+        from = end;
+    }
+    Ok(size)
+}
+
+async fn get_range(
+    store: &Arc<dyn ObjectStore>,
+    location: &object_store::path::Path,
+    range: std::ops::Range<usize>,
+) -> Result<Bytes> {
+    Ok(store.get_range(location, range).await.map_err(CsvError::Store)?)
+}
+
+/// Parse the object of `file_meta` while it downloads, on a blocking thread
+///
+/// The reader pulls chunks from the object store as the parser needs them,
+/// so memory stays bounded by the parser's buffer and two pending batches.
+async fn open_streaming(
+    opener: CsvOpener,
+    object_store: Arc<dyn ObjectStore>,
+    file_meta: FileMeta,
+) -> Result<BoxStream<'static, std::result::Result<RecordBatch, ArrowError>>> {
+    let location = file_meta.location().clone();
+    let object = stream_object(&object_store, &location).await?;
+    let compression = effective_compression(&opener.options, object.content_encoding);
+    let chunks = object.chunks.map_err(std::io::Error::other);
+    let reader = SyncIoBridge::new(decompress_reader(StreamReader::new(chunks), compression)?);
+
+    let last_modified = file_meta.object_meta.last_modified.timestamp_micros();
+    let mut builder = RecordBatchReceiverStream::builder(opener.output_schema(), 2);
+    let tx = builder.tx();
+    builder.spawn_blocking(move || {
+        let mut reader = BufReader::new(reader);
+        for _ in 0..opener.options.preamble_rows {
+            let mut line = Vec::new();
+            let read = reader.read_until(opener.options.line_terminator(), &mut line).map_err(|source| CsvError::Io {
+                context: "Failed to read preamble".to_string(),
+                source,
+            })?;
+            if read == 0 {
+                break;
+            }
+        }
+
+        let mut stream = CsvStream::from_reader(Box::new(reader), 0, opener, location.to_string())?;
+        stream.last_modified = Some(last_modified);
+        stream.record_bytes_read(object.size);
+        loop {
+            match stream.read_next_batch() {
+                Ok(Some(batch)) => {
+                    // A closed channel means the consumer stopped, e.g. at a LIMIT
+                    if tx.blocking_send(Ok(batch)).is_err() {
+                        return Ok(());
+                    }
+                }
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    stream.telemetry.record_error();
+                    return Err(e);
+                }
+            }
+        }
+    });
+    Ok(builder
+        .build()
+        .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+        .boxed())
+}
+
+/// Concatenate the batches of `input` into batches of at least `target_rows` rows
+///
+/// Only the last batch may be smaller.
+pub(crate) fn coalesce_batches(
+    input: SendableRecordBatchStream,
+    target_rows: usize,
+) -> SendableRecordBatchStream {
+    let schema = input.schema();
+    let coalescer = BatchCoalescer::new(schema.clone(), target_rows, None);
+    let batches = futures::stream::unfold(Some((input, coalescer)), |state| async move {
+        let (mut input, mut coalescer) = state?;
+        loop {
+            match input.next().await {
+                Some(Ok(batch)) => {
+                    if let CoalescerState::TargetReached = coalescer.push_batch(batch) {
+                        return Some((coalescer.finish_batch(), Some((input, coalescer))));
+                    }
+                }
+                Some(Err(e)) => return Some((Err(e), None)),
+                None if coalescer.is_empty() => return None,
+                None => return Some((coalescer.finish_batch(), None)),
+            }
+        }
+    });
+    Box::pin(RecordBatchStreamAdapter::new(schema, batches))
+}
+
+/// Parse in-memory CSV contents into batches, inferring the schema from them
+pub(crate) fn read_in_memory(
+    bytes: Bytes,
+    options: &CsvFormatOptions,
+    location: &str,
+) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    // Inference does no I/O, so blocking on it cannot stall the runtime
+    let schema = Arc::new(futures::executor::block_on(infer_schema(&bytes, options))?);
+    let mut batches = Vec::new();
+    scan_in_memory(bytes, schema.clone(), options, location, |batch| {
+        batches.push(batch);
+        Ok(())
+    })?;
+    Ok((schema, batches))
+}
+
+/// Parse in-memory CSV contents with `schema`, passing each batch to `f`
+pub(crate) fn scan_in_memory(
+    bytes: Bytes,
+    schema: SchemaRef,
+    options: &CsvFormatOptions,
+    location: &str,
+    mut f: impl FnMut(RecordBatch) -> Result<()>,
+) -> Result<()> {
+    let store = Arc::new(object_store::memory::InMemory::new());
+    let opener = CsvOpener::new(options.clone(), schema, None, store)
+        .with_batch_size(options.batch_size);
+
+    let mut stream = CsvStream::new(bytes, opener, location.to_string())?;
+    while let Some(batch) = stream.read_next_batch()? {
+        f(batch)?;
+    }
+    Ok(())
+}
+
+/// Decompress file contents according to the configured codec
+#[cfg_attr(not(any(feature = "gzip", feature = "zstd", feature = "bzip2")), allow(unreachable_code, unused_mut, unused_variables))]
+pub(crate) async fn decompress(bytes: Bytes, compression: CsvCompression) -> Result<Bytes> {
+    #[cfg(any(feature = "gzip", feature = "zstd", feature = "bzip2"))]
+    use tokio::io::AsyncReadExt;
+
+    compression.check_enabled()?;
+    let mut decompressed = Vec::new();
+    let result: std::io::Result<usize> = match compression {
+        CsvCompression::Uncompressed => return Ok(bytes),
+        #[cfg(feature = "gzip")]
+        CsvCompression::Gzip => {
+            let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(&bytes[..]);
+            // Concatenated gzip members are valid (e.g. `cat a.gz b.gz`)
+            decoder.multiple_members(true);
+            decoder.read_to_end(&mut decompressed).await
+        }
+        #[cfg(feature = "zstd")]
+        CsvCompression::Zstd => {
+            let mut decoder = async_compression::tokio::bufread::ZstdDecoder::new(&bytes[..]);
+            decoder.multiple_members(true);
+            decoder.read_to_end(&mut decompressed).await
+        }
+        #[cfg(feature = "bzip2")]
+        CsvCompression::Bzip2 => {
+            // Parallel compressors such as pbzip2 write concatenated streams
+            let mut decoder = async_compression::tokio::bufread::BzDecoder::new(&bytes[..]);
+            decoder.multiple_members(true);
+            decoder.read_to_end(&mut decompressed).await
+        }
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("disabled codecs are rejected by check_enabled"),
+    };
+
+    result.map_err(|source| CsvError::Io {
+        context: format!("Failed to decompress {:?} data", compression),
+        source,
+    })?;
+    Ok(Bytes::from(decompressed))
+}
+
+/// Wrap `reader` in a decoder for `compression`
+#[cfg_attr(not(any(feature = "gzip", feature = "zstd", feature = "bzip2")), allow(unreachable_code))]
+fn decompress_reader(
+    reader: impl AsyncBufRead + Send + Unpin + 'static,
+    compression: CsvCompression,
+) -> Result<Pin<Box<dyn AsyncRead + Send>>> {
+    compression.check_enabled()?;
+    Ok(match compression {
+        CsvCompression::Uncompressed => Box::pin(reader),
+        #[cfg(feature = "gzip")]
+        CsvCompression::Gzip => {
+            let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::pin(decoder)
+        }
+        #[cfg(feature = "zstd")]
+        CsvCompression::Zstd => {
+            let mut decoder = async_compression::tokio::bufread::ZstdDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::pin(decoder)
+        }
+        #[cfg(feature = "bzip2")]
+        CsvCompression::Bzip2 => {
+            let mut decoder = async_compression::tokio::bufread::BzDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::pin(decoder)
+        }
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("disabled codecs are rejected by check_enabled"),
+    })
+}
+
 /// Streaming CSV reader that yields RecordBatches incrementally
 ///
 /// This struct implements the `Stream` trait to provide on-demand batch processing.
@@ -99,7 +876,7 @@ impl FileOpener for CsvOpener {
 /// making it suitable for processing large CSV files efficiently.
 struct CsvStream {
     /// CSV reader
-    reader: csv::Reader<Cursor<Vec<u8>>>,
+    reader: csv::Reader<Box<dyn Read + Send>>,
     /// CSV opener with configuration
     opener: CsvOpener,
     /// Buffer for collecting records (reused to minimize allocations)
@@ -108,65 +885,256 @@ struct CsvStream {
     schema: SchemaRef,
     /// Whether we've finished reading
     finished: bool,
+    /// Location of the file being read, for error messages
+    location: String,
+    /// Bytes of file input held by the reader
+    buffered_bytes: usize,
+    /// Position of each table column in this file's records, when matched by header name
+    column_map: Option<Vec<Option<usize>>>,
+    /// Schema describing this file's records, as seen by the row filter
+    record_schema: SchemaRef,
+    /// Modification time of the file in microseconds since the epoch
+    last_modified: Option<i64>,
+    /// Metrics reported for this file when the stream is dropped
+    telemetry: FileScanTelemetry,
+    /// Whether record line numbers count from the start of the file, which
+    /// they don't in the later parts of a split file
+    lines_known: bool,
 }
 
 impl CsvStream {
-    fn new(bytes: Bytes, opener: CsvOpener) -> Result<Self> {
-        let cursor = Cursor::new(bytes.to_vec());
-        let reader = csv::ReaderBuilder::new()
-            .delimiter(opener.options.delimiter)
-            .has_headers(opener.options.has_header)
-            .from_reader(cursor);
+    fn new(bytes: Bytes, opener: CsvOpener, location: String) -> Result<Self> {
+        let input = prepare_input(&bytes, &opener.options).into_owned();
+        let buffered_bytes = input.len();
+        let mut stream = Self::from_reader(Box::new(Cursor::new(input)), buffered_bytes, opener, location)?;
+        stream.telemetry.set_bytes_read(bytes.len());
+        Ok(stream)
+    }
 
-        // Get the output schema (projected or full)
-        let schema = if let Some(ref proj) = opener.projection {
-            let fields: Vec<Field> = proj
-                .iter()
-                .map(|i| opener.schema.field(*i).clone())
-                .collect();
-            Arc::new(Schema::new(fields))
+    /// Read the records of `input`, which holds `buffered_bytes` of file contents in memory
+    fn from_reader(
+        input: Box<dyn Read + Send>,
+        buffered_bytes: usize,
+        opener: CsvOpener,
+        location: String,
+    ) -> Result<Self> {
+        let telemetry = FileScanTelemetry::new(&location, buffered_bytes);
+        opener.metrics.files_opened.add(1);
+        let mut reader = reader_builder(&opener.options).from_reader(input);
+
+        let options = &opener.options;
+        let header_error = |e: csv::Error| CsvError::from(e).locate(&location, &Schema::empty());
+        if options.schema_drift_check && options.has_header {
+            let headers = reader.headers().map_err(header_error)?;
+            check_schema_drift(headers, &opener.schema, &location, options)?;
+        }
+
+        // Files may lack table columns or order them differently
+        let column_map = if options.has_header {
+            let headers = reader.headers().map_err(header_error)?;
+            SchemaAdapter::new(&opener.schema, options).map_columns(headers)
         } else {
-            opener.schema.clone()
+            None
+        };
+        let record_schema = match &column_map {
+            Some(_) => {
+                let headers = reader.headers().map_err(header_error)?;
+                let fields: Vec<Field> = headers
+                    .iter()
+                    .map(|header| Field::new(column_name(header, options), DataType::Utf8, true))
+                    .collect();
+                Arc::new(Schema::new(fields))
+            }
+            None => opener.schema.clone(),
         };
 
+        skip_rows_after_header(&mut reader, options)
+            .map_err(|e| locate_error(e, &location, &record_schema))?;
+
+        // Get the output schema (projected or full)
+        let schema = opener.output_schema();
+
         Ok(Self {
             reader,
             opener,
             record_buffer: Vec::new(),
             schema,
             finished: false,
+            location,
+            buffered_bytes,
+            column_map,
+            record_schema,
+            last_modified: None,
+            telemetry,
+            lines_known: true,
         })
     }
 
-    /// Read next batch of records
+    /// Report `bytes` fetched from the object store for this file
+    fn record_bytes_read(&mut self, bytes: usize) {
+        self.telemetry.set_bytes_read(bytes);
+        self.opener.metrics.bytes_read.add(bytes);
+    }
+
+    /// Read next batch of records, naming this file and the column in errors
     fn read_next_batch(&mut self) -> Result<Option<RecordBatch>> {
-        if self.finished {
-            return Ok(None);
+        self.read_batch().map_err(|e| {
+            let e = locate_error(e, &self.location, &self.record_schema);
+            if self.lines_known { e } else { forget_line(e) }
+        })
+    }
+
+    fn read_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let elapsed_compute = self.opener.metrics.elapsed_compute.clone();
+        let _timer = elapsed_compute.timer();
+        // Every row of a batch may fail the predicate before the end of the file
+        loop {
+            if self.finished {
+                return Ok(None);
+            }
+            self.read_records()?;
+            if let Some(predicate) = &self.opener.predicate {
+                let keep = predicate.evaluate(self)?;
+                let mut rows = keep.iter();
+                self.record_buffer.retain(|_| rows.next() == Some(Some(true)));
+            }
+            if !self.record_buffer.is_empty() {
+                break;
+            }
         }
 
+        // Convert records to batch
+        let build_timer = self.opener.metrics.build_time.timer();
+        let batch = self.records_to_batch(&self.record_buffer)?;
+        build_timer.done();
+        check_scan_memory(
+            self.buffered_bytes + batch.get_array_memory_size(),
+            &self.location,
+            &self.opener.options,
+        )?;
+        Ok(Some(batch))
+    }
+
+    /// Read up to `batch_size` records into the record buffer
+    fn read_records(&mut self) -> Result<()> {
         self.record_buffer.clear();
 
-        // Read up to batch_size records
-        for _ in 0..self.opener.batch_size {
-            match self.reader.records().next() {
-                Some(Ok(record)) => self.record_buffer.push(record),
-                Some(Err(e)) => {
-                    return Err(DataFusionError::Execution(format!("CSV parse error: {}", e)))
+        let parse_timer = self.opener.metrics.parse_time.timer();
+        while self.record_buffer.len() < self.opener.batch_size {
+            // Read raw bytes first, so a rejected row's text can be captured
+            let mut raw = csv::ByteRecord::new();
+            match self.reader.read_byte_record(&mut raw) {
+                Ok(true) => {
+                    let record = match csv::StringRecord::from_byte_record(raw) {
+                        Ok(record) => record,
+                        Err(e) => {
+                            let column = e.utf8_error().field();
+                            let raw = e.into_byte_record();
+                            let error = CsvError::Encoding {
+                                file: None,
+                                line: raw.position().map(|p| p.line()),
+                                column: Some(column),
+                                column_name: None,
+                            };
+                            self.skip_or_fail(error, &raw)?;
+                            continue;
+                        }
+                    };
+                    self.telemetry.add_rows(1);
+                    self.opener.metrics.rows_parsed.add(1);
+                    check_field_count(&record, &self.opener.options)?;
+                    if let Some(filter) = &self.opener.options.row_filter
+                        && !(filter.0)(&RawRecord::new(&record, &self.record_schema))
+                    {
+                        continue;
+                    }
+                    if self.opener.options.on_error != ParseErrorPolicy::NullValue
+                        && let Some(error) = self.invalid_value(&record)
+                    {
+                        self.skip_or_fail(error, record.as_byte_record())?;
+                        continue;
+                    }
+                    self.record_buffer.push(record)
                 }
-                None => {
+                Err(e) => {
+                    check_parse_error_field_count(&e, &self.opener.options)?;
+                    match CsvError::from(e) {
+                        error @ CsvError::Io { .. } => return Err(error.into()),
+                        error => self.skip_or_fail(error, &raw)?,
+                    }
+                }
+                Ok(false) => {
                     self.finished = true;
                     break;
                 }
             }
         }
 
-        if self.record_buffer.is_empty() {
-            return Ok(None);
-        }
+        parse_timer.done();
+        Ok(())
+    }
 
-        // Convert records to batch
-        let batch = self.records_to_batch(&self.record_buffer)?;
-        Ok(Some(batch))
+    /// The error for the first value of `record` that does not parse as its column's type
+    fn invalid_value(&self, record: &csv::StringRecord) -> Option<CsvError> {
+        let options = &self.opener.options;
+        self.opener.schema.fields().iter().enumerate().find_map(|(idx, field)| {
+            let name = field.name();
+            // Parsers and categories reject values themselves
+            if options.is_virtual_column(name)
+                || options.column_parsers.contains_key(name)
+                || options.categorical_columns.contains_key(name)
+            {
+                return None;
+            }
+            let record_idx = match &self.column_map {
+                Some(column_map) => column_map[idx]?,
+                None => idx,
+            };
+            let value = options.prepare_value(name, record.get(record_idx)?);
+            let overflow = *field.data_type() == DataType::Int64 && is_integer_literal(&value);
+            if value.is_empty()
+                || overflow
+                || options.is_null_value(name, &value)
+                || value_parses_as(&value, field, options)
+            {
+                return None;
+            }
+            Some(CsvError::Parse {
+                file: Some(self.location.clone()),
+                line: record.position().map(|p| p.line()),
+                column: Some(record_idx),
+                column_name: Some(name.clone()),
+                value: Some(value.to_string()),
+                message: format!("'{}' is not a valid {}", value, field.data_type()),
+            })
+        })
+    }
+
+    /// Drop the row `raw` rejected with `error` under `SkipRow`, else fail with it
+    ///
+    /// Also fails once the scan has dropped more than `max_errors` rows.
+    fn skip_or_fail(&self, error: CsvError, raw: &csv::ByteRecord) -> Result<()> {
+        let options = &self.opener.options;
+        if options.on_error != ParseErrorPolicy::SkipRow {
+            return Err(error.into());
+        }
+        let rejected = self.opener.rejected_rows.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(max) = options.max_errors
+            && rejected > max
+        {
+            let context = format!("Scan aborted after {} bad rows (max_errors is {})", rejected, max);
+            return Err(error.locate(&self.location, &self.record_schema).into_parse(&context).into());
+        }
+        self.opener.metrics.skipped_rows.add(1);
+        if let Some(sink) = &options.dead_letter {
+            sink.push(RejectedRow {
+                file: self.location.clone(),
+                line: raw.position().map(|p| p.line()),
+                error: error.locate(&self.location, &self.record_schema).to_string(),
+                raw: raw_text(raw, reader_delimiter(options)),
+            })?;
+        }
+        Ok(())
     }
 
     /// Convert CSV records to a RecordBatch (copied from CsvOpener)
@@ -199,13 +1167,8 @@ impl CsvStream {
         let mut columns: Vec<ArrayRef> = Vec::with_capacity(column_indices.len());
 
         for &actual_idx in &column_indices {
-            let field = self.opener.schema.field(actual_idx);
-            let column_data: Vec<Option<&str>> = records
-                .iter()
-                .map(|record| record.get(actual_idx))
-                .collect();
-
-            let array = self.build_array(field, &column_data)?;
+            let (array, mismatches) = self.build_column(actual_idx, records)?;
+            self.opener.metrics.type_mismatches.add(mismatches);
             columns.push(array);
         }
 
@@ -214,43 +1177,481 @@ impl CsvStream {
         })
     }
 
+    /// Build the column of table column `idx` for `records`
+    ///
+    /// Also returns how many values were read as null because they did not parse.
+    fn build_column(&self, idx: usize, records: &[csv::StringRecord]) -> Result<(ArrayRef, usize)> {
+        let field = self.opener.schema.field(idx);
+        let options = &self.opener.options;
+        if options.file_modified_column.as_deref() == Some(field.name()) {
+            let array = TimestampMicrosecondArray::from(vec![self.last_modified; records.len()])
+                .with_timezone("UTC");
+            return Ok((Arc::new(array), 0));
+        }
+        if options.byte_offset_column.as_deref() == Some(field.name()) {
+            let offsets: UInt64Array = records
+                .iter()
+                .map(|record| record.position().map(|p| p.byte()))
+                .collect();
+            return Ok((Arc::new(offsets), 0));
+        }
+        if options.line_number_column.as_deref() == Some(field.name()) {
+            // Preamble lines are stripped before parsing
+            let preamble = options.preamble_rows as u64;
+            let lines: UInt64Array = records
+                .iter()
+                .map(|record| record.position().map(|p| p.line() + preamble))
+                .collect();
+            return Ok((Arc::new(lines), 0));
+        }
+        let record_idx = match &self.column_map {
+            Some(column_map) => column_map[idx],
+            None => Some(idx),
+        };
+        let transformed: Vec<Option<Cow<str>>> = records
+            .iter()
+            .map(|record| {
+                record_idx
+                    .and_then(|idx| record.get(idx))
+                    .map(|v| options.prepare_value(field.name(), v))
+            })
+            .collect();
+        let column_data: Vec<Option<&str>> = transformed
+            .iter()
+            .map(|v| {
+                v.as_deref()
+                    .filter(|v| !options.is_null_value(field.name(), v))
+            })
+            .collect();
+
+        let array = self
+            .build_array(field, &column_data)
+            .map_err(|e| locate_value(e, &column_data, records, record_idx))?;
+        let nulls = column_data.iter().filter(|v| v.is_none()).count();
+        let mismatches = array.null_count().saturating_sub(nulls);
+        Ok((array, mismatches))
+    }
+
     /// Build an Arrow array from column data (copied from CsvOpener)
     fn build_array(&self, field: &Field, data: &[Option<&str>]) -> Result<ArrayRef> {
+        if let Some(parser) = self.opener.options.column_parsers.get(field.name()) {
+            return parser.build_array(data);
+        }
+        if let Some(categories) = self.opener.options.categorical_columns.get(field.name()) {
+            return categories.build_array(field.name(), data);
+        }
+        if let Some(binary) = self.opener.options.binary_columns.get(field.name()) {
+            return binary.build_array(data);
+        }
+
+        // Numbers lose currency symbols and separators before parsing
+        let raw = data;
+        let normalized: Vec<Option<Cow<str>>>;
+        let numbers: Vec<Option<&str>>;
+        let data = if field.data_type().is_numeric() {
+            let options = &self.opener.options;
+            normalized = data.iter().map(|v| v.map(|s| normalize_number(s, options))).collect();
+            numbers = normalized.iter().map(Option::as_deref).collect();
+            &numbers[..]
+        } else {
+            data
+        };
+
         match field.data_type() {
             DataType::Utf8 => {
-                let array: StringArray = data.iter().map(|v| *v).collect();
+                if let Some(&max_length) = self.opener.options.max_string_lengths.get(field.name()) {
+                    let policy = self.opener.options.string_overflow_policy;
+                    let array = data
+                        .iter()
+                        .map(|v| {
+                            v.map(|s| limit_string_length(s, max_length, policy, field.name()))
+                                .transpose()
+                        })
+                        .collect::<Result<StringArray>>()?;
+                    return Ok(Arc::new(array));
+                }
+                let array: StringArray = data.iter().copied().collect();
                 Ok(Arc::new(array))
             }
             DataType::Int64 => {
                 use arrow_array::Int64Array;
-                let array: Int64Array = data
+                let policy = self.opener.options.integer_overflow_policy;
+                let array = data
                     .iter()
-                    .map(|v| v.and_then(|s| s.parse::<i64>().ok()))
-                    .collect();
+                    .zip(raw)
+                    .map(|(v, raw)| match v.map(|s| (s, s.parse::<i64>())) {
+                        None => Ok(None),
+                        Some((_, Ok(i))) => Ok(Some(i)),
+                        Some((s, Err(_)))
+                            if policy == IntegerOverflowPolicy::Error && is_integer_literal(s) =>
+                        {
+                            Err(CsvError::Parse {
+                                file: None,
+                                line: None,
+                                column: None,
+                                column_name: Some(field.name().clone()),
+                                value: raw.map(str::to_string),
+                                message: format!(
+                                    "'{}' is outside the Int64 range; read it as Decimal128 or \
+                                     Float64 with `with_integer_overflow_policy`",
+                                    s
+                                ),
+                            }
+                            .into())
+                        }
+                        Some((_, Err(_))) => Ok(None),
+                    })
+                    .collect::<Result<Int64Array>>()?;
                 Ok(Arc::new(array))
             }
+            DataType::Decimal128(precision, scale) => {
+                use arrow_array::Decimal128Array;
+                let array: Decimal128Array = data
+                    .iter()
+                    .map(|v| v.and_then(|s| parse_decimal128(s, *precision, *scale)))
+                    .collect();
+                Ok(Arc::new(array.with_precision_and_scale(*precision, *scale)?))
+            }
             DataType::Float64 => {
                 use arrow_array::Float64Array;
-                let array: Float64Array = data
+                let array: Float64Array = data.iter().map(|v| v.and_then(|s| s.parse::<f64>().ok())).collect();
+                Ok(Arc::new(array))
+            }
+            DataType::Date32 => {
+                use arrow_array::Date32Array;
+                let options = &self.opener.options;
+                let array: Date32Array = data
                     .iter()
-                    .map(|v| v.and_then(|s| s.parse::<f64>().ok()))
+                    .map(|v| v.and_then(|s| parse_date32(s, field.name(), options)))
                     .collect();
                 Ok(Arc::new(array))
             }
-            DataType::Boolean => {
-                use arrow_array::BooleanArray;
-                let array: BooleanArray = data
+            DataType::Timestamp(TimeUnit::Microsecond, None) => {
+                let options = &self.opener.options;
+                let array: TimestampMicrosecondArray = data
                     .iter()
-                    .map(|v| v.and_then(|s| s.parse::<bool>().ok()))
+                    .map(|v| v.and_then(|s| parse_timestamp(s, field.name(), options)))
                     .collect();
                 Ok(Arc::new(array))
             }
-            _ => {
-                // Default to string for unsupported types
-                let array: StringArray = data.iter().map(|v| *v).collect();
+            DataType::Boolean => {
+                use arrow_array::BooleanArray;
+                let options = &self.opener.options;
+                let array: BooleanArray = data.iter().map(|v| v.and_then(|s| options.parse_bool(s))).collect();
                 Ok(Arc::new(array))
             }
+            _ => {
+                // Cast other types, e.g. narrowed integers or those from a
+                // user-supplied schema, from text; values that do not convert are null
+                let array: StringArray = data.iter().copied().collect();
+                Ok(arrow_cast::cast(&array, field.data_type())?)
+            }
+        }
+    }
+}
+
+/// Whether a non-null value converts to `data_type` the way scans build arrays
+///
+/// Values in columns with a custom parser, and in types scans read as text,
+/// always convert.
+pub(crate) fn value_parses_as(value: &str, field: &Field, options: &CsvFormatOptions) -> bool {
+    if let Some(binary) = options.binary_columns.get(field.name()) {
+        return binary.decode(value).is_some();
+    }
+    let number = || normalize_number(value, options);
+    match field.data_type() {
+        DataType::Int64 => number().parse::<i64>().is_ok(),
+        DataType::Int32 => number().parse::<i32>().is_ok(),
+        DataType::Int16 => number().parse::<i16>().is_ok(),
+        DataType::UInt64 => number().parse::<u64>().is_ok(),
+        DataType::Decimal128(precision, scale) => parse_decimal128(&number(), *precision, *scale).is_some(),
+        DataType::Float64 => number().parse::<f64>().is_ok(),
+        DataType::Boolean => options.parse_bool(value).is_some(),
+        DataType::Date32 => parse_date32(value, field.name(), options).is_some(),
+        DataType::Timestamp(TimeUnit::Microsecond, None) => {
+            parse_timestamp(value, field.name(), options).is_some()
+        }
+        _ => true,
+    }
+}
+
+/// Parse a normalized number into a `Decimal128(precision, scale)` value
+///
+/// Digits past `scale` are truncated.
+fn parse_decimal128(value: &str, precision: u8, scale: i8) -> Option<i128> {
+    if scale == 0 {
+        return value.parse::<i128>().ok();
+    }
+    arrow_cast::parse::parse_decimal::<Decimal128Type>(value, precision, scale).ok()
+}
+
+/// Parse a timestamp into microseconds since the Unix epoch
+///
+/// Uses the column's strftime format when one is set. Otherwise accepts
+/// `YYYY-MM-DD HH:MM:SS` with an optional `T` separator and fraction, RFC 3339
+/// times (converted to UTC) and, with `epoch_timestamps`, epoch seconds or
+/// milliseconds.
+fn parse_timestamp(value: &str, column: &str, options: &CsvFormatOptions) -> Option<i64> {
+    if let Some(format) = options.timestamp_format(column) {
+        let time = chrono::NaiveDateTime::parse_from_str(value, format).ok()?;
+        return Some(time.and_utc().timestamp_micros());
+    }
+    if options.epoch_timestamps
+        && let Some(micros) = parse_epoch(value)
+    {
+        return Some(micros);
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(time.timestamp_micros());
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|time| time.and_utc().timestamp_micros())
+}
+
+/// Epoch seconds (10 digits) or milliseconds (13 digits) as microseconds
+fn parse_epoch(value: &str) -> Option<i64> {
+    if !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let number = value.parse::<i64>().ok()?;
+    match value.len() {
+        10 => number.checked_mul(1_000_000),
+        13 => number.checked_mul(1_000),
+        _ => None,
+    }
+}
+
+/// Parse a date, `YYYY-MM-DD` unless the column has a format, into days since the Unix epoch
+fn parse_date32(value: &str, column: &str, options: &CsvFormatOptions) -> Option<i32> {
+    let format = options.timestamp_format(column).unwrap_or("%Y-%m-%d");
+    let date = chrono::NaiveDate::parse_from_str(value, format).ok()?;
+    let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    i32::try_from(date.signed_duration_since(epoch).num_days()).ok()
+}
+
+/// Rewrite a numeric value into Rust's plain `-1234.5` form
+///
+/// Drops a leading currency symbol, removes thousands separators and turns a
+/// custom decimal separator into `.`. With a decimal comma, `.` groups
+/// thousands as in `1.234,56`. Separators that don't group digits in threes
+/// are left alone.
+pub(crate) fn normalize_number<'a>(value: &'a str, options: &CsvFormatOptions) -> Cow<'a, str> {
+    let (sign, unsigned) = value.split_at(value.len() - value.trim_start_matches(['+', '-']).len());
+    let digits = options
+        .currency_symbols
+        .iter()
+        .find_map(|symbol| unsigned.strip_prefix(symbol.as_str()))
+        .map_or(unsigned, str::trim_start);
+
+    let decimal_separator = options.decimal_separator as char;
+    let (integer, fraction) = match digits.split_once(decimal_separator) {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits, None),
+    };
+    let grouping = match options.thousands_separator {
+        Some(separator) => Some(separator as char),
+        None => (decimal_separator == ',').then_some('.'),
+    };
+    let grouped = grouping.filter(|&separator| has_thousands_groups(integer, separator));
+
+    if digits.len() == unsigned.len() && grouped.is_none() && (fraction.is_none() || decimal_separator == '.') {
+        return Cow::Borrowed(value);
+    }
+    let integer = match grouped {
+        Some(separator) => integer.replace(separator, ""),
+        None => integer.to_string(),
+    };
+    Cow::Owned(match fraction {
+        Some(fraction) => format!("{}{}.{}", sign, integer, fraction),
+        None => format!("{}{}", sign, integer),
+    })
+}
+
+/// Whether `integer` is digits grouped in threes by `separator`, e.g. `12.345.678`
+fn has_thousands_groups(integer: &str, separator: char) -> bool {
+    let digits = integer.trim_start_matches(['+', '-']);
+    let mut groups = digits.split(separator);
+    let first = groups.next().unwrap_or_default();
+    let all_digits = |group: &str| group.bytes().all(|b| b.is_ascii_digit());
+    digits.contains(separator)
+        && (1..=3).contains(&first.len())
+        && all_digits(first)
+        && groups.all(|group| group.len() == 3 && all_digits(group))
+}
+
+/// Maps the table schema's columns onto the columns of one file
+///
+/// Columns are matched by header name, so a file may order them differently
+/// or lack some (read as nulls). Files whose header shares no name with the
+/// table, e.g. one read with a user-supplied schema, are read by position.
+pub(crate) struct SchemaAdapter<'a> {
+    schema: &'a Schema,
+    options: &'a CsvFormatOptions,
+}
+
+impl<'a> SchemaAdapter<'a> {
+    pub(crate) fn new(schema: &'a Schema, options: &'a CsvFormatOptions) -> Self {
+        Self { schema, options }
+    }
+
+    /// Position in `headers` of each table column, or `None` when reading by position
+    pub(crate) fn map_columns(&self, headers: &csv::StringRecord) -> Option<Vec<Option<usize>>> {
+        let found: Vec<&str> = headers.iter().map(|h| column_name(h, self.options)).collect();
+        let column_map: Vec<Option<usize>> = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| {
+                if self.options.is_virtual_column(field.name()) {
+                    return None;
+                }
+                found.iter().position(|name| name == field.name())
+            })
+            .collect();
+
+        let positional = column_map
+            .iter()
+            .enumerate()
+            .all(|(idx, position)| position.is_none_or(|p| p == idx));
+        let read_columns = self
+            .schema
+            .fields()
+            .iter()
+            .filter(|field| !self.options.is_virtual_column(field.name()))
+            .count();
+        let all_present = column_map.iter().flatten().count() == read_columns;
+        if (positional && all_present) || column_map.iter().all(Option::is_none) {
+            return None;
+        }
+        Some(column_map)
+    }
+}
+
+/// Fail with a diff when a file's header does not match the table schema
+fn check_schema_drift(
+    headers: &csv::StringRecord,
+    schema: &Schema,
+    location: &str,
+    options: &CsvFormatOptions,
+) -> Result<()> {
+    let expected: Vec<&str> = schema
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .filter(|name| !options.is_virtual_column(name))
+        .collect();
+    let found: Vec<&str> = headers.iter().map(|h| column_name(h, options)).collect();
+
+    let mut problems = Vec::new();
+    let unexpected: Vec<&str> = found.iter().copied().filter(|c| !expected.contains(c)).collect();
+    if !unexpected.is_empty() {
+        problems.push(format!("unexpected columns [{}]", unexpected.join(", ")));
+    }
+    if !options.union_schema {
+        let missing: Vec<&str> = expected.iter().copied().filter(|c| !found.contains(c)).collect();
+        if !missing.is_empty() {
+            problems.push(format!("missing columns [{}]", missing.join(", ")));
+        }
+        if problems.is_empty() && found != expected {
+            problems.push(format!(
+                "columns reordered: expected [{}], found [{}]",
+                expected.join(", "),
+                found.join(", ")
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(CsvError::Schema(format!("Schema drift in '{}': {}", location, problems.join("; "))).into())
+}
+
+/// Fail with an actionable error when a scan needs more memory than allowed
+fn check_scan_memory(required: usize, location: &str, options: &CsvFormatOptions) -> Result<()> {
+    match options.max_scan_memory {
+        Some(limit) if required > limit => Err(DataFusionError::ResourcesExhausted(format!(
+            "CSV scan of '{}' needs {} bytes (buffered input + current batch), exceeding \
+             the per-scan memory limit of {} bytes; raise the limit with \
+             `with_max_scan_memory`, lower `batch_size`, or split the file",
+            location, required, limit
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Reject records with more fields than the configured maximum
+fn check_field_count(record: &csv::StringRecord, options: &CsvFormatOptions) -> Result<()> {
+    check_field_count_at(record.len(), record.position().map(|p| p.line()), options)
+}
+
+/// Apply the field-count guard to a ragged record reported by the csv reader
+fn check_parse_error_field_count(error: &csv::Error, options: &CsvFormatOptions) -> Result<()> {
+    match error.kind() {
+        csv::ErrorKind::UnequalLengths { pos, len, .. } => {
+            check_field_count_at(*len as usize, pos.as_ref().map(|p| p.line()), options)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_field_count_at(
+    num_fields: usize,
+    line: Option<u64>,
+    options: &CsvFormatOptions,
+) -> Result<()> {
+    match options.max_fields {
+        Some(max_fields) if num_fields > max_fields => Err(CsvError::Parse {
+            file: None,
+            line,
+            column: None,
+            column_name: None,
+            value: None,
+            message: format!(
+                "record has {} fields, exceeding the maximum of {}; \
+                 check the delimiter and quoting of the file",
+                num_fields, max_fields
+            ),
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Apply a column's maximum string length according to the overflow policy
+fn limit_string_length<'a>(
+    value: &'a str,
+    max_length: usize,
+    policy: StringOverflowPolicy,
+    column: &str,
+) -> Result<&'a str> {
+    if value.len() <= max_length {
+        return Ok(value);
+    }
+
+    match policy {
+        StringOverflowPolicy::Truncate => {
+            let mut end = max_length;
+            while !value.is_char_boundary(end) {
+                end -= 1;
+            }
+            Ok(&value[..end])
+        }
+        StringOverflowPolicy::Error => Err(CsvError::Parse {
+            file: None,
+            line: None,
+            column: None,
+            column_name: Some(column.to_string()),
+            value: Some(value.to_string()),
+            message: format!(
+                "value is {} bytes, exceeding the maximum string length of {}",
+                value.len(),
+                max_length
+            ),
         }
+        .into()),
     }
 }
 
@@ -262,6 +1663,7 @@ impl Stream for CsvStream {
             Ok(Some(batch)) => Poll::Ready(Some(Ok(batch))),
             Ok(None) => Poll::Ready(None),
             Err(e) => {
+                self.telemetry.record_error();
                 // Convert DataFusionError to ArrowError
                 let arrow_err = ArrowError::ExternalError(Box::new(e));
                 Poll::Ready(Some(Err(arrow_err)))
@@ -271,40 +1673,230 @@ impl Stream for CsvStream {
 }
 
 
+/// Delimiter used when whitespace runs are rewritten before parsing
+const WHITESPACE_DELIMITER: u8 = b'\t';
+
+/// Create a csv reader builder configured from the format options
+///
+/// The reader's default terminator accepts `\n`, `\r\n` and a bare `\r`, so
+/// classic Mac exports and files mixing line endings parse without configuration.
+pub(crate) fn reader_builder(options: &CsvFormatOptions) -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        .delimiter(reader_delimiter(options))
+        .escape(options.escape)
+        .has_headers(options.has_header);
+    if let Some(terminator) = options.terminator {
+        builder.terminator(csv::Terminator::Any(terminator));
+    }
+    builder
+}
+
+/// Delimiter of the input as seen by the reader, after [`prepare_input`]
+fn reader_delimiter(options: &CsvFormatOptions) -> u8 {
+    if options.whitespace_delimited {
+        WHITESPACE_DELIMITER
+    } else {
+        options.delimiter
+    }
+}
+
+/// Discard the `skip_rows_after_header` rows that follow the header
+///
+/// Skipped rows may have any number of fields.
+pub(crate) fn skip_rows_after_header<R: std::io::Read>(
+    reader: &mut csv::Reader<R>,
+    options: &CsvFormatOptions,
+) -> Result<()> {
+    if options.skip_rows_after_header == 0 {
+        return Ok(());
+    }
+    if options.has_header {
+        reader.byte_headers().map_err(CsvError::from)?;
+    }
+    let mut record = csv::ByteRecord::new();
+    for _ in 0..options.skip_rows_after_header {
+        match reader.read_byte_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) if matches!(e.kind(), csv::ErrorKind::UnequalLengths { .. }) => {}
+            Err(e) => return Err(CsvError::from(e).into()),
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite raw file contents into the form expected by [`reader_builder`]
+///
+/// Most files are passed through untouched; options that a single-byte
+/// delimiter cannot express are handled by rewriting the input first.
+pub(crate) fn prepare_input<'a>(bytes: &'a [u8], options: &CsvFormatOptions) -> Cow<'a, [u8]> {
+    let (_, bytes) = split_preamble(bytes, options);
+    if options.whitespace_delimited {
+        Cow::Owned(collapse_separator_runs(
+            bytes,
+            |b| b == b' ' || b == b'\t',
+            WHITESPACE_DELIMITER,
+            true,
+            options,
+        ))
+    } else if options.collapse_delimiters {
+        let delimiter = options.delimiter;
+        Cow::Owned(collapse_separator_runs(
+            bytes,
+            |b| b == delimiter,
+            delimiter,
+            false,
+            options,
+        ))
+    } else {
+        Cow::Borrowed(bytes)
+    }
+}
+
+/// Split file contents into the `preamble_rows` preamble lines and the rest
+pub(crate) fn split_preamble<'a>(bytes: &'a [u8], options: &CsvFormatOptions) -> (&'a [u8], &'a [u8]) {
+    let Some(last_line) = options.preamble_rows.checked_sub(1) else {
+        return (&[], bytes);
+    };
+    let end = bytes
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b == options.line_terminator())
+        .nth(last_line)
+        .map_or(bytes.len(), |(pos, _)| pos + 1);
+    bytes.split_at(end)
+}
+
+/// Parse the preamble's `key<delimiter>value` lines into metadata
+///
+/// Keys and values are trimmed; lines without a value map to an empty string
+/// and blank lines are ignored.
+pub(crate) fn parse_preamble(bytes: &[u8], options: &CsvFormatOptions) -> Result<HashMap<String, String>> {
+    let (preamble, _) = split_preamble(bytes, options);
+    let mut builder = csv::ReaderBuilder::new();
+    builder.delimiter(options.delimiter).has_headers(false).flexible(true);
+    if let Some(terminator) = options.terminator {
+        builder.terminator(csv::Terminator::Any(terminator));
+    }
+    let mut reader = builder.from_reader(preamble);
+
+    let mut metadata = HashMap::new();
+    for record in reader.records() {
+        let record = record.map_err(CsvError::from)?;
+        let Some(key) = record.get(0).map(str::trim).filter(|k| !k.is_empty()) else {
+            continue;
+        };
+        let value = record.get(1).unwrap_or_default().trim();
+        metadata.insert(key.to_string(), value.to_string());
+    }
+    Ok(metadata)
+}
+
+/// Replace each run of separator bytes outside quoted fields with one delimiter
+///
+/// With `trim_lines`, separators at the start and end of a line are dropped,
+/// as in column-aligned text where values are padded with spaces.
+fn collapse_separator_runs(
+    input: &[u8],
+    is_separator: impl Fn(u8) -> bool,
+    delimiter: u8,
+    trim_lines: bool,
+    options: &CsvFormatOptions,
+) -> Vec<u8> {
+    let is_line_end = |b: u8| match options.terminator {
+        Some(terminator) => b == terminator,
+        None => b == b'\n' || b == b'\r',
+    };
+    let mut output = Vec::with_capacity(input.len());
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut pending_separator = false;
+    let mut at_line_start = true;
+
+    for &b in input {
+        if in_quotes {
+            output.push(b);
+            if escaped {
+                escaped = false;
+            } else if Some(b) == options.escape {
+                escaped = true;
+            } else if b == b'"' {
+                in_quotes = false;
+            }
+        } else if is_separator(b) {
+            if !(trim_lines && at_line_start) {
+                pending_separator = true;
+            }
+        } else if is_line_end(b) {
+            if pending_separator && !trim_lines {
+                output.push(delimiter);
+            }
+            pending_separator = false;
+            at_line_start = true;
+            output.push(b);
+        } else {
+            if pending_separator {
+                output.push(delimiter);
+                pending_separator = false;
+            }
+            at_line_start = false;
+            in_quotes = b == b'"';
+            output.push(b);
+        }
+    }
+
+    if pending_separator && !trim_lines {
+        output.push(delimiter);
+    }
+
+    output
+}
+
 /// Infer schema from CSV file with type detection
 pub async fn infer_schema(
     bytes: &[u8],
     options: &CsvFormatOptions,
 ) -> Result<Schema> {
-    let cursor = Cursor::new(bytes);
-    let mut reader = csv::ReaderBuilder::new()
-        .delimiter(options.delimiter)
-        .has_headers(options.has_header)
-        .from_reader(cursor);
+    let input = prepare_input(bytes, options);
+    let cursor = Cursor::new(input.as_ref());
+    let mut reader = reader_builder(options).from_reader(cursor);
+    skip_rows_after_header(&mut reader, options)?;
 
+    let mut hints: Vec<Option<DataType>> = Vec::new();
     let headers: Vec<String> = if options.has_header {
-        reader
-            .headers()
-            .map_err(|e| DataFusionError::Execution(format!("Failed to read headers: {}", e)))?
-            .iter()
-            .map(|s| s.to_string())
-            .collect()
+        let headers = reader.headers().map_err(CsvError::from)?;
+        check_field_count(headers, options)?;
+        if options.header_type_hints {
+            let mut names = Vec::with_capacity(headers.len());
+            for header in headers {
+                let (name, hint) = split_type_hint(header)?;
+                names.push(name.to_string());
+                hints.push(hint);
+            }
+            names
+        } else {
+            headers.iter().map(|s| s.to_string()).collect()
+        }
     } else {
         // Generate column names if no header
         let first_record = reader.records().next();
         if let Some(Ok(record)) = first_record {
+            check_field_count(&record, options)?;
             (0..record.len())
                 .map(|i| format!("column_{}", i))
                 .collect()
         } else {
-            return Err(DataFusionError::Execution(
-                "Cannot infer schema from empty file".to_string(),
-            ));
+            return Err(CsvError::Schema("Cannot infer schema from empty file".to_string()).into());
         }
     };
 
     // Sample records to infer types
-    let max_records = options.schema_infer_max_rec.unwrap_or(1000);
+    let max_records = if options.two_pass {
+        usize::MAX
+    } else {
+        options.schema_infer_max_rec.unwrap_or(1000)
+    };
     let mut sample_records: Vec<csv::StringRecord> = Vec::new();
 
     for (i, result) in reader.records().enumerate() {
@@ -312,75 +1904,325 @@ pub async fn infer_schema(
             break;
         }
         match result {
-            Ok(record) => sample_records.push(record),
-            Err(_) => continue,
+            Ok(record) => {
+                check_field_count(&record, options)?;
+                sample_records.push(record)
+            }
+            Err(e) => {
+                check_parse_error_field_count(&e, options)?;
+                continue;
+            }
         }
     }
 
+    // Columns typed by a parser, categories or header hint are not inferred
+    let fixed: Vec<Option<DataType>> = headers
+        .iter()
+        .enumerate()
+        .map(|(col_idx, name)| {
+            options
+                .declared_type(name)
+                .or_else(|| hints.get(col_idx).cloned().flatten())
+        })
+        .collect();
+
     // Infer type for each column
-    let num_columns = headers.len();
-    let mut fields: Vec<Field> = Vec::with_capacity(num_columns);
+    let mut types: Vec<DataType> = fixed
+        .iter()
+        .enumerate()
+        .map(|(col_idx, fixed)| match fixed {
+            Some(data_type) => data_type.clone(),
+            None => infer_column_type(&sample_records, col_idx, &headers[col_idx], options),
+        })
+        .collect();
 
-    for (col_idx, name) in headers.into_iter().enumerate() {
-        let data_type = infer_column_type(&sample_records, col_idx);
-        fields.push(Field::new(name, data_type, true));
+    let mut ranges: Vec<Option<(i128, i128)>> = vec![None; headers.len()];
+    if options.narrow_integers {
+        for record in &sample_records {
+            track_integer_ranges(&mut ranges, &headers, record, options);
+        }
     }
 
-    Ok(Schema::new(fields))
-}
-
-/// Infer the data type of a column by sampling values
-fn infer_column_type(records: &[csv::StringRecord], col_idx: usize) -> DataType {
-    let mut has_float = false;
-    let mut has_int = false;
-    let mut has_bool = false;
-    let mut total_values = 0;
-
-    for record in records.iter().take(100) {
-        if let Some(value) = record.get(col_idx) {
-            let value = value.trim();
-            if value.is_empty() {
-                continue;
+    // Check the rest of the file and widen columns whose values don't fit
+    if options.type_promotion {
+        for record in &sample_records {
+            promote_types(&mut types, &fixed, &headers, record, options);
+        }
+        for record in reader.records().flatten() {
+            if types.iter().all(|t| *t == DataType::Utf8) {
+                break;
+            }
+            promote_types(&mut types, &fixed, &headers, &record, options);
+            if options.narrow_integers {
+                track_integer_ranges(&mut ranges, &headers, &record, options);
             }
+        }
+    }
+
+    // Store integer columns in the narrowest type holding every value seen
+    for ((data_type, range), fixed) in types.iter_mut().zip(&ranges).zip(&fixed) {
+        if let (DataType::Int64, Some((min, max)), None) = (&*data_type, range, fixed) {
+            *data_type = narrow_integer_type(*min, *max);
+        }
+    }
 
-            total_values += 1;
+    let fields: Vec<Field> = headers
+        .into_iter()
+        .zip(types)
+        .map(|(name, data_type)| Field::new(name, data_type, true))
+        .collect();
+    Ok(Schema::new(fields).with_metadata(parse_preamble(bytes, options)?))
+}
 
-            // Check if it's a boolean
-            if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
-                has_bool = true;
-                continue;
-            }
+/// Widen each column's smallest and largest integer value with `record`'s
+fn track_integer_ranges(
+    ranges: &mut [Option<(i128, i128)>],
+    headers: &[String],
+    record: &csv::StringRecord,
+    options: &CsvFormatOptions,
+) {
+    for (col_idx, range) in ranges.iter_mut().enumerate() {
+        let Some(value) = record.get(col_idx) else {
+            continue;
+        };
+        // Other values give the column a non-integer type
+        let Ok(value) = options.prepare_value(&headers[col_idx], value).trim().parse::<i128>() else {
+            continue;
+        };
+        *range = Some(range.map_or((value, value), |(min, max)| (min.min(value), max.max(value))));
+    }
+}
+
+/// The narrowest integer type holding every value from `min` to `max`
+fn narrow_integer_type(min: i128, max: i128) -> DataType {
+    let fits = |low: i128, high: i128| low <= min && max <= high;
+    if fits(i16::MIN.into(), i16::MAX.into()) {
+        DataType::Int16
+    } else if fits(i32::MIN.into(), i32::MAX.into()) {
+        DataType::Int32
+    } else if fits(0, u64::MAX.into()) && !fits(i64::MIN.into(), i64::MAX.into()) {
+        DataType::UInt64
+    } else {
+        DataType::Int64
+    }
+}
 
-            // Check if it's a float
-            if value.parse::<f64>().is_ok() {
-                if value.contains('.') {
-                    has_float = true;
-                } else {
-                    has_int = true;
+/// Merge schemas by column name in first-seen order, widening conflicting types
+pub(crate) fn union_schemas<'a>(schemas: impl IntoIterator<Item = &'a Schema>) -> Schema {
+    let mut fields: Vec<Field> = Vec::new();
+    for schema in schemas {
+        for field in schema.fields() {
+            match fields.iter_mut().find(|f| f.name() == field.name()) {
+                Some(existing) => {
+                    let data_type = widen_type(existing.data_type(), field.data_type());
+                    *existing = Field::new(field.name(), data_type, true);
                 }
+                None => fields.push(field.as_ref().clone()),
             }
         }
     }
+    Schema::new(fields)
+}
 
-    // Prioritize type inference: Bool > Float > Int > String
-    if total_values == 0 {
-        return DataType::Utf8;
+/// The narrowest type holding values inferred as either `left` or `right`
+pub(crate) fn widen_type(left: &DataType, right: &DataType) -> DataType {
+    match (left, right) {
+        (a, b) if a == b => a.clone(),
+        // Narrowed integers, see `narrow_integers`
+        (a, b) if is_narrow_integer(a) && is_narrow_integer(b) => {
+            let wider = if a.primitive_width() >= b.primitive_width() { a } else { b };
+            wider.clone()
+        }
+        (DataType::UInt64, b) | (b, DataType::UInt64) if is_narrow_integer(b) || *b == DataType::Int64 => {
+            DataType::Decimal128(20, 0)
+        }
+        (DataType::UInt64, b) | (b, DataType::UInt64) => widen_type(&DataType::Decimal128(20, 0), b),
+        (a, b) | (b, a) if is_narrow_integer(a) => widen_type(&DataType::Int64, b),
+        (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => DataType::Float64,
+        (DataType::Int64, DataType::Decimal128(precision, scale))
+        | (DataType::Decimal128(precision, scale), DataType::Int64) => {
+            // Room for any Int64 value
+            decimal_type(*precision.max(&(19 + *scale as u8)), *scale)
+        }
+        (DataType::Decimal128(p1, s1), DataType::Decimal128(p2, s2)) => {
+            let scale = *s1.max(s2);
+            let integer_digits = (p1 - *s1 as u8).max(p2 - *s2 as u8);
+            decimal_type(integer_digits + scale as u8, scale)
+        }
+        (DataType::Decimal128(..), DataType::Float64) | (DataType::Float64, DataType::Decimal128(..)) => {
+            DataType::Float64
+        }
+        _ => DataType::Utf8,
     }
+}
 
-    if has_bool && !has_int && !has_float {
-        DataType::Boolean
-    } else if has_float {
+/// Whether `data_type` is `Int16` or `Int32`, inferred only with `narrow_integers`
+fn is_narrow_integer(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Int16 | DataType::Int32)
+}
+
+/// `Decimal128(precision, scale)`, or `Float64` beyond 38 digits
+fn decimal_type(precision: u8, scale: i8) -> DataType {
+    if precision > DECIMAL128_MAX_PRECISION {
         DataType::Float64
-    } else if has_int {
+    } else {
+        DataType::Decimal128(precision, scale)
+    }
+}
+
+/// The `Decimal128` type of a fixed-point literal such as `-12.50`
+fn fixed_point_type(value: &str) -> Option<DataType> {
+    let (integer, fraction) = value.trim_start_matches(['+', '-']).split_once('.')?;
+    if fraction.is_empty() || !(integer.bytes().chain(fraction.bytes())).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let integer_digits = integer.trim_start_matches('0').len().max(1);
+    let scale = i8::try_from(fraction.len()).ok()?;
+    Some(decimal_type(u8::try_from(integer_digits + fraction.len()).ok()?, scale))
+}
+
+/// Infer the data type of a column by sampling values
+fn infer_column_type(
+    records: &[csv::StringRecord],
+    col_idx: usize,
+    name: &str,
+    options: &CsvFormatOptions,
+) -> DataType {
+    let limit = if options.two_pass { records.len() } else { 100 };
+    let types: Vec<DataType> = records
+        .iter()
+        .take(limit)
+        .filter_map(|record| value_type(record.get(col_idx)?, name, options))
+        .collect();
+
+    // Promotion and two-pass reads widen the column to hold every sampled value
+    if options.type_promotion || options.two_pass {
+        return widest_type(types);
+    }
+
+    // Otherwise text and booleans in a numeric column, and text in a boolean
+    // one, are read as null rather than widening it
+    let typed: Vec<DataType> = types
+        .iter()
+        .filter(|t| !matches!(t, DataType::Utf8 | DataType::Boolean))
+        .cloned()
+        .collect();
+    if !typed.is_empty() && typed.iter().all(DataType::is_numeric) {
+        widest_type(typed)
+    } else if typed.is_empty() && types.contains(&DataType::Boolean) {
+        DataType::Boolean
+    } else {
+        widest_type(types)
+    }
+}
+
+/// The narrowest type holding every one of `types`, `Utf8` when there are none
+fn widest_type(types: Vec<DataType>) -> DataType {
+    types
+        .into_iter()
+        .reduce(|left, right| widen_type(&left, &right))
+        .unwrap_or(DataType::Utf8)
+}
+
+/// The narrowest inferred type holding `value`, or `None` for nulls
+fn value_type(value: &str, name: &str, options: &CsvFormatOptions) -> Option<DataType> {
+    let value = options.prepare_value(name, value);
+    let value = value.trim();
+    if value.is_empty() || options.is_null_value(name, value) {
+        return None;
+    }
+    if options.parse_bool(value).is_some() {
+        return Some(DataType::Boolean);
+    }
+    if parse_timestamp(value, name, options).is_some() {
+        return Some(DataType::Timestamp(TimeUnit::Microsecond, None));
+    }
+    if options.timestamp_format(name).is_some() && parse_date32(value, name, options).is_some() {
+        return Some(DataType::Date32);
+    }
+    let value = normalize_number(value, options);
+    Some(if value.parse::<i64>().is_ok() {
         DataType::Int64
+    } else if is_integer_literal(&value) {
+        match options.integer_overflow_policy {
+            IntegerOverflowPolicy::Null | IntegerOverflowPolicy::Error => DataType::Int64,
+            IntegerOverflowPolicy::Float64 => DataType::Float64,
+            IntegerOverflowPolicy::Decimal128 if value.trim_start_matches(['+', '-']).len() <= 38 => {
+                DataType::Decimal128(38, 0)
+            }
+            IntegerOverflowPolicy::Decimal128 => DataType::Float64,
+        }
+    } else if let Some(data_type) = fixed_point_type(&value).filter(|_| options.decimal_inference) {
+        data_type
+    } else if value.parse::<f64>().is_ok() {
+        DataType::Float64
     } else {
         DataType::Utf8
+    })
+}
+
+/// Split a `name:type` header into the column name and its type hint
+///
+/// Headers without a `:` have no hint.
+pub(crate) fn split_type_hint(header: &str) -> Result<(&str, Option<DataType>)> {
+    let Some((name, hint)) = header.rsplit_once(':') else {
+        return Ok((header, None));
+    };
+    let data_type = match hint.trim().to_ascii_lowercase().as_str() {
+        "int" | "integer" | "long" | "bigint" => DataType::Int64,
+        "float" | "double" | "number" => DataType::Float64,
+        "bool" | "boolean" => DataType::Boolean,
+        "string" | "str" | "text" => DataType::Utf8,
+        "date" => DataType::Date32,
+        _ => {
+            return Err(DataFusionError::Configuration(format!(
+                "Unknown type hint '{}' in header '{}'; expected int, float, bool, string or date",
+                hint, header
+            )));
+        }
+    };
+    Ok((name, Some(data_type)))
+}
+
+/// The column name of a header, without any type hint
+pub(crate) fn column_name<'a>(header: &'a str, options: &CsvFormatOptions) -> &'a str {
+    if !options.header_type_hints {
+        return header;
+    }
+    split_type_hint(header).map_or(header, |(name, _)| name)
+}
+
+/// Whether `value` is an optionally signed run of decimal digits
+fn is_integer_literal(value: &str) -> bool {
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Widen `types` to hold every value in `record`, skipping `fixed` columns
+fn promote_types(
+    types: &mut [DataType],
+    fixed: &[Option<DataType>],
+    headers: &[String],
+    record: &csv::StringRecord,
+    options: &CsvFormatOptions,
+) {
+    for (col_idx, data_type) in types.iter_mut().enumerate() {
+        if *data_type == DataType::Utf8 || fixed[col_idx].is_some() {
+            continue;
+        }
+        if let Some(value_type) = record
+            .get(col_idx)
+            .and_then(|value| value_type(value, &headers[col_idx], options))
+        {
+            *data_type = widen_type(data_type, &value_type);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file_format::ControlCharPolicy;
 
     #[test]
     fn test_infer_schema() {
@@ -395,4 +2237,756 @@ mod tests {
         assert_eq!(schema.field(1).name(), "age");
         assert_eq!(schema.field(2).name(), "city");
     }
+
+    #[test]
+    fn test_infer_decimal_comma() {
+        let csv_data = b"name;price\nApple;3,14\nPear;2,5";
+        let options = CsvFormatOptions::default()
+            .with_delimiter(b';')
+            .with_decimal_separator(b',');
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let schema = rt.block_on(infer_schema(csv_data, &options)).unwrap();
+
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        assert_eq!(normalize_number("3,14", &options), "3.14");
+        assert_eq!(normalize_number("3.14", &CsvFormatOptions::default()), "3.14");
+        assert_eq!(normalize_number("1.234,56", &options), "1234.56");
+        assert_eq!(normalize_number("-12.345.678", &options), "-12345678");
+        assert_eq!(normalize_number("3.14", &options), "3.14");
+
+        let csv_data = "name;price\nTV;1.234,56\nCable;9,99\n";
+        let (_, batches) = read_in_memory(Bytes::from(csv_data), &options, "prices.csv").unwrap();
+        let prices = batches[0].column(1).as_any().downcast_ref::<arrow_array::Float64Array>().unwrap();
+        assert_eq!(prices.values(), &[1234.56, 9.99]);
+    }
+
+    #[test]
+    fn test_thousands_and_currency() {
+        let csv_data = "item,amount,units\nA,\"$1,234.50\",\"1,000\"\nB,-$5,12\nC,$ 0.99,\"2,5\"\n";
+        let options = CsvFormatOptions::default()
+            .with_thousands_separator(b',')
+            .with_currency_symbols(vec!["$".to_string()]);
+
+        let (schema, batches) = read_in_memory(Bytes::from(csv_data), &options, "ledger.csv").unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        let amounts = batches[0].column(1).as_any().downcast_ref::<arrow_array::Float64Array>().unwrap();
+        assert_eq!(amounts.values(), &[1234.5, -5.0, 0.99]);
+
+        // "2,5" is not grouped in threes, so it is not a number
+        assert_eq!(schema.field(2).data_type(), &DataType::Int64);
+        let units = batches[0].column(2).as_any().downcast_ref::<arrow_array::Int64Array>().unwrap();
+        assert_eq!(units.iter().collect::<Vec<_>>(), vec![Some(1000), Some(12), None]);
+        assert_eq!(normalize_number("1,000", &options), "1000");
+    }
+
+    #[test]
+    fn test_infer_type_promotion() {
+        let mut csv_data = String::from("id,score,code,flag\n");
+        for i in 0..200 {
+            csv_data.push_str(&format!("{},{},{},true\n", i, i, i));
+        }
+        csv_data.push_str("200,3.5,abc,false\n");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // Values past the sample are not checked by default
+        let options = CsvFormatOptions::default();
+        let schema = rt.block_on(infer_schema(csv_data.as_bytes(), &options)).unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(2).data_type(), &DataType::Int64);
+
+        let options = options.with_type_promotion(true);
+        let schema = rt.block_on(infer_schema(csv_data.as_bytes(), &options)).unwrap();
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(3).data_type(), &DataType::Boolean);
+
+        // Text within the sample is read as null by default, and widens with promotion
+        let schema = rt
+            .block_on(infer_schema(b"code\n1\nabc\n2", &CsvFormatOptions::default()))
+            .unwrap();
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        let schema = rt
+            .block_on(infer_schema(b"code\n1\nabc\n2", &options))
+            .unwrap();
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    }
+
+    #[test]
+    fn test_integer_overflow_policy() {
+        let csv_data = b"id,account\n1,12345678901234567890123\n2,-42";
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let infer = |policy| {
+            let options = CsvFormatOptions::default().with_integer_overflow_policy(policy);
+            let schema = rt.block_on(infer_schema(csv_data, &options)).unwrap();
+            schema.field(1).data_type().clone()
+        };
+
+        assert_eq!(infer(IntegerOverflowPolicy::Null), DataType::Int64);
+        assert_eq!(infer(IntegerOverflowPolicy::Float64), DataType::Float64);
+        assert_eq!(infer(IntegerOverflowPolicy::Decimal128), DataType::Decimal128(38, 0));
+        assert!(is_integer_literal("+007"));
+        assert!(!is_integer_literal("-"));
+        assert!(!is_integer_literal("1e30"));
+
+        // Decimal columns keep every digit
+        let options = CsvFormatOptions::default()
+            .with_integer_overflow_policy(IntegerOverflowPolicy::Decimal128);
+        let (_, batches) = read_in_memory(Bytes::from_static(csv_data), &options, "accounts.csv").unwrap();
+        let accounts = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<arrow_array::Decimal128Array>()
+            .unwrap();
+        assert_eq!(accounts.value(0), 12345678901234567890123);
+        assert_eq!(accounts.value(1), -42);
+
+        let options = CsvFormatOptions::default()
+            .with_integer_overflow_policy(IntegerOverflowPolicy::Error);
+        let err = read_in_memory(Bytes::from_static(csv_data), &options, "accounts.csv").unwrap_err();
+        let message = "line 2, field 2 ('account') of 'accounts.csv': '12345678901234567890123' is outside";
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+
+    #[test]
+    fn test_skip_rows_after_header() {
+        let csv_data = b"station,temp\nid,celsius,unused\nA,21.5\nB,19\n";
+        let options = CsvFormatOptions::default().with_skip_rows_after_header(1);
+
+        let (schema, batches) = read_in_memory(Bytes::from_static(csv_data), &options, "weather.csv").unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        assert_eq!(batches[0].num_rows(), 2);
+        let station = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(station.value(0), "A");
+    }
+
+    #[test]
+    fn test_byte_offset_column() {
+        let csv_data = b"id,note\n1,\"two\nlines\"\n2,x\n";
+        let options = CsvFormatOptions::default().with_byte_offset_column("_offset");
+        let schema = futures::executor::block_on(infer_schema(csv_data, &options)).unwrap();
+        let schema = options.with_virtual_columns(Arc::new(schema));
+        let opener = CsvOpener::new(
+            options.clone(),
+            schema,
+            None,
+            Arc::new(object_store::memory::InMemory::new()),
+        );
+
+        let mut stream = CsvStream::new(Bytes::from_static(csv_data), opener, "notes.csv".to_string()).unwrap();
+        let batch = stream.read_next_batch().unwrap().unwrap();
+        let offsets = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(offsets.values().to_vec(), vec![8, 22]);
+        assert_eq!(&csv_data[22..25], b"2,x");
+    }
+
+    #[test]
+    fn test_line_number_column() {
+        let csv_data = b"exported 2024-06-01\nid,note\n1,\"two\nlines\"\n2,x\n";
+        let options = CsvFormatOptions::default()
+            .with_preamble_rows(1)
+            .with_line_number_column("_line_number");
+        let schema = futures::executor::block_on(infer_schema(csv_data, &options)).unwrap();
+        let schema = options.with_virtual_columns(Arc::new(schema));
+        let opener = CsvOpener::new(
+            options.clone(),
+            schema,
+            None,
+            Arc::new(object_store::memory::InMemory::new()),
+        );
+
+        let mut stream = CsvStream::new(Bytes::from_static(csv_data), opener, "notes.csv".to_string()).unwrap();
+        let batch = stream.read_next_batch().unwrap().unwrap();
+        let lines = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(lines.values().to_vec(), vec![3, 5]);
+    }
+
+    /// Store whose first `drops` downloads break off halfway through the body
+    #[derive(Debug)]
+    struct FlakyStore {
+        inner: object_store::memory::InMemory,
+        drops: Mutex<usize>,
+        ranges: Mutex<Vec<Option<GetRange>>>,
+    }
+
+    impl std::fmt::Display for FlakyStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "FlakyStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for FlakyStore {
+        async fn put_opts(
+            &self,
+            location: &object_store::path::Path,
+            payload: object_store::PutPayload,
+            opts: object_store::PutOptions,
+        ) -> object_store::Result<object_store::PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &object_store::path::Path,
+            opts: object_store::PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn object_store::MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &object_store::path::Path,
+            options: GetOptions,
+        ) -> object_store::Result<object_store::GetResult> {
+            self.ranges.lock().unwrap().push(options.range.clone());
+            let result = self.inner.get_opts(location, options).await?;
+            {
+                let mut drops = self.drops.lock().unwrap();
+                if *drops == 0 {
+                    return Ok(result);
+                }
+                *drops -= 1;
+            }
+
+            let (meta, range, attributes) = (result.meta.clone(), result.range.clone(), result.attributes.clone());
+            let mut bytes = result.bytes().await?;
+            let half = bytes.split_to(bytes.len() / 2);
+            let broken = futures::stream::iter([
+                Ok(half),
+                Err(object_store::Error::Generic {
+                    store: "FlakyStore",
+                    source: "connection reset".into(),
+                }),
+            ]);
+            Ok(object_store::GetResult {
+                payload: object_store::GetResultPayload::Stream(Box::pin(broken)),
+                meta,
+                range,
+                attributes,
+            })
+        }
+
+        async fn delete(&self, location: &object_store::path::Path) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&object_store::path::Path>,
+        ) -> futures::stream::BoxStream<'_, object_store::Result<object_store::ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&object_store::path::Path>,
+        ) -> object_store::Result<object_store::ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(
+            &self,
+            from: &object_store::path::Path,
+            to: &object_store::path::Path,
+        ) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(
+            &self,
+            from: &object_store::path::Path,
+            to: &object_store::path::Path,
+        ) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_dropped_download() {
+        let contents = b"id,name\n1,Alice\n2,Bob\n3,Carol\n";
+        let location = object_store::path::Path::from("people.csv");
+        let flaky = Arc::new(FlakyStore {
+            inner: object_store::memory::InMemory::new(),
+            drops: Mutex::new(2),
+            ranges: Mutex::new(Vec::new()),
+        });
+        flaky.put(&location, Bytes::from_static(contents).into()).await.unwrap();
+        let store: Arc<dyn ObjectStore> = flaky.clone();
+
+        let object = fetch_object(&store, &location).await.unwrap();
+        assert_eq!(object.bytes.as_ref(), contents);
+        let ranges = flaky.ranges.lock().unwrap().clone();
+        assert_eq!(
+            ranges,
+            vec![None, Some(GetRange::Offset(15)), Some(GetRange::Offset(22))]
+        );
+
+        // Giving up after the last resume surfaces the store error
+        *flaky.drops.lock().unwrap() = MAX_DOWNLOAD_RESUMES + 1;
+        let err = fetch_object(&store, &location).await.err().unwrap();
+        assert!(err.to_string().contains("connection reset"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_reads() {
+        let contents = b"Instrument,XRF-200\nid,name\n1,Alice\n2,Bob\n3,Carol\n";
+        let location = object_store::path::Path::from("people.csv");
+        let flaky = Arc::new(FlakyStore {
+            inner: object_store::memory::InMemory::new(),
+            drops: Mutex::new(1),
+            ranges: Mutex::new(Vec::new()),
+        });
+        flaky.put(&location, Bytes::from_static(contents).into()).await.unwrap();
+        let store: Arc<dyn ObjectStore> = flaky.clone();
+
+        let options = CsvFormatOptions::default()
+            .with_streaming_reads(true)
+            .with_preamble_rows(1);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let opener = CsvOpener::new(options, schema, Some(vec![1]), store.clone()).with_batch_size(2);
+        let file_meta = FileMeta::from(store.head(&location).await.unwrap());
+        let stream = opener.open(file_meta).unwrap().await.unwrap();
+        let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+
+        // Parsed in batches as the body arrived, across the resumed download
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).collect::<Vec<_>>(), vec![2, 1]);
+        let names: Vec<&str> = batches
+            .iter()
+            .flat_map(|batch| batch.column(0).as_any().downcast_ref::<StringArray>().unwrap().iter())
+            .map(Option::unwrap)
+            .collect();
+        assert_eq!(names, vec!["Alice", "Bob", "Carol"]);
+        assert_eq!(flaky.ranges.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_on_error_policy() {
+        let csv_data = Bytes::from_static(b"id,score\n1,10\n2,abc\n3,\n4,40,extra\n5,50\n");
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("score", DataType::Int64, true),
+        ]));
+        let scan = |policy| {
+            let options = CsvFormatOptions::default().with_on_error(policy);
+            let mut batches = Vec::new();
+            scan_in_memory(csv_data.clone(), schema.clone(), &options, "scores.csv", |batch| {
+                batches.push(batch);
+                Ok(())
+            })
+            .map(|_| batches)
+        };
+
+        // Malformed records fail unless skipped
+        let err = scan(ParseErrorPolicy::NullValue).unwrap_err();
+        assert!(err.to_string().contains("at line 5"), "{}", err);
+
+        // Empty values are missing, not bad
+        let batches = scan(ParseErrorPolicy::SkipRow).unwrap();
+        let ids = batches[0].column(0).as_any().downcast_ref::<arrow_array::Int64Array>().unwrap();
+        assert_eq!(ids.values(), &[1, 3, 5]);
+
+        let err = scan(ParseErrorPolicy::Fail).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("at line 3, field 2 ('score') of 'scores.csv': 'abc' is not a valid Int64"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_timestamp_inference() {
+        let csv_data = "local,rfc3339,epoch_s,epoch_ms,mixed\n\
+            2024-01-31 10:00:00,2024-01-31T12:00:00+02:00,1706695200,1706695200000,2024-01-31 10:00:00\n\
+            2024-01-31T10:00:00.5,2024-01-31T10:00:00Z,1706695200,1706695200000,noon\n";
+        let timestamp = DataType::Timestamp(TimeUnit::Microsecond, None);
+        let types = |options: &CsvFormatOptions| {
+            let (schema, batches) = read_in_memory(Bytes::from(csv_data), options, "times.csv").unwrap();
+            let types: Vec<DataType> = schema.fields().iter().map(|f| f.data_type().clone()).collect();
+            (types, batches)
+        };
+
+        // Epoch integers stay integers unless asked for
+        let (inferred, _) = types(&CsvFormatOptions::default());
+        assert_eq!(
+            inferred,
+            vec![timestamp.clone(), timestamp.clone(), DataType::Int64, DataType::Int64, DataType::Utf8]
+        );
+
+        let (inferred, batches) = types(&CsvFormatOptions::default().with_epoch_timestamps(true));
+        assert_eq!(&inferred[..4], &vec![timestamp; 4][..]);
+        let micros = |column: usize| {
+            let array = batches[0].column(column).as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
+            array.values().to_vec()
+        };
+        assert_eq!(micros(0), vec![1_706_695_200_000_000, 1_706_695_200_500_000]);
+        // Offsets are converted to UTC
+        assert_eq!(micros(1), vec![1_706_695_200_000_000; 2]);
+        assert_eq!(micros(2), vec![1_706_695_200_000_000; 2]);
+        assert_eq!(micros(3), vec![1_706_695_200_000_000; 2]);
+    }
+
+    #[test]
+    fn test_timestamp_format() {
+        let csv_data = "booked,day,note\n31/01/2024 10:00,31/01/2024,x\n01/02/2024 23:30,01/02/2024,y\n";
+        let options = CsvFormatOptions::default()
+            .with_timestamp_format("%d/%m/%Y %H:%M")
+            .with_column_timestamp_format("day", "%d/%m/%Y");
+
+        let (schema, batches) = read_in_memory(Bytes::from(csv_data), &options, "bookings.csv").unwrap();
+        assert_eq!(schema.field(0).data_type(), &DataType::Timestamp(TimeUnit::Microsecond, None));
+        assert_eq!(schema.field(1).data_type(), &DataType::Date32);
+        assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+
+        let booked = batches[0].column(0).as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
+        assert_eq!(booked.value(1), 1_706_830_200_000_000);
+        let day = batches[0].column(1).as_any().downcast_ref::<arrow_array::Date32Array>().unwrap();
+        assert_eq!(day.values(), &[19753, 19754]);
+    }
+
+    #[test]
+    fn test_decimal_inference() {
+        let csv_data = "sku,price,qty,rate\nA,19.99,3,1e-3\nB,5,1,0.5\nC,-0.10,2,0.25\n";
+        let options = CsvFormatOptions::default().with_decimal_inference(true);
+
+        let (schema, batches) = read_in_memory(Bytes::from(csv_data), &options, "prices.csv").unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Decimal128(21, 2));
+        assert_eq!(schema.field(2).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(3).data_type(), &DataType::Float64);
+
+        let prices = batches[0].column(1).as_any().downcast_ref::<arrow_array::Decimal128Array>().unwrap();
+        assert_eq!(prices.values(), &[1999, 500, -10]);
+
+        // Off by default
+        let (schema, _) = read_in_memory(Bytes::from(csv_data), &CsvFormatOptions::default(), "prices.csv").unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+    }
+
+    #[test]
+    fn test_narrow_integers() {
+        let csv_data = "small,medium,large,huge,negative_huge\n\
+            1,70000,5000000000,18446744073709551615,-1\n\
+            -300,-2,1,0,99999999999999999999\n";
+        let types = |options: &CsvFormatOptions| {
+            let (schema, batches) = read_in_memory(Bytes::from(csv_data), options, "wide.csv").unwrap();
+            let types: Vec<DataType> = schema.fields().iter().map(|f| f.data_type().clone()).collect();
+            (types, batches)
+        };
+
+        assert_eq!(types(&CsvFormatOptions::default()).0, vec![DataType::Int64; 5]);
+
+        let (inferred, batches) = types(&CsvFormatOptions::default().with_narrow_integers(true));
+        assert_eq!(
+            inferred,
+            vec![DataType::Int16, DataType::Int32, DataType::Int64, DataType::UInt64, DataType::Int64]
+        );
+        let small = batches[0].column(0).as_any().downcast_ref::<arrow_array::Int16Array>().unwrap();
+        assert_eq!(small.values(), &[1, -300]);
+        let huge = batches[0].column(3).as_any().downcast_ref::<arrow_array::UInt64Array>().unwrap();
+        assert_eq!(huge.values(), &[u64::MAX, 0]);
+
+        // Files narrowed differently still combine
+        assert_eq!(widen_type(&DataType::Int16, &DataType::Int32), DataType::Int32);
+        assert_eq!(widen_type(&DataType::UInt64, &DataType::Int16), DataType::Decimal128(20, 0));
+        assert_eq!(widen_type(&DataType::Int32, &DataType::Float64), DataType::Float64);
+    }
+
+    #[test]
+    fn test_boolean_values() {
+        let csv_data = "active,flag,code\nYes,Y,1\nno,N,0\n,n,1\n";
+        let options = CsvFormatOptions::default()
+            .with_true_values(vec!["yes".to_string(), "y".to_string()])
+            .with_false_values(vec!["no".to_string(), "n".to_string()]);
+
+        let (schema, batches) = read_in_memory(Bytes::from(csv_data), &options, "flags.csv").unwrap();
+        assert_eq!(schema.field(0).data_type(), &DataType::Boolean);
+        assert_eq!(schema.field(1).data_type(), &DataType::Boolean);
+        assert_eq!(schema.field(2).data_type(), &DataType::Int64);
+
+        let active = batches[0].column(0).as_any().downcast_ref::<arrow_array::BooleanArray>().unwrap();
+        assert_eq!(active.iter().collect::<Vec<_>>(), vec![Some(true), Some(false), None]);
+        let flag = batches[0].column(1).as_any().downcast_ref::<arrow_array::BooleanArray>().unwrap();
+        assert_eq!(flag.iter().collect::<Vec<_>>(), vec![Some(true), Some(false), Some(false)]);
+
+        // The defaults ignore case too
+        let (_, batches) =
+            read_in_memory(Bytes::from("ok\nTRUE\nFalse\n"), &CsvFormatOptions::default(), "ok.csv").unwrap();
+        let ok = batches[0].column(0).as_any().downcast_ref::<arrow_array::BooleanArray>().unwrap();
+        assert_eq!(ok.iter().collect::<Vec<_>>(), vec![Some(true), Some(false)]);
+    }
+
+    #[test]
+    fn test_header_type_hints() {
+        let csv_data = b"age:int,name:string,joined:date,score\n30,007,2024-02-29,1.5\n";
+        let options = CsvFormatOptions::default().with_header_type_hints(true);
+
+        let (schema, batches) = read_in_memory(Bytes::from_static(csv_data), &options, "people.csv").unwrap();
+        let fields: Vec<(&str, &DataType)> = schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("age", &DataType::Int64),
+                ("name", &DataType::Utf8),
+                ("joined", &DataType::Date32),
+                ("score", &DataType::Float64),
+            ]
+        );
+        let joined = batches[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<arrow_array::Date32Array>()
+            .unwrap();
+        assert_eq!(joined.value(0), 19782);
+
+        let err = split_type_hint("when:timestamp").unwrap_err();
+        assert!(err.to_string().contains("Unknown type hint 'timestamp'"), "{}", err);
+
+        // Without the option, the suffix is part of the name
+        let schema = read_in_memory(Bytes::from_static(csv_data), &CsvFormatOptions::default(), "p.csv")
+            .unwrap()
+            .0;
+        assert_eq!(schema.field(0).name(), "age:int");
+    }
+
+    #[test]
+    fn test_whitespace_delimited() {
+        let csv_data = b"  PID   TTY      CMD\n  101   pts/0    \"bash -l\"\n 2042   pts/1    top  \n";
+        let options = CsvFormatOptions::default().with_whitespace_delimited(true);
+
+        let input = prepare_input(csv_data, &options);
+        assert_eq!(
+            input.as_ref(),
+            b"PID\tTTY\tCMD\n101\tpts/0\t\"bash -l\"\n2042\tpts/1\ttop\n"
+        );
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let schema = rt.block_on(infer_schema(csv_data, &options)).unwrap();
+        assert_eq!(schema.fields().len(), 3);
+        assert_eq!(schema.field(0).name(), "PID");
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+    }
+
+    #[test]
+    fn test_collapse_delimiters() {
+        let csv_data = b"a,,b\n1,,,\"x,,y\"\n,2,3,\n";
+        let options = CsvFormatOptions::default().with_collapse_delimiters(true);
+
+        // Runs collapse to one separator; quoted text and edge fields are kept
+        let input = prepare_input(csv_data, &options);
+        assert_eq!(input.as_ref(), b"a,b\n1,\"x,,y\"\n,2,3,\n");
+    }
+
+    #[test]
+    fn test_escape() {
+        let csv_data = b"id,comment\n1,\"say \\\"hi\\\",,, ok\"\n2,\"a\"\"b\"\n";
+        let options = CsvFormatOptions::default().with_escape(Some(b'\\'));
+
+        let (schema, batches) = read_in_memory(Bytes::from_static(csv_data), &options, "notes.csv").unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+        let comments = batches[0].column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(comments.value(0), "say \"hi\",,, ok");
+        assert_eq!(comments.value(1), "a\"b");
+
+        // An escaped quote does not end the field when collapsing delimiters
+        let options = options.with_collapse_delimiters(true);
+        assert_eq!(prepare_input(csv_data, &options).as_ref(), csv_data);
+    }
+
+    #[test]
+    fn test_infer_with_null_values() {
+        let csv_data = b"station,temperature,notes\nA,-999,NA\nB,21.5,ok\nC,NA,low";
+        let options = CsvFormatOptions::default()
+            .with_null_values(vec!["NA".to_string()])
+            .with_column_null_values("temperature", vec!["-999".to_string()]);
+
+        assert!(options.is_null_value("notes", "NA"));
+        assert!(options.is_null_value("temperature", "-999"));
+        assert!(!options.is_null_value("notes", "-999"));
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let schema = rt.block_on(infer_schema(csv_data, &options)).unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+    }
+
+    #[test]
+    fn test_null_regex() {
+        let csv_data = "score\n10\nNA\nn/a\n-\n20\n";
+        let options = CsvFormatOptions::default().with_null_regex(r"^(?i:NA|n/a|-)$").unwrap();
+
+        let (schema, batches) = read_in_memory(Bytes::from(csv_data), &options, "scores.csv").unwrap();
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        let scores = batches[0].column(0).as_any().downcast_ref::<arrow_array::Int64Array>().unwrap();
+        assert_eq!(scores.iter().collect::<Vec<_>>(), vec![Some(10), None, None, None, Some(20)]);
+
+        assert!(CsvFormatOptions::default().with_null_regex("(").is_err());
+    }
+
+    #[test]
+    fn test_value_transforms() {
+        let csv_data = b"item,weight\nApple,12 kg\nMelon,3 kg[1]";
+        let options = CsvFormatOptions::default()
+            .with_value_transform("weight", r"\[\d+\]$", "")
+            .unwrap()
+            .with_value_transform("weight", r"\s*kg$", "")
+            .unwrap();
+
+        assert_eq!(options.transform_value("weight", "3 kg[1]"), "3");
+        assert_eq!(options.transform_value("item", "3 kg"), "3 kg");
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let schema = rt.block_on(infer_schema(csv_data, &options)).unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+
+        assert!(CsvFormatOptions::default().with_value_transform("weight", "(", "").is_err());
+    }
+
+    #[test]
+    fn test_column_parser() {
+        use arrow_array::{Array, Int32Array};
+        use datafusion_common::ScalarValue;
+
+        // Domain codes like "Q3" mapped to their quarter number
+        let options = CsvFormatOptions::default().with_column_parser(
+            "quarter",
+            DataType::Int32,
+            |s| match s.strip_prefix('Q').and_then(|q| q.parse::<i32>().ok()) {
+                Some(q) => Ok(ScalarValue::Int32(Some(q))),
+                None => Err(DataFusionError::Execution(format!("Invalid quarter '{}'", s))),
+            },
+        );
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let schema = rt
+            .block_on(infer_schema(b"year,quarter\n2024,Q1", &options))
+            .unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::Int32);
+
+        let parser = &options.column_parsers["quarter"];
+        let array = parser.build_array(&[Some("Q3"), None]).unwrap();
+        let array = array.as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(array.value(0), 3);
+        assert!(array.is_null(1));
+
+        assert!(parser.build_array(&[Some("third")]).is_err());
+    }
+
+    #[test]
+    fn test_binary_columns() {
+        use crate::file_format::BinaryEncoding;
+        use arrow_array::{Array, BinaryArray, FixedSizeBinaryArray};
+
+        let csv_data = "id,digest,payload\n1,0xDEADBEEF,aGVsbG8=\n2,deadbe,not base64!\n";
+        let options = CsvFormatOptions::default()
+            .with_fixed_size_binary_column("digest", BinaryEncoding::Hex, 4)
+            .with_binary_column("payload", BinaryEncoding::Base64);
+
+        let (schema, batches) = read_in_memory(Bytes::from(csv_data), &options, "blobs.csv").unwrap();
+        assert_eq!(schema.field(1).data_type(), &DataType::FixedSizeBinary(4));
+        assert_eq!(schema.field(2).data_type(), &DataType::Binary);
+
+        // Values of the wrong width or encoding are null
+        let digest = batches[0].column(1).as_any().downcast_ref::<FixedSizeBinaryArray>().unwrap();
+        assert_eq!(digest.value(0), &[0xde, 0xad, 0xbe, 0xef]);
+        assert!(digest.is_null(1));
+        let payload = batches[0].column(2).as_any().downcast_ref::<BinaryArray>().unwrap();
+        assert_eq!(payload.value(0), b"hello");
+        assert!(payload.is_null(1));
+
+        // Strict mode rejects them instead
+        let options = options.with_on_error(ParseErrorPolicy::Fail);
+        let err = read_in_memory(Bytes::from(csv_data), &options, "blobs.csv").unwrap_err();
+        assert!(err.to_string().contains("'deadbe' is not a valid FixedSizeBinary(4)"), "{}", err);
+    }
+
+    #[test]
+    fn test_control_char_policy() {
+        let options = CsvFormatOptions::default().with_control_chars(ControlCharPolicy::Strip);
+        assert_eq!(options.prepare_value("c", "line\r\u{7}end"), "lineend");
+
+        let options =
+            CsvFormatOptions::default().with_control_chars(ControlCharPolicy::Replace(' '));
+        assert_eq!(options.prepare_value("c", "a\tb\r"), "a b ");
+
+        let options = CsvFormatOptions::default();
+        assert_eq!(options.prepare_value("c", "a\tb"), "a\tb");
+    }
+
+    #[test]
+    fn test_cr_line_endings() {
+        // Classic Mac exports terminate lines with a bare `\r`
+        let csv_data = b" id  score\r  1   10\r  2   20\r";
+        let options = CsvFormatOptions::default().with_whitespace_delimited(true);
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let schema = rt.block_on(infer_schema(csv_data, &options)).unwrap();
+
+        assert_eq!(schema.fields().len(), 2);
+        assert_eq!(schema.field(1).name(), "score");
+        assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+    }
+
+    #[test]
+    fn test_custom_terminator() {
+        // Mainframe export ending records with `~`; newlines are part of values
+        let csv_data = b"Site,Plant 7~id,note~1,first\nline~2,second~";
+        let options = CsvFormatOptions::default()
+            .with_terminator(Some(b'~'))
+            .with_preamble_rows(1);
+
+        let (schema, batches) = read_in_memory(Bytes::from_static(csv_data), &options, "export.csv").unwrap();
+        assert_eq!(schema.metadata().get("Site").map(String::as_str), Some("Plant 7"));
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        let notes = batches[0].column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(notes.iter().collect::<Vec<_>>(), vec![Some("first\nline"), Some("second")]);
+    }
+
+    #[test]
+    fn test_max_fields() {
+        let csv_data = b"a,b\n1,2\n3,4,5,6,7,8";
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let options = CsvFormatOptions::default().with_max_fields(Some(4));
+        let err = rt.block_on(infer_schema(csv_data, &options)).unwrap_err();
+        assert!(err.to_string().contains("at line 3: record has 6 fields"), "{}", err);
+
+        let options = CsvFormatOptions::default().with_max_fields(Some(1));
+        assert!(rt.block_on(infer_schema(csv_data, &options)).is_err());
+    }
+
+    #[test]
+    fn test_limit_string_length() {
+        let truncate = StringOverflowPolicy::Truncate;
+        assert_eq!(limit_string_length("abc", 5, truncate, "c").unwrap(), "abc");
+        assert_eq!(limit_string_length("abcdef", 3, truncate, "c").unwrap(), "abc");
+        // "é" is two bytes, so cutting at byte 2 falls back to the boundary at 1
+        assert_eq!(limit_string_length("aé", 2, truncate, "c").unwrap(), "a");
+
+        let err = limit_string_length("abcdef", 3, StringOverflowPolicy::Error, "notes").unwrap_err();
+        match CsvError::find(&err) {
+            Some(CsvError::Parse { column_name, .. }) => assert_eq!(column_name.as_deref(), Some("notes")),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,201 @@
+//! Schema sidecar files
+//!
+//! A sidecar is a JSON file stored next to a CSV file and named after it with
+//! a `.schema.json` suffix (`orders.csv.schema.json`). With
+//! [`CsvFormatOptions::with_schema_sidecar`] it fixes the table's columns
+//! instead of inference:
+//!
+//! ```json
+//! {
+//!   "columns": [
+//!     {"name": "id", "type": "Int64", "nullable": false},
+//!     {"name": "zip", "type": "Utf8"}
+//!   ]
+//! }
+//! ```
+//!
+//! `nullable` defaults to true. Types use Arrow's names; scans read `Utf8`,
+//! `Int64`, `Float64` and `Boolean`, and a column with a custom parser or
+//! categories must use the type they produce.
+
+use std::sync::Arc;
+
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload};
+use serde_json::{json, Value};
+
+use crate::error::CsvError;
+use crate::file_format::{infer_schema_from_store, CsvFormatOptions};
+use crate::file_source::list_matching_files;
+
+/// Suffix appended to a CSV file's name to locate its sidecar
+pub(crate) const SIDECAR_SUFFIX: &str = ".schema.json";
+
+/// Location of the sidecar for the CSV file at `location`
+fn sidecar_location(location: &Path) -> Result<Path> {
+    Path::parse(format!("{}{}", location, SIDECAR_SUFFIX))
+        .map_err(|e| DataFusionError::External(Box::new(e)))
+}
+
+/// Read the sidecar of the CSV file at `location`, if it has one
+pub(crate) async fn read_sidecar(
+    store: &Arc<dyn ObjectStore>,
+    location: &Path,
+    options: &CsvFormatOptions,
+) -> Result<Option<Schema>> {
+    let sidecar = sidecar_location(location)?;
+    let bytes = match store.get(&sidecar).await {
+        Ok(result) => result.bytes().await.map_err(CsvError::Store)?,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(e) => return Err(CsvError::Store(e).into()),
+    };
+    parse_sidecar(&bytes, &sidecar, options).map(Some)
+}
+
+/// Parse sidecar JSON into a schema, checking that scans can read its types
+fn parse_sidecar(bytes: &[u8], sidecar: &Path, options: &CsvFormatOptions) -> Result<Schema> {
+    let invalid = |reason: String| {
+        DataFusionError::Configuration(format!("Invalid schema sidecar '{}': {}", sidecar, reason))
+    };
+
+    let json: Value = serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+    let columns = json
+        .get("columns")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("expected a \"columns\" array".to_string()))?;
+
+    let mut fields = Vec::with_capacity(columns.len());
+    for column in columns {
+        let name = column
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid("every column needs a \"name\"".to_string()))?;
+        let type_name = column
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid(format!("column '{}' needs a \"type\"", name)))?;
+        let data_type: DataType = type_name
+            .parse()
+            .map_err(|_| invalid(format!("column '{}' has unknown type '{}'", name, type_name)))?;
+
+        let readable = match options.declared_type(name) {
+            Some(declared) => declared == data_type,
+            None => matches!(
+                data_type,
+                DataType::Utf8
+                    | DataType::Int64
+                    | DataType::Float64
+                    | DataType::Boolean
+                    | DataType::Decimal128(..)
+                    | DataType::Timestamp(TimeUnit::Microsecond, None)
+            ),
+        };
+        if !readable {
+            return Err(invalid(format!("column '{}' cannot be read as {}", name, data_type)));
+        }
+
+        let nullable = match column.get("nullable") {
+            None => true,
+            Some(value) => value.as_bool().ok_or_else(|| {
+                invalid(format!("\"nullable\" of column '{}' must be true or false", name))
+            })?,
+        };
+        fields.push(Field::new(name, data_type, nullable));
+    }
+    Ok(Schema::new(fields))
+}
+
+/// Render `schema` as sidecar JSON
+fn sidecar_json(schema: &Schema) -> Result<String> {
+    let columns: Vec<Value> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            json!({
+                "name": field.name(),
+                "type": field.data_type().to_string(),
+                "nullable": field.is_nullable(),
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({ "columns": columns }))
+        .map_err(|e| DataFusionError::External(Box::new(e)))
+}
+
+/// Infer the schema of the CSV files at `path` and store it as a sidecar
+///
+/// The sidecar is written next to the first file, where reads look for it.
+pub(crate) async fn write_sidecar(
+    state: &SessionState,
+    path: &str,
+    options: CsvFormatOptions,
+) -> Result<()> {
+    let (_, store, files, mut options) = list_matching_files(state, path, &options).await?;
+    let Some(first) = files.first() else {
+        return Err(DataFusionError::Plan(format!("No CSV files match '{}'", path)));
+    };
+
+    options.schema_sidecar = false;
+    let schema = infer_schema_from_store(&store, &files, &options, None).await?;
+    let sidecar = sidecar_location(&first.location)?;
+    store
+        .put(&sidecar, PutPayload::from(sidecar_json(&schema)?))
+        .await
+        .map_err(CsvError::Store)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SessionContextCsvExt;
+    use datafusion::prelude::SessionContext;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_schema_sidecar() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("stores.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id,zip").unwrap();
+        writeln!(file, "1,02139").unwrap();
+        let path = csv_path.to_str().unwrap();
+        let sidecar_path = temp_dir.path().join("stores.csv.schema.json");
+
+        let ctx = SessionContext::new();
+        ctx.write_csv_schema_sidecar(path, CsvFormatOptions::default()).await?;
+        let written = std::fs::read_to_string(&sidecar_path).unwrap();
+        assert!(written.contains("\"type\": \"Int64\""), "{}", written);
+
+        // The sidecar takes precedence over inference
+        std::fs::write(
+            &sidecar_path,
+            r#"{"columns": [{"name": "id", "type": "Int64", "nullable": false}, {"name": "zip", "type": "Utf8"}]}"#,
+        )
+        .unwrap();
+        let options = CsvFormatOptions::new().with_schema_sidecar(true);
+        ctx.register_csv_with_options("stores", path, options.clone()).await?;
+        let df = ctx.sql("SELECT zip FROM stores").await?;
+        let zip = df.schema().field(0).clone();
+        assert_eq!(zip.data_type(), &DataType::Utf8);
+        let batches = df.collect().await?;
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert!(formatted.contains("| 02139 |"), "{}", formatted);
+
+        std::fs::write(&sidecar_path, r#"{"columns": [{"name": "id", "type": "Date32"}]}"#).unwrap();
+        let err = ctx
+            .register_csv_with_options("bad", path, options)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("column 'id' cannot be read as Date32"), "{}", err);
+
+        Ok(())
+    }
+}
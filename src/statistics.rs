@@ -0,0 +1,194 @@
+//! Statistics collection for query planning
+//!
+//! DataFusion plans CSV scans without statistics, because a file's row count
+//! and value ranges are unknown until it is read. [`analyze_table`] reads every
+//! file of a registered table once and attaches exact row counts, null counts
+//! and min/max values to the table, in the spirit of SQL's `ANALYZE TABLE`.
+//! The optimizer then uses them, e.g. to answer `COUNT(*)` without scanning or
+//! to order joins.
+
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use arrow_schema::{DataType, SchemaRef};
+use datafusion::datasource::listing::{ListingTable, ListingTableConfig};
+use datafusion::datasource::TableProvider;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::cache::cache_unit::DefaultFileStatisticsCache;
+use datafusion::execution::cache::CacheAccessor;
+use datafusion::execution::context::SessionState;
+use datafusion::functions_aggregate::min_max::{MaxAccumulator, MinAccumulator};
+use datafusion::logical_expr::Accumulator;
+use datafusion::physical_plan::{ColumnStatistics, Statistics};
+use datafusion::prelude::SessionContext;
+use datafusion_common::stats::Precision;
+use futures::TryStreamExt;
+use object_store::{ObjectMeta, ObjectStore};
+
+use crate::file_format::{CsvFormat, CsvFormatOptions};
+use crate::file_source::MultiStoreCsvTable;
+use crate::physical_exec;
+
+/// Collect exact statistics for every file of table `name` and re-register it with them
+///
+/// Statistics are keyed by each file's size and modification time: files
+/// that change or appear later fall back to estimated row counts until the
+/// table is analyzed again.
+pub(crate) async fn analyze_table(ctx: &SessionContext, name: &str) -> Result<()> {
+    let provider = ctx.table_provider(name).await?;
+    let analyzed = analyze_provider(&ctx.state(), provider).await?.ok_or_else(|| {
+        DataFusionError::Plan(format!(
+            "Cannot analyze '{}': only tables registered by datafusion-csv are supported",
+            name
+        ))
+    })?;
+    ctx.deregister_table(name)?;
+    ctx.register_table(name, analyzed)?;
+    Ok(())
+}
+
+/// A copy of `provider` carrying exact statistics for every file, or `None` if it isn't a CSV table
+pub(crate) async fn analyze_provider(
+    state: &SessionState,
+    provider: Arc<dyn TableProvider>,
+) -> Result<Option<Arc<dyn TableProvider>>> {
+    if let Some(table) = provider.as_any().downcast_ref::<ListingTable>() {
+        let analyzed = analyze_listing_table(state, table).await?;
+        return Ok(analyzed.map(|table| Arc::new(table) as Arc<dyn TableProvider>));
+    }
+    let Some(table) = provider.as_any().downcast_ref::<MultiStoreCsvTable>() else {
+        return Ok(None);
+    };
+    let mut tables = Vec::with_capacity(table.tables().len());
+    for table in table.tables() {
+        let Some(analyzed) = analyze_listing_table(state, table).await? else {
+            return Ok(None);
+        };
+        tables.push(analyzed);
+    }
+    Ok(Some(Arc::new(MultiStoreCsvTable::new(tables))))
+}
+
+/// A copy of `table` carrying exact statistics for every file, or `None` if it doesn't read CSV
+async fn analyze_listing_table(state: &SessionState, table: &ListingTable) -> Result<Option<ListingTable>> {
+    let listing_options = table.options();
+    let Some(format) = listing_options.format.as_any().downcast_ref::<CsvFormat>() else {
+        return Ok(None);
+    };
+
+    // The table schema ends with the partition columns, which files don't contain
+    let table_schema = table.schema();
+    let file_columns = table_schema.fields().len() - listing_options.table_partition_cols.len();
+    let file_schema = Arc::new(table_schema.project(&(0..file_columns).collect::<Vec<_>>())?);
+
+    let cache = Arc::new(DefaultFileStatisticsCache::default());
+    for table_url in table.table_paths() {
+        let store = state.runtime_env().object_store(table_url)?;
+        let files: Vec<ObjectMeta> = table_url
+            .list_all_files(state, store.as_ref(), &listing_options.file_extension)
+            .await?
+            .try_collect()
+            .await?;
+        for meta in files {
+            let statistics = file_statistics(&store, &meta, &file_schema, format.options()).await?;
+            cache.put_with_extra(&meta.location, Arc::new(statistics), &meta);
+        }
+    }
+
+    let config = ListingTableConfig::new_with_multi_paths(table.table_paths().clone())
+        .with_listing_options(listing_options.clone())
+        .with_schema(file_schema);
+    Ok(Some(ListingTable::try_new(config)?.with_cache(Some(cache))))
+}
+
+/// Read one file and compute its exact statistics
+async fn file_statistics(
+    store: &Arc<dyn ObjectStore>,
+    meta: &ObjectMeta,
+    schema: &SchemaRef,
+    options: &CsvFormatOptions,
+) -> Result<Statistics> {
+    let bytes = physical_exec::fetch_decompressed(store, &meta.location, options).await?;
+    let (schema, options, location) = (schema.clone(), options.clone(), meta.location.to_string());
+
+    // Parsing is CPU-bound, so keep it off the async workers
+    tokio::task::spawn_blocking(move || {
+        let mut columns: Vec<ColumnSummary> = schema
+            .fields()
+            .iter()
+            .map(|field| ColumnSummary::new(field.name(), field.data_type(), &options))
+            .collect::<Result<_>>()?;
+        let mut rows = 0;
+        physical_exec::scan_in_memory(bytes, schema.clone(), &options, &location, |batch| {
+            rows += batch.num_rows();
+            for (column, summary) in columns.iter_mut().enumerate() {
+                summary.update(&batch, column)?;
+            }
+            Ok(())
+        })?;
+
+        Ok(Statistics {
+            num_rows: Precision::Exact(rows),
+            total_byte_size: Precision::Absent,
+            column_statistics: columns.into_iter().map(ColumnSummary::finish).collect::<Result<_>>()?,
+        })
+    })
+    .await
+    .map_err(|e| DataFusionError::Execution(format!("Statistics pass failed: {}", e)))?
+}
+
+/// Running null count and value range of one column
+struct ColumnSummary {
+    /// `None` for columns the scan fills rather than reads, which get no statistics
+    nulls: Option<usize>,
+    range: Option<(MinAccumulator, MaxAccumulator)>,
+}
+
+impl ColumnSummary {
+    fn new(name: &str, data_type: &DataType, options: &CsvFormatOptions) -> Result<Self> {
+        if options.is_virtual_column(name) {
+            return Ok(Self { nulls: None, range: None });
+        }
+        let ordered = data_type.is_numeric()
+            || matches!(
+                data_type,
+                DataType::Utf8 | DataType::Boolean | DataType::Date32 | DataType::Timestamp(_, _)
+            );
+        let range = if ordered {
+            Some((MinAccumulator::try_new(data_type)?, MaxAccumulator::try_new(data_type)?))
+        } else {
+            None
+        };
+        Ok(Self { nulls: Some(0), range })
+    }
+
+    fn update(&mut self, batch: &RecordBatch, column: usize) -> Result<()> {
+        let Some(nulls) = &mut self.nulls else {
+            return Ok(());
+        };
+        let array = batch.column(column);
+        *nulls += array.null_count();
+        if let Some((min, max)) = &mut self.range {
+            min.update_batch(std::slice::from_ref(array))?;
+            max.update_batch(std::slice::from_ref(array))?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<ColumnStatistics> {
+        let Some(nulls) = self.nulls else {
+            return Ok(ColumnStatistics::new_unknown());
+        };
+        let mut statistics = ColumnStatistics::new_unknown();
+        statistics.null_count = Precision::Exact(nulls);
+        if let Some((mut min, mut max)) = self.range {
+            // All-null columns have no range
+            let (min, max) = (min.evaluate()?, max.evaluate()?);
+            if !min.is_null() {
+                statistics.min_value = Precision::Exact(min);
+                statistics.max_value = Precision::Exact(max);
+            }
+        }
+        Ok(statistics)
+    }
+}
@@ -0,0 +1,198 @@
+//! Substrait plans over CSV tables
+//!
+//! Substrait refers to tables by name only, so a consumer must already have
+//! every table a plan reads. [`to_substrait_plan`] produces a plan with
+//! `datafusion-substrait` and records the paths and [`CsvFormatOptions`] of
+//! each CSV table it scans in the plan's advanced extensions;
+//! [`from_substrait_plan`] registers the tables the consuming session lacks
+//! from that record before converting the plan back:
+//!
+//! ```no_run
+//! use datafusion::prelude::*;
+//! use datafusion_csv::{substrait, SessionContextCsvExt};
+//!
+//! # async fn example() -> datafusion_common::Result<()> {
+//! let ctx = SessionContext::new();
+//! ctx.register_csv_file("orders", "data/orders.csv").await?;
+//! let plan = ctx.sql("SELECT * FROM orders").await?.into_optimized_plan()?;
+//! let substrait_plan = substrait::to_substrait_plan(&ctx, &plan).await?;
+//!
+//! // In another session, or after sending the plan elsewhere
+//! let other = SessionContext::new();
+//! let plan = substrait::from_substrait_plan(&other, &substrait_plan).await?;
+//! let batches = other.execute_logical_plan(plan).await?.collect().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Consumers other than this crate ignore the extension and resolve table
+//! names as usual. Tables whose options hold closures or dead-letter sinks
+//! cannot be recorded, and producing a plan over them fails.
+
+use datafusion::common::tree_node::TreeNodeRecursion;
+use datafusion::datasource::listing::ListingTable;
+use datafusion::datasource::{source_as_provider, TableProvider};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::prelude::SessionContext;
+use datafusion_substrait::logical_plan::{consumer, producer};
+use datafusion_substrait::substrait::proto::Plan;
+use serde_json::{json, Value};
+
+use crate::file_format::{CsvFormat, CsvFormatOptions};
+use crate::file_source::{CsvSourceBuilder, MultiStoreCsvTable};
+use crate::options_json::{options_from_json, options_to_json};
+
+/// Type URL of the advanced extension listing a plan's CSV tables
+pub const CSV_TABLES_TYPE_URL: &str = "datafusion-csv/tables";
+
+/// Convert `plan` to Substrait, recording the CSV tables it reads
+pub async fn to_substrait_plan(ctx: &SessionContext, plan: &LogicalPlan) -> Result<Box<Plan>> {
+    let mut substrait_plan = producer::to_substrait_plan(plan, ctx)?;
+
+    let tables = csv_tables(plan)?;
+    if !tables.is_empty() {
+        let tables = Value::Array(tables).to_string();
+        let extension = substrait_plan.advanced_extensions.get_or_insert_with(Default::default);
+        extension.optimization.push(Default::default());
+        let record = extension.optimization.last_mut().expect("just pushed");
+        record.type_url = CSV_TABLES_TYPE_URL.to_string();
+        record.value = tables.into_bytes().into();
+    }
+    Ok(substrait_plan)
+}
+
+/// Convert a Substrait plan to a logical plan, first registering the CSV tables it records
+///
+/// Tables already registered under a recorded name are left as they are.
+pub async fn from_substrait_plan(ctx: &SessionContext, plan: &Plan) -> Result<LogicalPlan> {
+    let records = plan
+        .advanced_extensions
+        .iter()
+        .flat_map(|extension| &extension.optimization)
+        .filter(|record| record.type_url == CSV_TABLES_TYPE_URL);
+    for record in records {
+        let tables: Value = serde_json::from_slice(&record.value).map_err(|e| {
+            DataFusionError::Substrait(format!("Invalid CSV tables in Substrait plan: {}", e))
+        })?;
+        for table in tables.as_array().into_iter().flatten() {
+            register_table(ctx, table).await?;
+        }
+    }
+    consumer::from_substrait_plan(ctx, plan).await
+}
+
+/// Name, paths and options of every CSV table scanned by `plan`
+fn csv_tables(plan: &LogicalPlan) -> Result<Vec<Value>> {
+    let mut tables = Vec::new();
+    plan.apply_with_subqueries(|node| {
+        if let LogicalPlan::TableScan(scan) = node {
+            let provider = source_as_provider(&scan.source)?;
+            if let Some((paths, options)) = csv_table_source(provider.as_ref()) {
+                let table = json!({
+                    "name": scan.table_name.to_string(),
+                    "paths": paths,
+                    "options": options_to_json(options)?,
+                });
+                // Self-joins scan the same table more than once
+                if !tables.contains(&table) {
+                    tables.push(table);
+                }
+            }
+        }
+        Ok(TreeNodeRecursion::Continue)
+    })?;
+    Ok(tables)
+}
+
+/// Paths and options of a table created by this crate
+fn csv_table_source(provider: &dyn TableProvider) -> Option<(Vec<String>, &CsvFormatOptions)> {
+    let listing_tables: Vec<&ListingTable> = match provider.as_any().downcast_ref::<MultiStoreCsvTable>() {
+        Some(table) => table.tables().iter().collect(),
+        None => vec![provider.as_any().downcast_ref::<ListingTable>()?],
+    };
+    let format = listing_tables[0].options().format.as_any().downcast_ref::<CsvFormat>()?;
+    let paths = listing_tables
+        .iter()
+        .flat_map(|table| table.table_paths())
+        .map(|url| url.as_str().to_string())
+        .collect();
+    Some((paths, format.options()))
+}
+
+/// Register a table recorded by [`csv_tables`] unless `ctx` already has one by its name
+async fn register_table(ctx: &SessionContext, table: &Value) -> Result<()> {
+    let invalid = || DataFusionError::Substrait(format!("Invalid CSV table in Substrait plan: {}", table));
+    let name = table.get("name").and_then(Value::as_str).ok_or_else(invalid)?;
+    if ctx.table_exist(name)? {
+        return Ok(());
+    }
+    let paths = table
+        .get("paths")
+        .and_then(Value::as_array)
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|path| path.as_str().ok_or_else(invalid))
+        .collect::<Result<Vec<_>>>()?;
+    let Some((first, rest)) = paths.split_first() else {
+        return Err(invalid());
+    };
+    let options = options_from_json(table.get("options").ok_or_else(invalid)?)?;
+
+    let mut builder = CsvSourceBuilder::new(*first).with_options(options);
+    for path in rest {
+        builder = builder.with_additional_path(*path);
+    }
+    ctx.register_table(name, builder.build(&ctx.state()).await?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    use crate::SessionContextCsvExt;
+
+    #[tokio::test]
+    async fn test_substrait_round_trip() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("orders.csv");
+        let mut file = File::create(&csv_path).unwrap();
+        writeln!(file, "id;amount").unwrap();
+        for i in 0..10 {
+            writeln!(file, "{};{}", i, i * 10).unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        ctx.register_csv_with_delimiter("orders", csv_path.to_str().unwrap(), b';')
+            .await?;
+        let plan = ctx
+            .sql("SELECT count(*) AS n FROM orders WHERE amount >= 50")
+            .await?
+            .into_optimized_plan()?;
+        let substrait_plan = to_substrait_plan(&ctx, &plan).await?;
+
+        // A fresh session registers the table with the same delimiter
+        let other = SessionContext::new();
+        let plan = from_substrait_plan(&other, &substrait_plan).await?;
+        let batches = other.execute_logical_plan(plan).await?.collect().await?;
+        let n = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow_array::Int64Array>()
+            .unwrap()
+            .value(0);
+        assert_eq!(n, 5);
+
+        // Consumers without the record still see a plain Substrait plan
+        let mut bare = substrait_plan.as_ref().clone();
+        bare.advanced_extensions = None;
+        let err = from_substrait_plan(&SessionContext::new(), &bare).await.unwrap_err();
+        assert!(err.to_string().contains("orders"), "{}", err);
+
+        Ok(())
+    }
+}
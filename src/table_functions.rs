@@ -0,0 +1,282 @@
+//! Table functions for exploring CSV files from SQL
+//!
+//! [`CsvSampleFunc`] implements `csv_sample('path', fraction)`, which returns
+//! an approximate random sample of a local file without reading all of it.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use datafusion::prelude::SessionContext;
+//! use datafusion_csv::CsvSampleFunc;
+//!
+//! # async fn example() -> datafusion::error::Result<()> {
+//! let ctx = SessionContext::new();
+//! ctx.register_udtf("csv_sample", Arc::new(CsvSampleFunc::default()));
+//! let df = ctx.sql("SELECT * FROM csv_sample('data/events.csv', 0.01)").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use datafusion::catalog::TableProvider;
+use datafusion::datasource::function::TableFunctionImpl;
+use datafusion::datasource::MemTable;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::Expr;
+use datafusion_common::ScalarValue;
+
+use crate::file_format::{detect_file_extension, CsvCompression, CsvFormatOptions};
+use crate::physical_exec;
+use crate::testing::SplitMix64;
+
+/// Bytes read per sampled record range
+const SAMPLE_RANGE_SIZE: u64 = 64 * 1024;
+
+/// `csv_sample('path', fraction)`: an approximate random sample of a local CSV file
+///
+/// The file is split into 64 KiB ranges and enough randomly chosen ranges are
+/// read to cover `fraction` of it, so a 1% sample of a multi-GB file reads
+/// about 1% of its bytes. Files too small to split are read whole and sampled
+/// record by record. Ranges are split on newlines, so a quoted value that
+/// spans lines may be cut at a range boundary. The schema is inferred from
+/// the sample. Compressed files cannot be sampled.
+#[derive(Debug, Clone)]
+pub struct CsvSampleFunc {
+    options: CsvFormatOptions,
+    seed: u64,
+}
+
+impl Default for CsvSampleFunc {
+    fn default() -> Self {
+        Self::new(CsvFormatOptions::default())
+    }
+}
+
+impl CsvSampleFunc {
+    /// Create the function, reading files with `options`
+    pub fn new(options: CsvFormatOptions) -> Self {
+        Self { options, seed: 0 }
+    }
+
+    /// Set the random seed; the same seed always picks the same records
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Read the header and the sampled records of `path`
+    fn sample(&self, path: &str, fraction: f64) -> Result<Vec<u8>> {
+        let io_err = |e: std::io::Error| {
+            DataFusionError::Execution(format!("Failed to read '{}': {}", path, e))
+        };
+        let file = File::open(path).map_err(io_err)?;
+        let size = file.metadata().map_err(io_err)?.len();
+        let mut reader = BufReader::new(file);
+        let mut rng = SplitMix64(self.seed);
+
+        let mut output = Vec::new();
+        if self.options.has_header {
+            reader.read_until(b'\n', &mut output).map_err(io_err)?;
+            terminate_line(&mut output);
+        }
+        let data_start = output.len() as u64;
+
+        let ranges = (fraction * size as f64 / SAMPLE_RANGE_SIZE as f64).round() as u64;
+        if ranges == 0 {
+            read_lines(&mut reader, data_start, size, &mut output, || rng.chance(fraction))
+                .map_err(io_err)?;
+            return Ok(output);
+        }
+
+        // Pick distinct ranges with a partial Fisher-Yates shuffle
+        let count = size.div_ceil(SAMPLE_RANGE_SIZE);
+        let mut indices: Vec<u64> = (0..count).collect();
+        for i in 0..ranges.min(count) as usize {
+            let j = i + (rng.next() % (count - i as u64)) as usize;
+            indices.swap(i, j);
+        }
+        let mut chosen = indices[..ranges.min(count) as usize].to_vec();
+        chosen.sort_unstable();
+
+        for index in chosen {
+            let start = (index * SAMPLE_RANGE_SIZE).max(data_start);
+            let end = ((index + 1) * SAMPLE_RANGE_SIZE).min(size);
+            if start >= end {
+                continue;
+            }
+            read_lines(&mut reader, start, end, &mut output, || true).map_err(io_err)?;
+        }
+        Ok(output)
+    }
+}
+
+impl TableFunctionImpl for CsvSampleFunc {
+    fn call(&self, args: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let (path, fraction) = match args {
+            [Expr::Literal(ScalarValue::Utf8(Some(path))), Expr::Literal(fraction)] => {
+                let fraction = match fraction {
+                    ScalarValue::Float64(Some(f)) => *f,
+                    ScalarValue::Int64(Some(i)) => *i as f64,
+                    _ => f64::NAN,
+                };
+                (path.as_str(), fraction)
+            }
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "csv_sample expects a path and a fraction, e.g. csv_sample('data.csv', 0.01)"
+                        .to_string(),
+                ));
+            }
+        };
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(DataFusionError::Plan(format!(
+                "csv_sample fraction must be in (0, 1], got {}",
+                fraction
+            )));
+        }
+
+        let path = path.strip_prefix("file://").unwrap_or(path);
+        if path.contains("://") {
+            return Err(DataFusionError::Plan(format!(
+                "csv_sample reads local files only, got '{}'",
+                path
+            )));
+        }
+        let compressed = detect_file_extension(path)
+            .and_then(|ext| CsvCompression::from_extension(&ext))
+            .is_some_and(|c| c != CsvCompression::Uncompressed);
+        if compressed || self.options.compression != CsvCompression::Uncompressed {
+            return Err(DataFusionError::Plan(format!(
+                "csv_sample cannot skip through compressed files; decompress '{}' first",
+                path
+            )));
+        }
+
+        let sample = self.sample(path, fraction)?;
+        let (schema, batches) = physical_exec::read_in_memory(Bytes::from(sample), &self.options, path)?;
+        Ok(Arc::new(MemTable::try_new(schema, vec![batches])?))
+    }
+}
+
+/// Append the lines starting in `start..end` for which `keep` returns true
+///
+/// A line cut by `start` belongs to the previous range and is skipped.
+fn read_lines(
+    reader: &mut BufReader<File>,
+    start: u64,
+    end: u64,
+    output: &mut Vec<u8>,
+    mut keep: impl FnMut() -> bool,
+) -> std::io::Result<()> {
+    let mut position = start;
+    let mut line = Vec::new();
+    if start > 0 {
+        // Step back one byte so a line starting exactly at `start` is kept
+        reader.seek(SeekFrom::Start(start - 1))?;
+        position = start - 1 + reader.read_until(b'\n', &mut line)? as u64;
+    } else {
+        reader.seek(SeekFrom::Start(0))?;
+    }
+
+    while position < end {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        position += read as u64;
+        if keep() {
+            output.extend_from_slice(&line);
+            terminate_line(output);
+        }
+    }
+    Ok(())
+}
+
+/// Make sure `output` ends with a newline
+fn terminate_line(output: &mut Vec<u8>) {
+    if output.last().is_some_and(|&b| b != b'\n') {
+        output.push(b'\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_csv_sample() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("big.csv");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "id,name").unwrap();
+        for i in 0..200_000 {
+            writeln!(file, "{},name_{}", i, i).unwrap();
+        }
+        drop(file);
+
+        let ctx = SessionContext::new();
+        ctx.register_udtf("csv_sample", Arc::new(CsvSampleFunc::default()));
+
+        let sql = format!(
+            "SELECT count(*) AS n, count(DISTINCT id) AS d, min(id) AS lo FROM csv_sample('{}', 0.05)",
+            path.to_str().unwrap()
+        );
+        let batches = ctx.sql(&sql).await?.collect().await?;
+        let value = |i: usize| {
+            batches[0]
+                .column(i)
+                .as_any()
+                .downcast_ref::<arrow_array::Int64Array>()
+                .unwrap()
+                .value(0)
+        };
+        // Whole records only, roughly 5% of them
+        assert!((5_000..15_000).contains(&value(0)), "sampled {} rows", value(0));
+        assert_eq!(value(0), value(1));
+        assert!(value(2) >= 0);
+
+        let err = ctx
+            .sql(&format!("SELECT * FROM csv_sample('{}', 2.0)", path.to_str().unwrap()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("fraction must be in (0, 1]"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_csv_sample_small_file() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("small.csv");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "id,value").unwrap();
+        for i in 0..1_000 {
+            writeln!(file, "{},{}.5", i, i).unwrap();
+        }
+        drop(file);
+
+        let func = CsvSampleFunc::default().with_seed(7);
+        let args = [
+            Expr::Literal(ScalarValue::Utf8(Some(path.to_str().unwrap().to_string()))),
+            Expr::Literal(ScalarValue::Float64(Some(0.1))),
+        ];
+        let table = func.call(&args)?;
+        assert_eq!(
+            table.schema().field(1).data_type(),
+            &arrow_schema::DataType::Float64
+        );
+
+        let ctx = SessionContext::new();
+        let rows = ctx.read_table(table)?.count().await?;
+        assert!((50..150).contains(&rows), "sampled {} rows", rows);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+//! Scan metrics for monitoring long-running services
+//!
+//! With the `tracing` feature, every file a scan reads emits one event on the
+//! `datafusion_csv::metrics` target when the file is done (finished, failed or
+//! abandoned by a `LIMIT`):
+//!
+//! | Field | Meaning |
+//! |-------|---------|
+//! | `file` | Location of the file |
+//! | `monotonic_counter.csv_rows_parsed` | Records parsed |
+//! | `monotonic_counter.csv_bytes_read` | Bytes fetched from the object store |
+//! | `monotonic_counter.csv_parse_errors` | 1 if the file failed to parse, else 0 |
+//! | `histogram.csv_scan_duration_seconds` | Time from opening to finishing the file |
+//!
+//! The field prefixes follow `tracing-opentelemetry`'s `MetricsLayer`, which
+//! turns them into OpenTelemetry counters and histograms; any other
+//! `tracing` subscriber can read the same fields. Without the feature these
+//! types compile to nothing.
+
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+/// Metrics of one file's scan, emitted when dropped
+#[derive(Debug)]
+pub(crate) struct FileScanTelemetry {
+    #[cfg(feature = "tracing")]
+    location: String,
+    #[cfg(feature = "tracing")]
+    started: Instant,
+    #[cfg(feature = "tracing")]
+    rows: u64,
+    #[cfg(feature = "tracing")]
+    bytes: u64,
+    #[cfg(feature = "tracing")]
+    failed: bool,
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+impl FileScanTelemetry {
+    pub(crate) fn new(location: &str, bytes: usize) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            location: location.to_string(),
+            #[cfg(feature = "tracing")]
+            started: Instant::now(),
+            #[cfg(feature = "tracing")]
+            rows: 0,
+            #[cfg(feature = "tracing")]
+            bytes: bytes as u64,
+            #[cfg(feature = "tracing")]
+            failed: false,
+        }
+    }
+
+    /// Report `bytes` as the file's size in the store, before decompression
+    pub(crate) fn set_bytes_read(&mut self, bytes: usize) {
+        #[cfg(feature = "tracing")]
+        {
+            self.bytes = bytes as u64;
+        }
+    }
+
+    pub(crate) fn add_rows(&mut self, rows: usize) {
+        #[cfg(feature = "tracing")]
+        {
+            self.rows += rows as u64;
+        }
+    }
+
+    pub(crate) fn record_error(&mut self) {
+        #[cfg(feature = "tracing")]
+        {
+            self.failed = true;
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for FileScanTelemetry {
+    fn drop(&mut self) {
+        tracing::info!(
+            target: "datafusion_csv::metrics",
+            file = %self.location,
+            monotonic_counter.csv_rows_parsed = self.rows,
+            monotonic_counter.csv_bytes_read = self.bytes,
+            monotonic_counter.csv_parse_errors = u64::from(self.failed),
+            histogram.csv_scan_duration_seconds = self.started.elapsed().as_secs_f64(),
+            "CSV file scanned"
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Synthetic CSV data for testing pipelines
+//!
+//! [`CsvGenerator`] produces CSV files with a configurable schema and row
+//! count, optionally injecting the kinds of dirty data found in real exports:
+//! values of the wrong type, rows with missing or extra fields, invalid UTF-8,
+//! byte-order marks and Windows line endings. Output can be compressed with
+//! any [`CsvCompression`] codec. Generation is deterministic for a given seed.
+//!
+//! ```no_run
+//! use arrow_schema::DataType;
+//! use datafusion_csv::testing::CsvGenerator;
+//!
+//! # async fn example() -> datafusion_common::Result<()> {
+//! CsvGenerator::new()
+//!     .with_column("id", DataType::Int64)
+//!     .with_column("price", DataType::Float64)
+//!     .with_column("name", DataType::Utf8)
+//!     .with_rows(10_000)
+//!     .with_bad_type_rate(0.01)
+//!     .write_to("/tmp/dirty.csv")
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use arrow_schema::DataType;
+use datafusion::error::{DataFusionError, Result};
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "bzip2"))]
+use tokio::io::AsyncReadExt;
+
+use crate::file_format::CsvCompression;
+
+/// Sample text values, including ones that need quoting
+const TEXT_VALUES: &[&str] = &[
+    "alpha",
+    "Bravo Charlie",
+    "O'Brien",
+    "comma, inside",
+    "quote \"inside\"",
+    "line\nbreak",
+    "ünïcødé",
+    "",
+];
+
+/// Values injected into typed columns to simulate bad data
+const BAD_VALUES: &[&str] = &["N/A", "abc", "1.2.3", "#REF!", "--", "tru"];
+
+/// Generator for synthetic CSV files
+#[derive(Debug, Clone)]
+pub struct CsvGenerator {
+    columns: Vec<(String, DataType)>,
+    rows: usize,
+    delimiter: u8,
+    has_header: bool,
+    seed: u64,
+    null_rate: f64,
+    bad_type_rate: f64,
+    ragged_row_rate: f64,
+    invalid_utf8_rate: f64,
+    byte_order_mark: bool,
+    crlf: bool,
+    compression: CsvCompression,
+}
+
+impl Default for CsvGenerator {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            rows: 100,
+            delimiter: b',',
+            has_header: true,
+            seed: 42,
+            null_rate: 0.0,
+            bad_type_rate: 0.0,
+            ragged_row_rate: 0.0,
+            invalid_utf8_rate: 0.0,
+            byte_order_mark: false,
+            crlf: false,
+            compression: CsvCompression::Uncompressed,
+        }
+    }
+}
+
+impl CsvGenerator {
+    /// Create a generator with no columns, 100 rows and seed 42
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a column; supported types are Int64, Float64, Boolean, Utf8 and Date32
+    pub fn with_column(mut self, name: impl Into<String>, data_type: DataType) -> Self {
+        self.columns.push((name.into(), data_type));
+        self
+    }
+
+    /// Set the number of data rows
+    pub fn with_rows(mut self, rows: usize) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    /// Set the delimiter
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set whether a header row is written
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Set the random seed; the same seed always produces the same output
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Fraction of values written as empty fields
+    pub fn with_null_rate(mut self, rate: f64) -> Self {
+        self.null_rate = rate;
+        self
+    }
+
+    /// Fraction of non-text values replaced with text that does not parse
+    pub fn with_bad_type_rate(mut self, rate: f64) -> Self {
+        self.bad_type_rate = rate;
+        self
+    }
+
+    /// Fraction of rows with a field missing or an extra field appended
+    pub fn with_ragged_row_rate(mut self, rate: f64) -> Self {
+        self.ragged_row_rate = rate;
+        self
+    }
+
+    /// Fraction of text values containing bytes that are not valid UTF-8
+    pub fn with_invalid_utf8_rate(mut self, rate: f64) -> Self {
+        self.invalid_utf8_rate = rate;
+        self
+    }
+
+    /// Start the output with a UTF-8 byte-order mark
+    pub fn with_byte_order_mark(mut self, byte_order_mark: bool) -> Self {
+        self.byte_order_mark = byte_order_mark;
+        self
+    }
+
+    /// End lines with `\r\n` instead of `\n`
+    pub fn with_crlf(mut self, crlf: bool) -> Self {
+        self.crlf = crlf;
+        self
+    }
+
+    /// Compress the output
+    pub fn with_compression(mut self, compression: CsvCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Generate the file contents, compressed if configured
+    pub async fn generate(&self) -> Result<Vec<u8>> {
+        let text = self.generate_uncompressed()?;
+        compress(&text, self.compression).await
+    }
+
+    /// Generate the file and write it to `path`
+    pub async fn write_to(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let bytes = self.generate().await?;
+        tokio::fs::write(path.as_ref(), bytes).await.map_err(DataFusionError::IoError)
+    }
+
+    fn generate_uncompressed(&self) -> Result<Vec<u8>> {
+        if self.columns.is_empty() {
+            return Err(DataFusionError::Configuration(
+                "CsvGenerator needs at least one column".to_string(),
+            ));
+        }
+
+        let mut rng = SplitMix64(self.seed);
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .flexible(true)
+            .terminator(if self.crlf {
+                csv::Terminator::CRLF
+            } else {
+                csv::Terminator::Any(b'\n')
+            })
+            .from_writer(Vec::new());
+        let csv_err = |e: csv::Error| DataFusionError::Execution(format!("Failed to generate CSV: {}", e));
+
+        if self.has_header {
+            writer
+                .write_record(self.columns.iter().map(|(name, _)| name.as_bytes()))
+                .map_err(csv_err)?;
+        }
+
+        for row in 0..self.rows {
+            let mut record: Vec<Vec<u8>> = Vec::with_capacity(self.columns.len() + 1);
+            for (_, data_type) in &self.columns {
+                record.push(self.value(row, data_type, &mut rng)?);
+            }
+
+            if rng.chance(self.ragged_row_rate) {
+                if rng.next().is_multiple_of(2) && record.len() > 1 {
+                    record.pop();
+                } else {
+                    record.push(b"extra".to_vec());
+                }
+            }
+            writer.write_record(&record).map_err(csv_err)?;
+        }
+
+        let mut bytes = writer
+            .into_inner()
+            .map_err(|e| DataFusionError::Execution(format!("Failed to generate CSV: {}", e)))?;
+        if self.byte_order_mark {
+            bytes.splice(0..0, [0xEF, 0xBB, 0xBF]);
+        }
+        Ok(bytes)
+    }
+
+    /// Generate one field value, applying any configured dirt
+    fn value(&self, row: usize, data_type: &DataType, rng: &mut SplitMix64) -> Result<Vec<u8>> {
+        if rng.chance(self.null_rate) {
+            return Ok(Vec::new());
+        }
+
+        let is_text = *data_type == DataType::Utf8;
+        if !is_text && rng.chance(self.bad_type_rate) {
+            return Ok(rng.pick(BAD_VALUES).as_bytes().to_vec());
+        }
+
+        let value = match data_type {
+            DataType::Int64 => (row as i64 * 7 - 100 + (rng.next() % 50) as i64).to_string(),
+            DataType::Float64 => format!("{:.3}", (rng.next() % 1_000_000) as f64 / 100.0 - 5000.0),
+            DataType::Boolean => rng.next().is_multiple_of(2).to_string(),
+            DataType::Date32 => {
+                let day = chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()
+                    + chrono::Days::new(rng.next() % 2000);
+                day.format("%Y-%m-%d").to_string()
+            }
+            DataType::Utf8 => rng.pick(TEXT_VALUES).to_string(),
+            other => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "CsvGenerator does not support {} columns",
+                    other
+                )));
+            }
+        };
+
+        let mut bytes = value.into_bytes();
+        if is_text && rng.chance(self.invalid_utf8_rate) {
+            // Latin-1 "é" and a lone continuation byte
+            bytes.extend_from_slice(&[0xE9, 0x80]);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Compress `bytes` with `compression`
+#[cfg_attr(not(any(feature = "gzip", feature = "zstd", feature = "bzip2")), allow(unreachable_code, unused_mut, unused_variables))]
+pub(crate) async fn compress(bytes: &[u8], compression: CsvCompression) -> Result<Vec<u8>> {
+    compression.check_enabled()?;
+    let mut output = Vec::new();
+    let result: std::io::Result<usize> = match compression {
+        CsvCompression::Uncompressed => return Ok(bytes.to_vec()),
+        #[cfg(feature = "gzip")]
+        CsvCompression::Gzip => {
+            async_compression::tokio::bufread::GzipEncoder::new(bytes)
+                .read_to_end(&mut output)
+                .await
+        }
+        #[cfg(feature = "zstd")]
+        CsvCompression::Zstd => {
+            async_compression::tokio::bufread::ZstdEncoder::new(bytes)
+                .read_to_end(&mut output)
+                .await
+        }
+        #[cfg(feature = "bzip2")]
+        CsvCompression::Bzip2 => {
+            async_compression::tokio::bufread::BzEncoder::new(bytes)
+                .read_to_end(&mut output)
+                .await
+        }
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("disabled codecs are rejected by check_enabled"),
+    };
+    result.map_err(DataFusionError::IoError)?;
+    Ok(output)
+}
+
+/// Small deterministic PRNG, so generated data is reproducible without extra dependencies
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Return `true` with probability `rate`
+    pub(crate) fn chance(&mut self, rate: f64) -> bool {
+        let sample = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        rate > 0.0 && sample < rate
+    }
+
+    fn pick<'a>(&mut self, values: &[&'a str]) -> &'a str {
+        values[(self.next() % values.len() as u64) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CsvFormatOptions, SessionContextCsvExt};
+    use datafusion::prelude::SessionContext;
+    use tempfile::TempDir;
+
+    fn generator() -> CsvGenerator {
+        CsvGenerator::new()
+            .with_column("id", DataType::Int64)
+            .with_column("price", DataType::Float64)
+            .with_column("active", DataType::Boolean)
+            .with_column("name", DataType::Utf8)
+            .with_rows(250)
+    }
+
+    #[tokio::test]
+    async fn test_generate_clean_and_compressed() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let ctx = SessionContext::new();
+
+        let codecs = [
+            CsvCompression::Uncompressed,
+            CsvCompression::Gzip,
+            CsvCompression::Zstd,
+            CsvCompression::Bzip2,
+        ];
+        for compression in codecs.into_iter().filter(|c| c.check_enabled().is_ok()) {
+            let path = temp_dir
+                .path()
+                .join(format!("clean.csv{}", compression.file_suffix()));
+            generator().with_compression(compression).write_to(&path).await?;
+
+            let df = ctx
+                .read_csv_with_options(path.to_str().unwrap(), CsvFormatOptions::default())
+                .await?;
+            assert_eq!(
+                df.schema().field_with_unqualified_name("price")?.data_type(),
+                &DataType::Float64
+            );
+            assert_eq!(df.count().await?, 250);
+        }
+
+        // The same seed reproduces the same bytes
+        assert_eq!(generator().generate().await?, generator().generate().await?);
+        assert_ne!(generator().generate().await?, generator().with_seed(7).generate().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_generate_dirty() -> Result<()> {
+        let text = String::from_utf8(generator().with_bad_type_rate(0.2).generate().await?).unwrap();
+        assert!(BAD_VALUES.iter().any(|bad| text.contains(bad)));
+
+        let ragged = generator().with_ragged_row_rate(0.1).generate().await?;
+        let ctx = SessionContext::new();
+        let df = ctx.read_csv_str(&String::from_utf8(ragged).unwrap()).await?;
+        assert!(df.collect().await.is_err());
+
+        let encoded = generator()
+            .with_invalid_utf8_rate(0.5)
+            .with_byte_order_mark(true)
+            .with_crlf(true)
+            .generate()
+            .await?;
+        assert!(encoded.starts_with(&[0xEF, 0xBB, 0xBF]));
+        assert!(std::str::from_utf8(&encoded).is_err());
+        assert!(encoded.windows(2).any(|w| w == b"\r\n"));
+
+        Ok(())
+    }
+}